@@ -155,6 +155,11 @@ fn configure_pyo3() -> Result<()> {
         println!("cargo:rustc-cfg=addr_of");
     }
 
+    // Enable use of std::backtrace on Rust 1.65 and greater
+    if rustc_minor_version >= 65 {
+        println!("cargo:rustc-cfg=backtrace");
+    }
+
     // Extra lines come last, to support last write wins.
     for line in &interpreter_config.extra_build_script_lines {
         println!("{}", line);
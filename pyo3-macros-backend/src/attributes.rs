@@ -1,3 +1,5 @@
+use proc_macro2::TokenStream;
+use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
@@ -11,13 +13,23 @@ use crate::deprecations::{Deprecation, Deprecations};
 pub mod kw {
     syn::custom_keyword!(annotation);
     syn::custom_keyword!(attribute);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(extends);
+    syn::custom_keyword!(flatten);
     syn::custom_keyword!(from_py_with);
     syn::custom_keyword!(get);
     syn::custom_keyword!(item);
+    syn::custom_keyword!(module);
+    syn::custom_keyword!(multi_phase);
     syn::custom_keyword!(pass_module);
+    syn::custom_keyword!(panic);
+    syn::custom_keyword!(raise_as);
     syn::custom_keyword!(name);
+    syn::custom_keyword!(rename_all);
     syn::custom_keyword!(set);
     syn::custom_keyword!(signature);
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(tag);
     syn::custom_keyword!(text_signature);
     syn::custom_keyword!(transparent);
 }
@@ -46,6 +58,79 @@ impl Parse for NameAttribute {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenamingRule {
+    CamelCase,
+    KebabCase,
+    Lowercase,
+    PascalCase,
+    ScreamingKebabCase,
+    ScreamingSnakeCase,
+    SnakeCase,
+    Uppercase,
+}
+
+impl RenamingRule {
+    /// Applies the rule to a Rust identifier, which is assumed to be in `snake_case`.
+    pub fn apply(self, name: &str) -> String {
+        let words = name.split('_').filter(|word| !word.is_empty());
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        };
+        match self {
+            RenamingRule::CamelCase => words
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.to_owned()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            RenamingRule::KebabCase => words.collect::<Vec<_>>().join("-"),
+            RenamingRule::Lowercase => name.to_lowercase(),
+            RenamingRule::PascalCase => words.map(capitalize).collect(),
+            RenamingRule::ScreamingKebabCase => words.collect::<Vec<_>>().join("-").to_uppercase(),
+            RenamingRule::ScreamingSnakeCase => name.to_uppercase(),
+            RenamingRule::SnakeCase => name.to_owned(),
+            RenamingRule::Uppercase => name.to_uppercase(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameAllAttribute {
+    pub kw: kw::rename_all,
+    pub rule: RenamingRule,
+}
+
+impl Parse for RenameAllAttribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let kw: kw::rename_all = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let string_literal: LitStr = input.parse()?;
+        let rule = match string_literal.value().as_ref() {
+            "camelCase" => RenamingRule::CamelCase,
+            "kebab-case" => RenamingRule::KebabCase,
+            "lowercase" => RenamingRule::Lowercase,
+            "PascalCase" => RenamingRule::PascalCase,
+            "SCREAMING-KEBAB-CASE" => RenamingRule::ScreamingKebabCase,
+            "SCREAMING_SNAKE_CASE" => RenamingRule::ScreamingSnakeCase,
+            "snake_case" => RenamingRule::SnakeCase,
+            "UPPERCASE" => RenamingRule::Uppercase,
+            _ => {
+                bail_spanned!(string_literal.span() => "expected one of: \"camelCase\", \"kebab-case\", \"lowercase\", \"PascalCase\", \"SCREAMING-KEBAB-CASE\", \"SCREAMING_SNAKE_CASE\", \"snake_case\", \"UPPERCASE\"")
+            }
+        };
+        Ok(RenameAllAttribute { kw, rule })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TextSignatureAttribute {
     pub kw: kw::text_signature,
@@ -63,6 +148,102 @@ impl Parse for TextSignatureAttribute {
     }
 }
 
+/// How panics are handled by the generated wrapper, set with `panic = "..."`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PanicPolicy {
+    /// `"raise"`: raise a `PanicException`, the default.
+    Raise,
+    /// `"abort"`: abort the process.
+    Abort,
+    /// `"raise_as = Type"`: raise an exception of the given type.
+    RaiseAs(syn::Path),
+}
+
+impl PanicPolicy {
+    /// The `pyo3::panic::PanicPolicy` implementation of the policy.
+    pub fn policy_type(&self) -> TokenStream {
+        match self {
+            PanicPolicy::Raise => quote!(::pyo3::panic::RaisePanic),
+            PanicPolicy::Abort => quote!(::pyo3::panic::AbortOnPanic),
+            PanicPolicy::RaiseAs(ty) => quote!(::pyo3::panic::RaisePanicAs<#ty>),
+        }
+    }
+
+    /// The function called by the wrapper to run its body and handle panics.
+    pub fn handle_panic(&self) -> TokenStream {
+        let policy_type = self.policy_type();
+        quote!(::pyo3::callback::handle_panic_with::<#policy_type, _, _>)
+    }
+}
+
+/// The function called by a wrapper to run its body and handle panics, according to `panic` if
+/// set, otherwise according to the `panic` option of the `#[pymodule]` which adds the class
+/// `cls`, or the function `wrapper` if there is no class.
+pub fn handle_panic(
+    panic: Option<&PanicAttribute>,
+    cls: Option<&syn::Type>,
+    wrapper: &syn::Ident,
+) -> TokenStream {
+    match (panic, cls) {
+        (Some(panic), _) => panic.policy.handle_panic(),
+        (None, Some(cls)) => quote!(::pyo3::panic::PanicScope::class::<#cls>().handle_panic),
+        (None, None) => quote! {
+            ::pyo3::panic::PanicScope::Function(#wrapper as usize).handle_panic
+        },
+    }
+}
+
+/// The function converting a panic in the future of an `async fn` to the exception to raise,
+/// chosen like in [`handle_panic`].
+pub fn panic_into_err(
+    panic: Option<&PanicAttribute>,
+    cls: Option<&syn::Type>,
+    wrapper: &syn::Ident,
+) -> TokenStream {
+    match (panic, cls) {
+        (Some(panic), _) => {
+            let policy_type = panic.policy.policy_type();
+            quote!(<#policy_type as ::pyo3::panic::PanicPolicy>::panic_into_err)
+        }
+        (None, Some(cls)) => quote! {
+            move |py, payload| ::pyo3::panic::PanicScope::class::<#cls>().panic_into_err(py, payload)
+        },
+        (None, None) => quote! {
+            move |py, payload| {
+                ::pyo3::panic::PanicScope::Function(#wrapper as usize).panic_into_err(py, payload)
+            }
+        },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PanicAttribute {
+    pub kw: kw::panic,
+    pub policy: PanicPolicy,
+}
+
+impl Parse for PanicAttribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let kw: kw::panic = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let string_literal: LitStr = input.parse()?;
+        let policy = match string_literal.value().as_ref() {
+            "raise" => PanicPolicy::Raise,
+            "abort" => PanicPolicy::Abort,
+            _ => string_literal
+                .parse_with(|input: ParseStream| {
+                    let _: kw::raise_as = input.parse()?;
+                    let _: Token![=] = input.parse()?;
+                    input.parse().map(PanicPolicy::RaiseAs)
+                })
+                .map_err(|_| {
+                    err_spanned!(string_literal.span() => "expected one of: \"raise\", \"abort\", \"raise_as = ExceptionType\"")
+                })?,
+        };
+        Ok(PanicAttribute { kw, policy })
+    }
+}
+
 pub fn get_pyo3_options<T: Parse>(attr: &syn::Attribute) -> Result<Option<Punctuated<T, Comma>>> {
     if is_attribute_ident(attr, "pyo3") {
         attr.parse_args_with(Punctuated::parse_terminated).map(Some)
@@ -176,3 +357,27 @@ pub fn take_deprecated_text_signature_attribute(
     *attrs = attrs_out;
     Ok(text_signature)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RenamingRule;
+
+    #[test]
+    fn test_renaming_rules() {
+        let name = "some_field_name";
+        assert_eq!(RenamingRule::CamelCase.apply(name), "someFieldName");
+        assert_eq!(RenamingRule::KebabCase.apply(name), "some-field-name");
+        assert_eq!(RenamingRule::Lowercase.apply(name), "some_field_name");
+        assert_eq!(RenamingRule::PascalCase.apply(name), "SomeFieldName");
+        assert_eq!(
+            RenamingRule::ScreamingKebabCase.apply(name),
+            "SOME-FIELD-NAME"
+        );
+        assert_eq!(
+            RenamingRule::ScreamingSnakeCase.apply(name),
+            "SOME_FIELD_NAME"
+        );
+        assert_eq!(RenamingRule::SnakeCase.apply(name), "some_field_name");
+        assert_eq!(RenamingRule::Uppercase.apply(name), "SOME_FIELD_NAME");
+    }
+}
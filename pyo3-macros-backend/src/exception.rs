@@ -0,0 +1,303 @@
+use crate::attributes::{self, get_pyo3_options, NameAttribute};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    DataEnum, DeriveInput, Ident, LitStr, Result, Token,
+};
+
+/// `#[pyo3()]` options on the enum deriving `PyException`.
+struct ExceptionOptions {
+    /// Python name of the base class, the enum identifier if not given.
+    name: Option<NameAttribute>,
+    /// Module in which the classes are defined, `builtins` if not given.
+    module: Option<LitStr>,
+    /// Python exception type the base class derives from, `PyException` if not given.
+    extends: Option<syn::Path>,
+}
+
+enum ExceptionOption {
+    Name(NameAttribute),
+    Module(LitStr),
+    Extends(syn::Path),
+}
+
+impl Parse for ExceptionOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(attributes::kw::name) {
+            input.parse().map(ExceptionOption::Name)
+        } else if lookahead.peek(attributes::kw::module) {
+            let _: attributes::kw::module = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            input.parse().map(ExceptionOption::Module)
+        } else if lookahead.peek(attributes::kw::extends) {
+            let _: attributes::kw::extends = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            input.parse().map(ExceptionOption::Extends)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl ExceptionOptions {
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self> {
+        let mut options = ExceptionOptions {
+            name: None,
+            module: None,
+            extends: None,
+        };
+        for attr in attrs {
+            for option in get_pyo3_options(attr)?.into_iter().flatten() {
+                match option {
+                    ExceptionOption::Name(name) => {
+                        ensure_spanned!(
+                            options.name.is_none(),
+                            name.0.span() => "`name` may only be specified once"
+                        );
+                        options.name = Some(name);
+                    }
+                    ExceptionOption::Module(module) => {
+                        ensure_spanned!(
+                            options.module.is_none(),
+                            module.span() => "`module` may only be specified once"
+                        );
+                        options.module = Some(module);
+                    }
+                    ExceptionOption::Extends(extends) => {
+                        ensure_spanned!(
+                            options.extends.is_none(),
+                            extends.span() => "`extends` may only be specified once"
+                        );
+                        options.extends = Some(extends);
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// `#[pyo3()]` options on variants and their fields.
+struct MemberOptions {
+    name: Option<NameAttribute>,
+    skip: Option<attributes::kw::skip>,
+}
+
+enum MemberOption {
+    Name(NameAttribute),
+    Skip(attributes::kw::skip),
+}
+
+impl Parse for MemberOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(attributes::kw::name) {
+            input.parse().map(MemberOption::Name)
+        } else if lookahead.peek(attributes::kw::skip) {
+            input.parse().map(MemberOption::Skip)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl MemberOptions {
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self> {
+        let mut options = MemberOptions {
+            name: None,
+            skip: None,
+        };
+        for attr in attrs {
+            for option in get_pyo3_options(attr)?.into_iter().flatten() {
+                match option {
+                    MemberOption::Name(name) => {
+                        ensure_spanned!(
+                            options.name.is_none(),
+                            name.0.span() => "`name` may only be specified once"
+                        );
+                        options.name = Some(name);
+                    }
+                    MemberOption::Skip(skip) => {
+                        ensure_spanned!(
+                            options.skip.is_none(),
+                            skip.span() => "`skip` may only be specified once"
+                        );
+                        options.skip = Some(skip);
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Builds the match arm creating the exception instance for one variant.
+///
+/// `index` is the position of the variant's class in the list of variant classes.
+fn build_variant_arm(ident: &Ident, variant: &syn::Variant, index: usize) -> Result<TokenStream> {
+    let var_ident = &variant.ident;
+    let mut bindings = Vec::new();
+    let mut set_attrs = Vec::new();
+    for (i, field) in variant.fields.iter().enumerate() {
+        let options = MemberOptions::from_attrs(&field.attrs)?;
+        // Tuple fields are exposed as `_0`, `_1`, etc, like the fields of `#[pyclass]` enums
+        let binding = match &field.ident {
+            Some(field_ident) => field_ident.clone(),
+            None => format_ident!("_{}", i),
+        };
+        if options.skip.is_some() {
+            if let Some(name) = &options.name {
+                bail_spanned!(name.0.span() => "`name` cannot be used with `skip`");
+            }
+            bindings.push(match &field.ident {
+                Some(field_ident) => quote!(#field_ident: _),
+                None => quote!(_),
+            });
+            continue;
+        }
+        let name = options
+            .name
+            .map_or_else(|| binding.unraw(), |name| name.0)
+            .to_string();
+        set_attrs.push(quote! {
+            instance.setattr(#name, ::pyo3::IntoPy::<::pyo3::PyObject>::into_py(#binding, py))?;
+        });
+        bindings.push(quote!(#binding));
+    }
+    let pattern = match &variant.fields {
+        syn::Fields::Named(_) => quote!(#ident::#var_ident { #(#bindings),* }),
+        syn::Fields::Unnamed(_) => quote!(#ident::#var_ident(#(#bindings),*)),
+        syn::Fields::Unit => quote!(#ident::#var_ident),
+    };
+    // the base class comes first
+    let item = index + 1;
+    Ok(quote! {
+        #pattern => {
+            let instance = types.get_item(#item)?.call1((message,))?;
+            #(#set_attrs)*
+            instance
+        }
+    })
+}
+
+fn build_enum(data_enum: &DataEnum, tokens: &DeriveInput) -> Result<TokenStream> {
+    let ident = &tokens.ident;
+    ensure_spanned!(
+        !data_enum.variants.is_empty(),
+        ident.span() => "cannot derive PyException for empty enum"
+    );
+    ensure_spanned!(
+        tokens.generics.params.is_empty(),
+        tokens.generics.span() => "cannot derive PyException for generic enums"
+    );
+    let options = ExceptionOptions::from_attrs(&tokens.attrs)?;
+    let base_name = options
+        .name
+        .map_or_else(|| ident.unraw(), |name| name.0)
+        .to_string();
+    let module = options
+        .module
+        .map_or_else(|| "builtins".to_string(), |module| module.value());
+    let extends = options.extends.map_or_else(
+        || quote!(::pyo3::exceptions::PyException),
+        |path| quote!(#path),
+    );
+
+    let mut variant_names = Vec::new();
+    let mut arms = Vec::new();
+    for (index, variant) in data_enum.variants.iter().enumerate() {
+        let options = MemberOptions::from_attrs(&variant.attrs)?;
+        if let Some(skip) = options.skip {
+            bail_spanned!(skip.span() => "`skip` is only supported for fields");
+        }
+        variant_names.push(
+            options
+                .name
+                .map_or_else(|| variant.ident.unraw(), |name| name.0)
+                .to_string(),
+        );
+        arms.push(build_variant_arm(ident, variant, index)?);
+    }
+    let variant_count = variant_names.len();
+    let indices = 0..variant_count;
+    let qualified_base_name = format!("{}.{}", module, base_name);
+    let qualified_variant_names = variant_names
+        .iter()
+        .map(|name| format!("{}.{}", module, name));
+
+    Ok(quote! {
+        const _: () = {
+            use ::pyo3::types::PyType;
+            use ::pyo3::{Py, PyErr, PyResult, Python};
+
+            /// The base class followed by the classes of the variants, in declaration order.
+            fn exception_types<'py>(py: Python<'py>) -> &'py ::pyo3::types::PyTuple {
+                fn new_type(py: Python, name: &str, base: &PyType) -> Py<PyType> {
+                    unsafe {
+                        Py::from_owned_ptr(
+                            py,
+                            PyErr::new_type(py, name, ::std::option::Option::Some(base), ::std::option::Option::None)
+                                .as_ptr() as *mut ::pyo3::ffi::PyObject,
+                        )
+                    }
+                }
+
+                static TYPES: ::pyo3::once_cell::GILOnceCell<Py<::pyo3::types::PyTuple>> =
+                    ::pyo3::once_cell::GILOnceCell::new();
+                TYPES.get_or_init_in_interpreter(py, || {
+                    let base = new_type(py, #qualified_base_name, py.get_type::<#extends>());
+                    let variants = ::std::vec![#(new_type(py, #qualified_variant_names, base.as_ref(py))),*];
+                    #(
+                        base.as_ref(py)
+                            .setattr(#variant_names, &variants[#indices])
+                            .expect("failed to add variant class");
+                    )*
+                    let types: ::std::vec::Vec<_> = ::std::iter::once(&base).chain(&variants).collect();
+                    ::pyo3::types::PyTuple::new(py, types).into()
+                })
+            }
+
+            unsafe impl ::pyo3::type_object::PyTypeObject for #ident {
+                fn type_object<'py>(py: Python<'py>) -> &'py PyType {
+                    let base = exception_types(py).get_item(0).expect("missing exception base class");
+                    unsafe { <PyType as ::pyo3::PyTryFrom>::try_from_unchecked(base) }
+                }
+            }
+
+            impl ::std::convert::From<#ident> for PyErr {
+                fn from(err: #ident) -> PyErr {
+                    fn to_instance<'py>(py: Python<'py>, err: #ident) -> PyResult<&'py ::pyo3::PyAny> {
+                        let types = exception_types(py);
+                        let message = ::std::string::ToString::to_string(&err);
+                        Ok(match err {
+                            #(#arms)*
+                        })
+                    }
+                    Python::with_gil(|py| match to_instance(py, err) {
+                        Ok(instance) => PyErr::from_instance(instance),
+                        Err(err) => err,
+                    })
+                }
+            }
+        };
+    })
+}
+
+/// Derive a Python exception hierarchy for an error enum.
+///
+///   * The enum becomes an exception class deriving from `PyException`, or the type given with
+///     `#[pyo3(extends = ...)]`, and is available with `Python::get_type`.
+///   * Each variant becomes a subclass of it, which is also an attribute of the base class.
+///   * The `Display` text of the error becomes the exception message, and the variant's fields
+///     are set as attributes of the exception instance, tuple fields as `_0`, `_1`, etc.
+///   * `From<Enum> for PyErr` is implemented, so the enum can be returned from `#[pyfunction]`s.
+pub fn build_derive_py_exception(tokens: &DeriveInput) -> Result<TokenStream> {
+    match &tokens.data {
+        syn::Data::Enum(en) => build_enum(en, tokens),
+        _ => bail_spanned!(tokens.span() => "#[derive(PyException)] is only supported for enums"),
+    }
+}
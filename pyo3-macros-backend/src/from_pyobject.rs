@@ -1,7 +1,10 @@
-use crate::attributes::{self, get_pyo3_options, FromPyWithAttribute};
+use crate::attributes::{
+    self, get_pyo3_options, FromPyWithAttribute, RenameAllAttribute, RenamingRule,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
+    ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream},
     parse_quote,
@@ -15,23 +18,29 @@ use syn::{
 struct Enum<'a> {
     enum_ident: &'a Ident,
     variants: Vec<Container<'a>>,
+    /// The key or attribute holding the name of the variant to extract, if any.
+    tag: Option<LitStr>,
+    /// Whether the tag is read as an attribute instead of an item.
+    tag_attribute: bool,
 }
 
 impl<'a> Enum<'a> {
     /// Construct a new enum representation.
     ///
     /// `data_enum` is the `syn` representation of the input enum, `ident` is the
-    /// `Identifier` of the enum.
-    fn new(data_enum: &'a DataEnum, ident: &'a Ident) -> Result<Self> {
+    /// `Identifier` of the enum and `options` are the options given on the enum itself.
+    fn new(data_enum: &'a DataEnum, ident: &'a Ident, options: ContainerOptions) -> Result<Self> {
         ensure_spanned!(
             !data_enum.variants.is_empty(),
             ident.span() => "cannot derive FromPyObject for empty enum"
         );
+        options.ensure_only_tag()?;
         let variants = data_enum
             .variants
             .iter()
             .map(|variant| {
                 let attrs = ContainerOptions::from_attrs(&variant.attrs)?;
+                attrs.ensure_no_tag()?;
                 let var_ident = &variant.ident;
                 Container::new(
                     &variant.fields,
@@ -45,11 +54,16 @@ impl<'a> Enum<'a> {
         Ok(Enum {
             enum_ident: ident,
             variants,
+            tag: options.tag,
+            tag_attribute: options.tag_attribute.is_some(),
         })
     }
 
     /// Build derivation body for enums.
     fn build(&self) -> TokenStream {
+        if let Some(tag) = &self.tag {
+            return self.build_tagged(tag);
+        }
         let mut var_extracts = Vec::new();
         let mut error_names = String::new();
         for (i, var) in self.variants.iter().enumerate() {
@@ -85,6 +99,63 @@ impl<'a> Enum<'a> {
             ::std::result::Result::Err(::pyo3::exceptions::PyTypeError::new_err(err_msg))
         )
     }
+
+    /// Build derivation body for enums with a `tag`, which only extract the variant named by the
+    /// tag's value.
+    ///
+    /// The tag is looked up with the mapping protocol, and tuple variants are extracted from the
+    /// items keyed by their index, newtype variants from the whole mapping. With `attribute`, the
+    /// tag is looked up as an attribute instead, and variants are extracted as without a tag.
+    fn build_tagged(&self, tag: &LitStr) -> TokenStream {
+        let ty_name = self.enum_ident.to_string();
+        let mut var_names = Vec::new();
+        let mut var_extracts = Vec::new();
+        for var in &self.variants {
+            var_names.push(var.path.segments.last().unwrap().ident.to_string());
+            var_extracts.push(match &var.ty {
+                ContainerType::Tuple(len) if !self.tag_attribute => var.build_tuple_items(*len),
+                _ => var.build(),
+            });
+        }
+        let expected = var_names
+            .iter()
+            .map(|name| format!("'{}'", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let missing_tag_msg = format!(
+            "failed to extract enum {}: missing or invalid tag '{}'",
+            ty_name,
+            tag.value()
+        );
+        let get_tag = if self.tag_attribute {
+            quote!(getattr)
+        } else {
+            quote!(get_item)
+        };
+        quote!(
+            let tag_value: &str = obj
+                .#get_tag(#tag)
+                .and_then(|tag_value| tag_value.extract())
+            .map_err(|inner| {
+                let py = ::pyo3::PyNativeType::py(obj);
+                let new_err = ::pyo3::exceptions::PyTypeError::new_err(#missing_tag_msg);
+                new_err.set_cause(py, ::std::option::Option::Some(inner));
+                new_err
+            })?;
+            match tag_value {
+                #(#var_names => { #var_extracts })*
+                other => ::std::result::Result::Err(::pyo3::exceptions::PyTypeError::new_err(
+                    ::std::format!(
+                        "failed to extract enum {}: unknown value '{}' for tag '{}', expected one of {}",
+                        #ty_name,
+                        other,
+                        #tag,
+                        #expected
+                    )
+                )),
+            }
+        )
+    }
 }
 
 /// Container Style
@@ -119,6 +190,7 @@ struct Container<'a> {
     ty: ContainerType<'a>,
     err_name: String,
     is_enum_variant: bool,
+    rename_rule: Option<RenamingRule>,
 }
 
 impl<'a> Container<'a> {
@@ -135,13 +207,13 @@ impl<'a> Container<'a> {
             !fields.is_empty(),
             fields.span() => "cannot derive FromPyObject for empty structs and variants"
         );
-        if options.transparent {
+        if options.transparent.is_some() {
             ensure_spanned!(
                 fields.len() == 1,
                 fields.span() => "transparent structs and variants can only have 1 field"
             );
         }
-        let style = match (fields, options.transparent) {
+        let style = match (fields, options.transparent.is_some()) {
             (Fields::Unnamed(_), true) => ContainerType::TupleNewtype,
             (Fields::Unnamed(unnamed), false) => match unnamed.unnamed.len() {
                 1 => ContainerType::TupleNewtype,
@@ -173,6 +245,12 @@ impl<'a> Container<'a> {
             }
             (Fields::Unit, _) => unreachable!(), // covered by length check above
         };
+        match (&style, &options.rename_all) {
+            (ContainerType::Struct(_), _) | (_, None) => {}
+            (_, Some(rename_all)) => bail_spanned!(
+                rename_all.kw.span() => "`rename_all` is only supported for structs and variants with named fields"
+            ),
+        }
         let err_name = options.annotation.map_or_else(
             || path.segments.last().unwrap().ident.to_string(),
             |lit_str| lit_str.value(),
@@ -183,6 +261,7 @@ impl<'a> Container<'a> {
             ty: style,
             err_name,
             is_enum_variant,
+            rename_rule: options.rename_all.map(|rename_all| rename_all.rule),
         };
         Ok(v)
     }
@@ -263,19 +342,51 @@ impl<'a> Container<'a> {
         )
     }
 
+    /// Build derivation body for a tuple variant of a tagged enum, whose fields are the items of
+    /// the mapping keyed by their index.
+    fn build_tuple_items(&self, len: usize) -> TokenStream {
+        let self_ty = &self.path;
+        let mut fields: Punctuated<TokenStream, syn::Token![,]> = Punctuated::new();
+        for i in 0..len {
+            let error_msg = format!("failed to extract field {}.{}", quote!(#self_ty), i);
+            fields.push(quote!(
+                obj.get_item(#i).and_then(::pyo3::types::PyAny::extract).map_err(|inner| {
+                let py = ::pyo3::PyNativeType::py(obj);
+                let new_err = ::pyo3::exceptions::PyTypeError::new_err(#error_msg);
+                new_err.set_cause(py, ::std::option::Option::Some(inner));
+                new_err
+                })?));
+        }
+        quote!(::std::result::Result::Ok(#self_ty(#fields)))
+    }
+
     fn build_struct(&self, tups: &[(&Ident, FieldPyO3Attributes)]) -> TokenStream {
         let self_ty = &self.path;
         let mut fields: Punctuated<TokenStream, syn::Token![,]> = Punctuated::new();
         for (ident, attrs) in tups {
-            let getter = match &attrs.getter {
-                FieldGetter::GetAttr(Some(name)) => quote!(getattr(#name)),
-                FieldGetter::GetAttr(None) => quote!(getattr(stringify!(#ident))),
-                FieldGetter::GetItem(Some(key)) => quote!(get_item(#key)),
-                FieldGetter::GetItem(None) => quote!(get_item(stringify!(#ident))),
+            let field_name = field_name(ident, self.rename_rule);
+            let (getter, missing_err) = match &attrs.getter {
+                FieldGetter::GetAttr(Some(name)) => {
+                    (quote!(getattr(#name)), quote!(PyAttributeError))
+                }
+                FieldGetter::GetAttr(None) => {
+                    (quote!(getattr(#field_name)), quote!(PyAttributeError))
+                }
+                FieldGetter::GetItem(Some(key)) => (quote!(get_item(#key)), quote!(PyLookupError)),
+                FieldGetter::GetItem(None) => {
+                    (quote!(get_item(#field_name)), quote!(PyLookupError))
+                }
             };
             let conversion_error_msg =
                 format!("failed to extract field {}.{}", quote!(#self_ty), ident);
-            let get_field = quote!(obj.#getter?);
+            let get_field = if attrs.flatten.is_some() {
+                // Flattened fields are extracted from the same object as the container
+                quote!(obj)
+            } else if attrs.default.is_some() {
+                quote!(value)
+            } else {
+                quote!(obj.#getter?)
+            };
             let extractor = match &attrs.from_py_with {
                 None => quote!(
                     #get_field.extract().map_err(|inner| {
@@ -294,17 +405,56 @@ impl<'a> Container<'a> {
                 ),
             };
 
+            let extractor = match &attrs.default {
+                None => extractor,
+                Some(default) => {
+                    let default_expr = match default {
+                        Some(expr) => quote!(#expr),
+                        None => quote!(::std::default::Default::default()),
+                    };
+                    quote!(
+                        match obj.#getter {
+                            ::std::result::Result::Ok(value) => #extractor,
+                            ::std::result::Result::Err(err)
+                                if err.is_instance::<::pyo3::exceptions::#missing_err>(
+                                    ::pyo3::PyNativeType::py(obj),
+                                ) => #default_expr,
+                            ::std::result::Result::Err(err) => {
+                                return ::std::result::Result::Err(err)
+                            }
+                        }
+                    )
+                }
+            };
+
             fields.push(quote!(#ident: #extractor));
         }
         quote!(::std::result::Result::Ok(#self_ty{#fields}))
     }
 }
 
-struct ContainerOptions {
+/// The name used to look up a field which has no explicit `item` key or `attribute` name.
+pub(crate) fn field_name(ident: &Ident, rename_rule: Option<RenamingRule>) -> TokenStream {
+    match rename_rule {
+        Some(rule) => {
+            let name = rule.apply(&ident.unraw().to_string());
+            quote!(#name)
+        }
+        None => quote!(stringify!(#ident)),
+    }
+}
+
+pub(crate) struct ContainerOptions {
     /// Treat the Container as a Wrapper, directly extract its fields from the input object.
-    transparent: bool,
+    pub(crate) transparent: Option<attributes::kw::transparent>,
     /// Change the name of an enum variant in the generated error message.
-    annotation: Option<syn::LitStr>,
+    pub(crate) annotation: Option<syn::LitStr>,
+    /// Rename all fields which have no explicit name according to the given rule.
+    pub(crate) rename_all: Option<RenameAllAttribute>,
+    /// Select the variant of an enum by the value of this key or attribute.
+    pub(crate) tag: Option<LitStr>,
+    /// Read the `tag` as an attribute instead of an item.
+    pub(crate) tag_attribute: Option<attributes::kw::attribute>,
 }
 
 /// Attributes for deriving FromPyObject scoped on containers.
@@ -314,6 +464,12 @@ enum ContainerPyO3Attribute {
     Transparent(attributes::kw::transparent),
     /// Change the name of an enum variant in the generated error message.
     ErrorAnnotation(LitStr),
+    /// Rename all fields which have no explicit name according to the given rule.
+    RenameAll(RenameAllAttribute),
+    /// Select the variant of an enum by the value of this key or attribute.
+    Tag(LitStr),
+    /// Read the tag as an attribute instead of an item.
+    TagAttribute(attributes::kw::attribute),
 }
 
 impl Parse for ContainerPyO3Attribute {
@@ -326,6 +482,14 @@ impl Parse for ContainerPyO3Attribute {
             let _: attributes::kw::annotation = input.parse()?;
             let _: Token![=] = input.parse()?;
             input.parse().map(ContainerPyO3Attribute::ErrorAnnotation)
+        } else if lookahead.peek(attributes::kw::rename_all) {
+            input.parse().map(ContainerPyO3Attribute::RenameAll)
+        } else if lookahead.peek(attributes::kw::tag) {
+            let _: attributes::kw::tag = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            input.parse().map(ContainerPyO3Attribute::Tag)
+        } else if lookahead.peek(attributes::kw::attribute) {
+            input.parse().map(ContainerPyO3Attribute::TagAttribute)
         } else {
             Err(lookahead.error())
         }
@@ -333,10 +497,13 @@ impl Parse for ContainerPyO3Attribute {
 }
 
 impl ContainerOptions {
-    fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
         let mut options = ContainerOptions {
-            transparent: false,
+            transparent: None,
             annotation: None,
+            rename_all: None,
+            tag: None,
+            tag_attribute: None,
         };
         for attr in attrs {
             if let Some(pyo3_attrs) = get_pyo3_options(attr)? {
@@ -344,10 +511,10 @@ impl ContainerOptions {
                     match pyo3_attr {
                         ContainerPyO3Attribute::Transparent(kw) => {
                             ensure_spanned!(
-                                !options.transparent,
+                                options.transparent.is_none(),
                                 kw.span() => "`transparent` may only be provided once"
                             );
-                            options.transparent = true;
+                            options.transparent = Some(kw);
                         }
                         ContainerPyO3Attribute::ErrorAnnotation(lit_str) => {
                             ensure_spanned!(
@@ -356,23 +523,74 @@ impl ContainerOptions {
                             );
                             options.annotation = Some(lit_str);
                         }
+                        ContainerPyO3Attribute::RenameAll(rename_all) => {
+                            ensure_spanned!(
+                                options.rename_all.is_none(),
+                                rename_all.kw.span() => "`rename_all` may only be provided once"
+                            );
+                            options.rename_all = Some(rename_all);
+                        }
+                        ContainerPyO3Attribute::Tag(lit_str) => {
+                            ensure_spanned!(
+                                options.tag.is_none(),
+                                lit_str.span() => "`tag` may only be provided once"
+                            );
+                            options.tag = Some(lit_str);
+                        }
+                        ContainerPyO3Attribute::TagAttribute(kw) => {
+                            ensure_spanned!(
+                                options.tag_attribute.is_none(),
+                                kw.span() => "`attribute` may only be provided once"
+                            );
+                            options.tag_attribute = Some(kw);
+                        }
                     }
                 }
             }
         }
         Ok(options)
     }
+
+    /// Options on an enum itself may only select the tag, the rest apply to variants.
+    pub(crate) fn ensure_only_tag(&self) -> Result<()> {
+        if let Some(kw) = &self.transparent {
+            bail_spanned!(kw.span() => "`transparent` is not supported for enums, only for variants");
+        }
+        if let Some(lit_str) = &self.annotation {
+            bail_spanned!(lit_str.span() => "`annotation` is not supported for enums, only for variants");
+        }
+        if let Some(rename_all) = &self.rename_all {
+            bail_spanned!(rename_all.kw.span() => "`rename_all` is not supported for enums, only for variants");
+        }
+        if let (Some(kw), None) = (&self.tag_attribute, &self.tag) {
+            bail_spanned!(kw.span() => "`attribute` requires `tag`");
+        }
+        Ok(())
+    }
+
+    pub(crate) fn ensure_no_tag(&self) -> Result<()> {
+        if let Some(lit_str) = &self.tag {
+            bail_spanned!(lit_str.span() => "`tag` is only supported for enums");
+        }
+        if let Some(kw) = &self.tag_attribute {
+            bail_spanned!(kw.span() => "`attribute` is only supported for enums with a `tag`");
+        }
+        Ok(())
+    }
 }
 
 /// Attributes for deriving FromPyObject scoped on fields.
 #[derive(Clone, Debug)]
-struct FieldPyO3Attributes {
-    getter: FieldGetter,
-    from_py_with: Option<FromPyWithAttribute>,
+pub(crate) struct FieldPyO3Attributes {
+    pub(crate) getter: FieldGetter,
+    pub(crate) from_py_with: Option<FromPyWithAttribute>,
+    /// The value to use if the field is missing, `Some(None)` for `Default::default()`.
+    pub(crate) default: Option<Option<syn::Expr>>,
+    pub(crate) flatten: Option<attributes::kw::flatten>,
 }
 
 #[derive(Clone, Debug)]
-enum FieldGetter {
+pub(crate) enum FieldGetter {
     GetItem(Option<syn::Lit>),
     GetAttr(Option<LitStr>),
 }
@@ -380,6 +598,8 @@ enum FieldGetter {
 enum FieldPyO3Attribute {
     Getter(FieldGetter),
     FromPyWith(FromPyWithAttribute),
+    Default(Option<syn::Expr>),
+    Flatten(attributes::kw::flatten),
 }
 
 impl Parse for FieldPyO3Attribute {
@@ -423,6 +643,18 @@ impl Parse for FieldPyO3Attribute {
             }
         } else if lookahead.peek(attributes::kw::from_py_with) {
             input.parse().map(FieldPyO3Attribute::FromPyWith)
+        } else if lookahead.peek(attributes::kw::default) {
+            let _: attributes::kw::default = input.parse()?;
+            if input.peek(Token![=]) {
+                let _: Token![=] = input.parse()?;
+                input
+                    .parse()
+                    .map(|expr| FieldPyO3Attribute::Default(Some(expr)))
+            } else {
+                Ok(FieldPyO3Attribute::Default(None))
+            }
+        } else if lookahead.peek(attributes::kw::flatten) {
+            input.parse().map(FieldPyO3Attribute::Flatten)
         } else {
             Err(lookahead.error())
         }
@@ -432,9 +664,11 @@ impl Parse for FieldPyO3Attribute {
 impl FieldPyO3Attributes {
     /// Extract the field attributes.
     ///
-    fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
         let mut getter = None;
         let mut from_py_with = None;
+        let mut default = None;
+        let mut flatten: Option<attributes::kw::flatten> = None;
 
         for attr in attrs {
             if let Some(pyo3_attrs) = get_pyo3_options(attr)? {
@@ -454,14 +688,37 @@ impl FieldPyO3Attributes {
                             );
                             from_py_with = Some(from_py_with_attr);
                         }
+                        FieldPyO3Attribute::Default(default_expr) => {
+                            ensure_spanned!(
+                                default.is_none(),
+                                attr.span() => "`default` may only be provided once"
+                            );
+                            default = Some(default_expr);
+                        }
+                        FieldPyO3Attribute::Flatten(kw) => {
+                            ensure_spanned!(
+                                flatten.is_none(),
+                                kw.span() => "`flatten` may only be provided once"
+                            );
+                            flatten = Some(kw);
+                        }
                     }
                 }
             }
         }
 
+        if let Some(kw) = &flatten {
+            ensure_spanned!(
+                getter.is_none() && from_py_with.is_none() && default.is_none(),
+                kw.span() => "`flatten` cannot be combined with `attribute`, `item`, `from_py_with` or `default`"
+            );
+        }
+
         Ok(FieldPyO3Attributes {
             getter: getter.unwrap_or(FieldGetter::GetAttr(None)),
             from_py_with,
+            default,
+            flatten,
         })
     }
 }
@@ -502,7 +759,8 @@ pub fn build_derive_from_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
     }
     let derives = match &tokens.data {
         syn::Data::Enum(en) => {
-            let en = Enum::new(en, &tokens.ident)?;
+            let options = ContainerOptions::from_attrs(&tokens.attrs)?;
+            let en = Enum::new(en, &tokens.ident, options)?;
             en.build()
         }
         syn::Data::Struct(st) => {
@@ -510,6 +768,7 @@ pub fn build_derive_from_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
             if let Some(lit_str) = &options.annotation {
                 bail_spanned!(lit_str.span() => "`annotation` is unsupported for structs");
             }
+            options.ensure_no_tag()?;
             let ident = &tokens.ident;
             let st = Container::new(&st.fields, parse_quote!(#ident), options, false)?;
             st.build()
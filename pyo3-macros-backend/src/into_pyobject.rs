@@ -0,0 +1,466 @@
+use crate::from_pyobject::{field_name, ContainerOptions, FieldGetter, FieldPyO3Attributes};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse_quote, spanned::Spanned, DataEnum, DeriveInput, Fields, Ident, LitStr, Result};
+
+/// Container Style
+///
+/// Covers Structs, Tuplestructs and corresponding Newtypes.
+enum ContainerType<'a> {
+    /// Struct Container with `#[pyo3(item)]` fields, e.g. `struct Foo { #[pyo3(item)] a: String }`
+    ///
+    /// Variant contains the list of field identifiers and types, and the key used in the
+    /// resulting dict or `None` if the field is flattened into the dict.
+    Struct(Vec<(&'a Ident, &'a syn::Type, Option<TokenStream>)>),
+    /// Struct Container with attribute fields, e.g. `struct Foo { a: String }`
+    ///
+    /// Variant contains the list of field identifiers and types, and the attribute name of the
+    /// resulting `types.SimpleNamespace` or `None` if the field is flattened into it.
+    StructAttributes(Vec<(&'a Ident, &'a syn::Type, Option<TokenStream>)>),
+    /// Newtype struct container, e.g. `#[transparent] struct Foo { a: String }`
+    ///
+    /// The field specified by the identifier is converted directly.
+    StructNewtype(&'a Ident, &'a syn::Type),
+    /// Tuple struct, e.g. `struct Foo(String)`.
+    ///
+    /// Fields are converted into a tuple.
+    Tuple(usize),
+    /// Tuple newtype, e.g. `#[transparent] struct Foo(String)`
+    ///
+    /// The wrapped field is converted directly.
+    TupleNewtype(&'a syn::Type),
+}
+
+/// The object a container with fields is converted to.
+#[derive(Clone, Copy, PartialEq)]
+enum FieldsTarget {
+    /// A `dict`, whose fields can be merged into another through `IntoPyDictFields`.
+    Dict,
+    /// A `types.SimpleNamespace`, whose fields can be merged into another through
+    /// `IntoPyNamespaceFields`.
+    Namespace,
+}
+
+/// Data container
+///
+/// Either describes a struct or an enum variant.
+struct Container<'a> {
+    path: syn::Path,
+    ty: ContainerType<'a>,
+}
+
+impl<'a> Container<'a> {
+    /// Construct a container based on fields, identifier and attributes.
+    ///
+    /// Fails if the variant has no fields or incompatible attributes.
+    fn new(fields: &'a Fields, path: syn::Path, options: ContainerOptions) -> Result<Self> {
+        ensure_spanned!(
+            !fields.is_empty(),
+            fields.span() => "cannot derive IntoPyObject for empty structs and variants"
+        );
+        if let Some(lit_str) = &options.annotation {
+            bail_spanned!(lit_str.span() => "`annotation` is not supported for `IntoPyObject`");
+        }
+        for field in fields.iter() {
+            if let Some(from_py_with) = FieldPyO3Attributes::from_attrs(&field.attrs)?.from_py_with
+            {
+                bail_spanned!(
+                    from_py_with.0.span() => "`from_py_with` is not supported for `IntoPyObject`"
+                );
+            }
+        }
+        if options.transparent.is_some() {
+            ensure_spanned!(
+                fields.len() == 1,
+                fields.span() => "transparent structs and variants can only have 1 field"
+            );
+        }
+        let rename_rule = options
+            .rename_all
+            .as_ref()
+            .map(|rename_all| rename_all.rule);
+        let style = match (fields, options.transparent.is_some()) {
+            (Fields::Unnamed(unnamed), true) => ContainerType::TupleNewtype(&unnamed.unnamed[0].ty),
+            (Fields::Unnamed(unnamed), false) => match unnamed.unnamed.len() {
+                1 => ContainerType::TupleNewtype(&unnamed.unnamed[0].ty),
+                len => ContainerType::Tuple(len),
+            },
+            (Fields::Named(named), true) => {
+                let field = named
+                    .named
+                    .iter()
+                    .next()
+                    .expect("Check for len 1 is done above");
+                let ident = field
+                    .ident
+                    .as_ref()
+                    .expect("Named fields should have identifiers");
+                ContainerType::StructNewtype(ident, &field.ty)
+            }
+            (Fields::Named(named), false) => {
+                let mut fields = Vec::new();
+                let mut items = None;
+                for field in named.named.iter() {
+                    let ident = field
+                        .ident
+                        .as_ref()
+                        .expect("Named fields should have identifiers");
+                    let attrs = FieldPyO3Attributes::from_attrs(&field.attrs)?;
+                    if attrs.flatten.is_some() {
+                        fields.push((ident, &field.ty, None));
+                        continue;
+                    }
+                    let (is_item, key) = match attrs.getter {
+                        FieldGetter::GetItem(Some(key)) => (true, quote!(#key)),
+                        FieldGetter::GetItem(None) => (true, field_name(ident, rename_rule)),
+                        FieldGetter::GetAttr(Some(name)) => (false, quote!(#name)),
+                        FieldGetter::GetAttr(None) => (false, field_name(ident, rename_rule)),
+                    };
+                    ensure_spanned!(
+                        *items.get_or_insert(is_item) == is_item,
+                        ident.span() => "fields of `IntoPyObject` structs must either all be `item`s, \
+                            converted to a `dict`, or all be attributes, converted to a \
+                            `types.SimpleNamespace`"
+                    );
+                    fields.push((ident, &field.ty, Some(key)))
+                }
+                if items == Some(true) {
+                    ContainerType::Struct(fields)
+                } else {
+                    ContainerType::StructAttributes(fields)
+                }
+            }
+            (Fields::Unit, _) => unreachable!(), // covered by length check above
+        };
+        match (&style, &options.rename_all) {
+            (ContainerType::Struct(_), _) | (ContainerType::StructAttributes(_), _) | (_, None) => {
+            }
+            (_, Some(rename_all)) => bail_spanned!(
+                rename_all.kw.span() => "`rename_all` is only supported for structs and variants with named fields"
+            ),
+        }
+        Ok(Container { path, ty: style })
+    }
+
+    /// Build a pattern destructuring the container and the conversion of the bound fields.
+    ///
+    /// The conversion evaluates to a `PyResult<PyObject>`.
+    ///
+    /// If `tag` is given, the container is converted to a dict with the name of the container
+    /// stored under that key.
+    fn build(&self, tag: Option<&LitStr>) -> (TokenStream, TokenStream) {
+        if let Some((target, pattern, set_fields)) = self.build_fields(tag) {
+            let object = match target {
+                FieldsTarget::Dict => quote!(dict),
+                FieldsTarget::Namespace => quote!(py
+                    .import("types")?
+                    .getattr("SimpleNamespace")?
+                    .call((), ::std::option::Option::Some(dict))?),
+            };
+            return (
+                pattern,
+                quote!(
+                    let dict = ::pyo3::types::PyDict::new(py);
+                    #set_fields
+                    Ok(::pyo3::IntoPy::<::pyo3::PyObject>::into_py(#object, py))
+                ),
+            );
+        }
+        let path = &self.path;
+        match &self.ty {
+            ContainerType::StructNewtype(ident, _) => (
+                quote!(#path { #ident: inner }),
+                quote!(::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(inner, py)),
+            ),
+            ContainerType::TupleNewtype(_) => (
+                quote!(#path(inner)),
+                quote!(::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(inner, py)),
+            ),
+            ContainerType::Tuple(len) => {
+                let bindings: Vec<Ident> = (0..*len).map(|i| format_ident!("arg{}", i)).collect();
+                (
+                    quote!(#path(#(#bindings),*)),
+                    quote!(
+                        let elements: ::std::vec::Vec<::pyo3::PyObject> = ::std::vec![
+                            #(::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(#bindings, py)?),*
+                        ];
+                        Ok(::pyo3::IntoPy::<::pyo3::PyObject>::into_py(
+                            ::pyo3::types::PyTuple::new(py, elements),
+                            py,
+                        ))
+                    ),
+                )
+            }
+            ContainerType::Struct(_) | ContainerType::StructAttributes(_) => {
+                unreachable!("structs with named fields are built from their fields")
+            }
+        }
+    }
+
+    /// The span of the first attribute field, if the container is converted to a
+    /// `types.SimpleNamespace`.
+    fn attribute_field_span(&self) -> Option<proc_macro2::Span> {
+        match &self.ty {
+            ContainerType::StructAttributes(fields) => Some(fields[0].0.span()),
+            _ => None,
+        }
+    }
+
+    /// Build a pattern destructuring the container and the statements storing the bound fields
+    /// in `dict`, or `None` if the container isn't built from its fields.
+    ///
+    /// Containers with named fields store their fields in `dict`, which is either the converted
+    /// dict itself or the keyword arguments of the `types.SimpleNamespace`. Under a `tag`, the
+    /// fields of tuple containers are stored under their index, and the inner value of newtypes
+    /// is merged into the dict. `flatten`ed fields and tagged newtypes are merged through
+    /// `IntoPyDictFields` or `IntoPyNamespaceFields`, so they must be converted to the same kind of
+    /// object as the container.
+    fn build_fields(
+        &self,
+        tag: Option<&LitStr>,
+    ) -> Option<(FieldsTarget, TokenStream, TokenStream)> {
+        let path = &self.path;
+        let merge_fields = |target: FieldsTarget, ty: &syn::Type, binding: &Ident| match target {
+            FieldsTarget::Dict => quote_spanned!(ty.span() =>
+                <#ty as ::pyo3::derive_utils::IntoPyDictFields>::set_dict_fields(#binding, py, dict)?;
+            ),
+            FieldsTarget::Namespace => quote_spanned!(ty.span() =>
+                <#ty as ::pyo3::derive_utils::IntoPyNamespaceFields>::set_namespace_fields(#binding, py, dict)?;
+            ),
+        };
+        let inner = format_ident!("inner");
+        let (target, pattern, set_fields) = match (&self.ty, tag) {
+            (ContainerType::Struct(fields), _) | (ContainerType::StructAttributes(fields), _) => {
+                let target = match &self.ty {
+                    ContainerType::Struct(_) => FieldsTarget::Dict,
+                    _ => FieldsTarget::Namespace,
+                };
+                let idents = fields.iter().map(|(ident, _, _)| ident);
+                let bindings: Vec<Ident> = (0..fields.len())
+                    .map(|i| format_ident!("arg{}", i))
+                    .collect();
+                let set_fields: Vec<TokenStream> = fields
+                    .iter()
+                    .zip(&bindings)
+                    .map(|((_, ty, key), binding)| match key {
+                        Some(key) => quote!(
+                            dict.set_item(#key, ::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(#binding, py)?)?;
+                        ),
+                        None => merge_fields(target, ty, binding),
+                    })
+                    .collect();
+                (
+                    target,
+                    quote!(#path { #(#idents: #bindings),* }),
+                    quote!(#(#set_fields)*),
+                )
+            }
+            (ContainerType::Tuple(len), Some(_)) => {
+                let bindings: Vec<Ident> = (0..*len).map(|i| format_ident!("arg{}", i)).collect();
+                let indices = 0..*len;
+                (
+                    FieldsTarget::Dict,
+                    quote!(#path(#(#bindings),*)),
+                    quote!(
+                        #(dict.set_item(#indices, ::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(#bindings, py)?)?;)*
+                    ),
+                )
+            }
+            (ContainerType::StructNewtype(ident, ty), Some(_)) => (
+                FieldsTarget::Dict,
+                quote!(#path { #ident: #inner }),
+                merge_fields(FieldsTarget::Dict, ty, &inner),
+            ),
+            (ContainerType::TupleNewtype(ty), Some(_)) => (
+                FieldsTarget::Dict,
+                quote!(#path(#inner)),
+                merge_fields(FieldsTarget::Dict, ty, &inner),
+            ),
+            (_, None) => return None,
+        };
+        let set_tag = tag.map(|tag| {
+            let name = path.segments.last().unwrap().ident.to_string();
+            quote!(dict.set_item(#tag, #name)?;)
+        });
+        Some((
+            target,
+            pattern,
+            quote!(
+                #set_tag
+                #set_fields
+            ),
+        ))
+    }
+}
+
+/// The conversion of a derived type, and the kind of object and the body of its
+/// `IntoPyDictFields` or `IntoPyNamespaceFields` implementation if it is always built from its
+/// fields.
+struct Conversion {
+    into_py: TokenStream,
+    fields: Option<(FieldsTarget, TokenStream)>,
+}
+
+fn build_enum(
+    data_enum: &DataEnum,
+    ident: &Ident,
+    options: ContainerOptions,
+) -> Result<Conversion> {
+    ensure_spanned!(
+        !data_enum.variants.is_empty(),
+        ident.span() => "cannot derive IntoPyObject for empty enum"
+    );
+    options.ensure_only_tag()?;
+    if let Some(kw) = &options.tag_attribute {
+        bail_spanned!(kw.span() => "`attribute` tags are not supported for `IntoPyObject`");
+    }
+    let tag = options.tag.as_ref();
+    let mut fields_target = None;
+    let mut fields_arms = Some(Vec::new());
+    let arms = data_enum
+        .variants
+        .iter()
+        .map(|variant| {
+            let options = ContainerOptions::from_attrs(&variant.attrs)?;
+            options.ensure_no_tag()?;
+            let var_ident = &variant.ident;
+            let container =
+                Container::new(&variant.fields, parse_quote!(#ident::#var_ident), options)?;
+            if let (Some(span), Some(_)) = (container.attribute_field_span(), tag) {
+                bail_spanned!(
+                    span => "fields of variants under a `tag` must be `item`s, as the variant is \
+                        converted to a `dict`"
+                );
+            }
+            match (container.build_fields(tag), &mut fields_arms) {
+                (Some((target, pattern, set_fields)), Some(fields_arms))
+                    if *fields_target.get_or_insert(target) == target =>
+                {
+                    fields_arms.push(quote!(#pattern => { #set_fields }))
+                }
+                _ => fields_arms = None,
+            }
+            let (pattern, body) = container.build(tag);
+            Ok(quote!(#pattern => { #body }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Conversion {
+        into_py: quote!(
+            match self {
+                #(#arms)*
+            }
+        ),
+        fields: fields_target.and_then(|target| {
+            fields_arms.map(|fields_arms| {
+                (
+                    target,
+                    quote!(
+                        match self {
+                            #(#fields_arms)*
+                        }
+                    ),
+                )
+            })
+        }),
+    })
+}
+
+/// Derive `IntoPy<PyObject>` for enums and structs.
+///
+///   * Structs with `#[pyo3(item)]` fields are converted to a `dict`, keyed by field name or by
+///     the `item` key. Structs with attribute fields, which `FromPyObject` reads by default, are
+///     converted to a `types.SimpleNamespace` instead. A struct can't mix both. `rename_all`
+///     applies as for `FromPyObject`, and `flatten`ed fields are merged into the `dict` or
+///     `types.SimpleNamespace`, so their type must be converted to the same kind of object.
+///   * Tuple structs are converted to a `tuple`
+///   * Newtypes and `#[pyo3(transparent)]` containers are converted as their inner value
+///   * Enums are converted as their active variant. Enums with a `#[pyo3(tag = "...")]` are
+///     converted to a `dict` with the variant name stored under the `tag` key, the fields of tuple
+///     variants stored under their index, and the inner value of newtype variants merged in
+///   * Types always converted to a `dict` also implement `IntoPyDictFields`, and types always
+///     converted to a `types.SimpleNamespace` implement `IntoPyNamespaceFields`, so they can be
+///     flattened
+///   * `IntoPy::try_into_py` returns the errors raised while building the object, which
+///     `IntoPy::into_py` turns into a panic
+///   * Derivation for structs with generic fields like `struct<T> Foo(T)`
+///     adds `T: IntoPy<PyObject>` on the derived implementation.
+pub fn build_derive_into_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
+    let mut generics = tokens.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for param in tokens.generics.type_params() {
+        let gen_ident = &param.ident;
+        where_clause
+            .predicates
+            .push(parse_quote!(#gen_ident: ::pyo3::IntoPy<::pyo3::PyObject>))
+    }
+    let ident = &tokens.ident;
+    let conversion = match &tokens.data {
+        syn::Data::Enum(en) => {
+            let options = ContainerOptions::from_attrs(&tokens.attrs)?;
+            build_enum(en, ident, options)?
+        }
+        syn::Data::Struct(st) => {
+            let options = ContainerOptions::from_attrs(&tokens.attrs)?;
+            options.ensure_no_tag()?;
+            let container = Container::new(&st.fields, parse_quote!(#ident), options)?;
+            let (pattern, body) = container.build(None);
+            Conversion {
+                into_py: quote!(
+                    let #pattern = self;
+                    #body
+                ),
+                fields: container
+                    .build_fields(None)
+                    .map(|(target, pattern, set_fields)| {
+                        (
+                            target,
+                            quote!(
+                                let #pattern = self;
+                                #set_fields
+                            ),
+                        )
+                    }),
+            }
+        }
+        syn::Data::Union(_) => bail_spanned!(
+            tokens.span() => "#[derive(IntoPyObject)] is not supported for unions"
+        ),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = conversion.into_py;
+    let fields_impl = conversion.fields.map(|(target, set_fields)| {
+        let (fields_trait, set_fields_fn) = match target {
+            FieldsTarget::Dict => (quote!(IntoPyDictFields), quote!(set_dict_fields)),
+            FieldsTarget::Namespace => (quote!(IntoPyNamespaceFields), quote!(set_namespace_fields)),
+        };
+        quote!(
+            #[automatically_derived]
+            impl #impl_generics ::pyo3::derive_utils::#fields_trait for #ident #ty_generics #where_clause {
+                fn #set_fields_fn(
+                    self,
+                    py: ::pyo3::Python,
+                    dict: &::pyo3::types::PyDict,
+                ) -> ::pyo3::PyResult<()> {
+                    #set_fields
+                    Ok(())
+                }
+            }
+        )
+    });
+    Ok(quote!(
+        #[automatically_derived]
+        impl #impl_generics ::pyo3::IntoPy<::pyo3::PyObject> for #ident #ty_generics #where_clause {
+            fn into_py(self, py: ::pyo3::Python) -> ::pyo3::PyObject {
+                let result = ::pyo3::IntoPy::<::pyo3::PyObject>::try_into_py(self, py);
+                ::pyo3::derive_utils::unwrap_into_py(py, result)
+            }
+
+            fn try_into_py(self, py: ::pyo3::Python) -> ::pyo3::PyResult<::pyo3::PyObject> {
+                #body
+            }
+        }
+
+        #fields_impl
+    ))
+}
@@ -11,7 +11,9 @@ mod utils;
 mod attributes;
 mod defs;
 mod deprecations;
+mod exception;
 mod from_pyobject;
+mod into_pyobject;
 mod konst;
 mod method;
 mod module;
@@ -23,9 +25,11 @@ mod pyimpl;
 mod pymethod;
 mod pyproto;
 
+pub use exception::build_derive_py_exception;
 pub use from_pyobject::build_derive_from_pyobject;
+pub use into_pyobject::build_derive_into_pyobject;
 pub use module::{process_functions_in_module, py_init, PyModuleOptions};
-pub use pyclass::{build_py_class, PyClassArgs};
+pub use pyclass::{build_py_class, build_py_enum, PyClassArgs};
 pub use pyfunction::{build_py_function, PyFunctionOptions};
 pub use pyimpl::{build_py_methods, PyClassMethodsType};
 pub use pyproto::build_py_proto;
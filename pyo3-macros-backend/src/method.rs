@@ -1,6 +1,6 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
-use crate::attributes::TextSignatureAttribute;
+use crate::attributes::{handle_panic, panic_into_err, PanicAttribute, TextSignatureAttribute};
 use crate::params::{accept_args_kwargs, impl_arg_params};
 use crate::pyfunction::PyFunctionOptions;
 use crate::pyfunction::{PyFunctionArgPyO3Attributes, PyFunctionSignature};
@@ -179,6 +179,29 @@ impl SelfType {
             }
         }
     }
+
+    /// Like `receiver`, for `async fn`s: `&self` and `&mut self` are borrowed through guards which
+    /// own a reference to the object, so that the borrow can be held by the returned future.
+    ///
+    /// Returns the conversion and the receiver argument of the call.
+    pub fn async_receiver(&self, cls: &syn::Type) -> (TokenStream, TokenStream) {
+        let cell = quote! {
+            _py.from_borrowed_ptr::<::pyo3::PyAny>(_slf).downcast::<::pyo3::PyCell<#cls>>()?
+        };
+        match self {
+            SelfType::Receiver { mutable: false } => (
+                quote! { let _slf = ::pyo3::coroutine::RefGuard::<#cls>::new(#cell)?; },
+                quote!(&_slf,),
+            ),
+            SelfType::Receiver { mutable: true } => (
+                quote! { let mut _slf = ::pyo3::coroutine::RefMutGuard::<#cls>::new(#cell)?; },
+                quote!(&mut _slf,),
+            ),
+            SelfType::TryFromPyCell(_) => {
+                (self.receiver(cls, ExtractErrorMode::Raise), quote!(_slf,))
+            }
+        }
+    }
 }
 
 /// Determines which CPython calling convention a given FnSpec uses.
@@ -224,12 +247,204 @@ pub struct FnSpec<'a> {
     // Wrapped python name. This should not have any leading r#.
     // r# can be removed by syn::ext::IdentExt::unraw()
     pub python_name: syn::Ident,
+    // Whether `attrs` come from `#[pyo3(signature = (...))]`, whose default values are used as
+    // they are instead of being wrapped in `Some` for `Option<T>` arguments.
+    pub python_signature: bool,
     pub attrs: Vec<Argument>,
     pub args: Vec<FnArg<'a>>,
     pub output: syn::Type,
     pub doc: PythonDoc,
     pub deprecations: Deprecations,
     pub convention: CallingConvention,
+    // Set for `async fn`s, which return a `pyo3::coroutine::Coroutine` driving their future.
+    pub asyncness: Option<syn::Token![async]>,
+    // How panics are handled, from `#[pyo3(panic = "...")]`; if not set, the policy of the module
+    // which adds the function or its class is used.
+    pub panic: Option<PanicAttribute>,
+}
+
+/// How a Python parameter is passed, mirroring `pyo3::inspect::ParameterKind`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParameterKind {
+    PositionalOnly,
+    PositionalOrKeyword,
+    VarPositional,
+    KeywordOnly,
+    VarKeyword,
+}
+
+/// A parameter of the Python signature of a function.
+pub struct PythonParameter<'a, 'b> {
+    pub arg: &'b FnArg<'a>,
+    pub name: String,
+    pub kind: ParameterKind,
+    /// The default value rendered as a Python expression
+    pub default: Option<String>,
+}
+
+/// Orders the arguments of a function, apart from `py`, as in its Python signature: positional
+/// parameters, `*args`, keyword-only parameters and `**kwargs`.
+///
+/// Returns `None` for argument lists which are not valid in Python, such as a required positional
+/// argument following one with a default value.
+pub fn python_parameters<'a, 'b>(
+    args: &'b [FnArg<'a>],
+    attrs: &[Argument],
+) -> Option<Vec<PythonParameter<'a, 'b>>> {
+    let mut positional = Vec::new();
+    let mut keyword_only = Vec::new();
+    let mut varargs = None;
+    let mut kwargs = None;
+    let mut after_default = false;
+    'args: for arg in args.iter().filter(|arg| !arg.py) {
+        let mut parameter = PythonParameter {
+            arg,
+            name: arg.name.unraw().to_string(),
+            kind: ParameterKind::PositionalOrKeyword,
+            default: arg.optional.map(|_| "None".to_string()),
+        };
+        for attr in attrs {
+            match attr {
+                Argument::VarArgs(path) if path.is_ident(arg.name) => {
+                    parameter.kind = ParameterKind::VarPositional;
+                    parameter.default = None;
+                    varargs = Some(parameter);
+                    continue 'args;
+                }
+                Argument::KeywordArgs(path) if path.is_ident(arg.name) => {
+                    parameter.kind = ParameterKind::VarKeyword;
+                    parameter.default = None;
+                    kwargs = Some(parameter);
+                    continue 'args;
+                }
+                Argument::Arg(path, value) if path.is_ident(arg.name) => {
+                    if let Some(value) = value {
+                        parameter.default = Some(python_default_value(value));
+                    }
+                }
+                Argument::Kwarg(path, value) if path.is_ident(arg.name) => {
+                    parameter.kind = ParameterKind::KeywordOnly;
+                    if let Some(value) = value {
+                        parameter.default = Some(python_default_value(value));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if parameter.kind == ParameterKind::KeywordOnly {
+            keyword_only.push(parameter);
+        } else {
+            if parameter.default.is_some() {
+                after_default = true;
+            } else if after_default {
+                return None;
+            }
+            positional.push(parameter);
+        }
+    }
+
+    let positional_only = attrs
+        .iter()
+        .position(|attr| *attr == Argument::PosOnlyArgsSeparator)
+        .unwrap_or(0);
+    for parameter in positional.iter_mut().take(positional_only) {
+        parameter.kind = ParameterKind::PositionalOnly;
+    }
+    let mut parameters = positional;
+    parameters.extend(varargs);
+    parameters.extend(keyword_only);
+    parameters.extend(kwargs);
+    Some(parameters)
+}
+
+/// Generates the `__text_signature__` of a function from its arguments, e.g. `($self, a, b=1, *, c=None)`.
+///
+/// Returns `None` for methods which can't have a signature, and for argument lists which are not
+/// valid in Python.
+pub fn text_signature(fn_type: &FnType, args: &[FnArg], attrs: &[Argument]) -> Option<String> {
+    let mut params = match fn_type {
+        FnType::Fn(_) => vec!["$self".to_string()],
+        FnType::FnClass => vec!["$cls".to_string()],
+        FnType::FnNew | FnType::FnStatic | FnType::FnModule => Vec::new(),
+        FnType::Getter(_) | FnType::Setter(_) | FnType::FnCall(_) | FnType::ClassAttribute => {
+            return None
+        }
+    };
+
+    let parameters = python_parameters(args, attrs)?;
+    let mut keyword_only_marker = true;
+    for (i, parameter) in parameters.iter().enumerate() {
+        let name = match parameter.kind {
+            ParameterKind::VarPositional => {
+                keyword_only_marker = false;
+                format!("*{}", parameter.name)
+            }
+            ParameterKind::VarKeyword => format!("**{}", parameter.name),
+            ParameterKind::KeywordOnly if keyword_only_marker => {
+                keyword_only_marker = false;
+                params.push("*".to_string());
+                parameter.name.clone()
+            }
+            _ => parameter.name.clone(),
+        };
+        match &parameter.default {
+            Some(default) => params.push(format!("{}={}", name, default)),
+            None => params.push(name),
+        }
+        let next = parameters.get(i + 1).map(|next| next.kind);
+        if parameter.kind == ParameterKind::PositionalOnly
+            && next != Some(ParameterKind::PositionalOnly)
+        {
+            params.push("/".to_string());
+        }
+    }
+    Some(format!("({})", params.join(", ")))
+}
+
+/// Renders a default value as a Python expression, falling back to `...` for values which have
+/// no literal Python equivalent.
+fn python_default_value(value: &syn::Expr) -> String {
+    match value {
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => match lit {
+            syn::Lit::Int(int) => int.base10_digits().to_string(),
+            syn::Lit::Float(float) => float.base10_digits().to_string(),
+            syn::Lit::Bool(bool) if bool.value => "True".to_string(),
+            syn::Lit::Bool(_) => "False".to_string(),
+            syn::Lit::Str(string)
+                if string
+                    .value()
+                    .chars()
+                    .all(|c| c.is_ascii_graphic() || c == ' ') =>
+            {
+                format!("{:?}", string.value())
+            }
+            _ => "...".to_string(),
+        },
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(_),
+                ..
+            })
+            | syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Float(_),
+                ..
+            }) => format!("-{}", python_default_value(expr)),
+            _ => "...".to_string(),
+        },
+        syn::Expr::Path(path) if path.path.is_ident("None") => "None".to_string(),
+        syn::Expr::Call(call) if call.args.len() == 1 => match &*call.func {
+            syn::Expr::Path(path) if path.path.is_ident("Some") => {
+                python_default_value(&call.args[0])
+            }
+            _ => "...".to_string(),
+        },
+        syn::Expr::Paren(paren) => python_default_value(&paren.expr),
+        _ => "...".to_string(),
+    }
 }
 
 pub fn get_return_info(output: &syn::ReturnType) -> syn::Type {
@@ -262,7 +477,7 @@ impl<'a> FnSpec<'a> {
     ) -> Result<FnSpec<'a>> {
         let MethodAttributes {
             ty: fn_type_attr,
-            args: fn_attrs,
+            args: mut fn_attrs,
             mut python_name,
         } = parse_method_attributes(meth_attrs, options.name.map(|name| name.0))?;
 
@@ -285,19 +500,12 @@ impl<'a> FnSpec<'a> {
         let (fn_type, skip_first_arg, fixed_convention) =
             Self::parse_fn_type(sig, fn_type_attr, &mut python_name)?;
         Self::ensure_text_signature_on_valid_method(&fn_type, options.text_signature.as_ref())?;
+        Self::ensure_async_on_valid_method(&fn_type, sig.asyncness.as_ref())?;
 
         let name = &sig.ident;
         let ty = get_return_info(&sig.output);
         let python_name = python_name.as_ref().unwrap_or(name).unraw();
 
-        let doc = utils::get_doc(
-            meth_attrs,
-            options
-                .text_signature
-                .as_ref()
-                .map(|attr| (&python_name, attr)),
-        );
-
         let arguments: Vec<_> = if skip_first_arg {
             sig.inputs
                 .iter_mut()
@@ -311,6 +519,27 @@ impl<'a> FnSpec<'a> {
                 .collect::<Result<_>>()?
         };
 
+        let mut python_signature = false;
+        if let Some(signature) = options.signature {
+            ensure_spanned!(
+                fn_attrs.is_empty(),
+                sig.ident.span() => "`#[args]` cannot be used together with `signature`"
+            );
+            signature.validate(&arguments)?;
+            python_signature = signature.python_syntax;
+            fn_attrs = signature.arguments;
+        }
+
+        let text_signature = match &options.text_signature {
+            Some(attr) => Some(attr.lit.value()),
+            None if utils::doc_has_text_signature(meth_attrs, &python_name) => None,
+            None => text_signature(&fn_type, &arguments, &fn_attrs),
+        };
+        let doc = utils::get_doc(
+            meth_attrs,
+            text_signature.map(|signature| (&python_name, signature)),
+        );
+
         let convention =
             fixed_convention.unwrap_or_else(|| CallingConvention::from_args(&arguments, &fn_attrs));
 
@@ -319,11 +548,14 @@ impl<'a> FnSpec<'a> {
             name,
             convention,
             python_name,
+            python_signature,
             attrs: fn_attrs,
             args: arguments,
             output: ty,
             doc,
             deprecations: options.deprecations,
+            panic: options.panic,
+            asyncness: sig.asyncness,
         })
     }
 
@@ -354,6 +586,25 @@ impl<'a> FnSpec<'a> {
         Ok(())
     }
 
+    fn ensure_async_on_valid_method(
+        fn_type: &FnType,
+        asyncness: Option<&syn::Token![async]>,
+    ) -> syn::Result<()> {
+        if let Some(asyncness) = asyncness {
+            match fn_type {
+                FnType::FnNew
+                | FnType::FnCall(_)
+                | FnType::Getter(_)
+                | FnType::Setter(_)
+                | FnType::ClassAttribute => bail_spanned!(
+                    asyncness.span() => "`async fn` is not allowed with this method type"
+                ),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     fn parse_fn_type(
         sig: &syn::Signature,
         fn_type_attr: Option<MethodTypeAttribute>,
@@ -438,8 +689,7 @@ impl<'a> FnSpec<'a> {
                 Argument::Arg(path, opt) | Argument::Kwarg(path, opt) => {
                     if path.is_ident(name) {
                         if let Some(val) = opt {
-                            let i: syn::Expr = syn::parse_str(val).unwrap();
-                            return Some(i.into_token_stream());
+                            return Some(val.into_token_stream());
                         }
                     }
                 }
@@ -467,8 +717,16 @@ impl<'a> FnSpec<'a> {
         cls: Option<&syn::Type>,
     ) -> Result<TokenStream> {
         let deprecations = &self.deprecations;
-        let self_conversion = self.tp.self_conversion(cls, ExtractErrorMode::Raise);
-        let self_arg = self.tp.self_arg();
+        let handle_panic = handle_panic(self.panic.as_ref(), cls, ident);
+        let (self_conversion, self_arg) = match (&self.asyncness, &self.tp) {
+            // The future of an `async fn` outlives the call, so `self` is borrowed through a guard
+            (Some(_), FnType::Fn(self_type)) => self_type
+                .async_receiver(cls.expect("no class given for Fn with a \"self\" receiver")),
+            _ => (
+                self.tp.self_conversion(cls, ExtractErrorMode::Raise),
+                self.tp.self_arg(),
+            ),
+        };
         let arg_names = (0..self.args.len())
             .map(|pos| syn::Ident::new(&format!("arg{}", pos), Span::call_site()))
             .collect::<Vec<_>>();
@@ -479,8 +737,28 @@ impl<'a> FnSpec<'a> {
         } else {
             quote!(#func_name)
         };
-        let rust_call =
-            quote! { ::pyo3::callback::convert(#py, #rust_name(#self_arg #(#arg_names),*)) };
+        let rust_call = if self.asyncness.is_some() {
+            let python_name = self.python_name.to_string();
+            let qualname_prefix = match cls {
+                Some(cls) => quote! {
+                    ::std::option::Option::Some(<#cls as ::pyo3::type_object::PyTypeInfo>::NAME)
+                },
+                None => quote!(::std::option::Option::None),
+            };
+            let panic_into_err = panic_into_err(self.panic.as_ref(), cls, ident);
+            quote! {{
+                let future = async move { #rust_name(#self_arg #(#arg_names),*).await };
+                let coroutine = ::pyo3::coroutine::Coroutine::new(
+                    #python_name,
+                    #qualname_prefix,
+                    #panic_into_err,
+                    future,
+                );
+                ::pyo3::callback::convert(#py, coroutine)
+            }}
+        } else {
+            quote! { ::pyo3::callback::convert(#py, #rust_name(#self_arg #(#arg_names),*)) }
+        };
         Ok(match self.convention {
             CallingConvention::Noargs => {
                 quote! {
@@ -490,7 +768,7 @@ impl<'a> FnSpec<'a> {
                     ) -> *mut ::pyo3::ffi::PyObject
                     {
                         #deprecations
-                        ::pyo3::callback::handle_panic(|#py| {
+                        #handle_panic(|#py| {
                             #self_conversion
                             #rust_call
                         })
@@ -507,7 +785,7 @@ impl<'a> FnSpec<'a> {
                         _kwnames: *mut ::pyo3::ffi::PyObject) -> *mut ::pyo3::ffi::PyObject
                     {
                         #deprecations
-                        ::pyo3::callback::handle_panic(|#py| {
+                        #handle_panic(|#py| {
                             #self_conversion
                             let _kwnames: ::std::option::Option<&::pyo3::types::PyTuple> = #py.from_borrowed_ptr_or_opt(_kwnames);
                             // Safety: &PyAny has the same memory layout as `*mut ffi::PyObject`
@@ -533,7 +811,7 @@ impl<'a> FnSpec<'a> {
                         _kwargs: *mut ::pyo3::ffi::PyObject) -> *mut ::pyo3::ffi::PyObject
                     {
                         #deprecations
-                        ::pyo3::callback::handle_panic(|#py| {
+                        #handle_panic(|#py| {
                             #self_conversion
                             let _args = #py.from_borrowed_ptr::<::pyo3::types::PyTuple>(_args);
                             let _kwargs: ::std::option::Option<&::pyo3::types::PyDict> = #py.from_borrowed_ptr_or_opt(_kwargs);
@@ -554,7 +832,7 @@ impl<'a> FnSpec<'a> {
                     {
                         #deprecations
                         use ::pyo3::callback::IntoPyCallbackOutput;
-                        ::pyo3::callback::handle_panic(|#py| {
+                        #handle_panic(|#py| {
                             let _args = #py.from_borrowed_ptr::<::pyo3::types::PyTuple>(_args);
                             let _kwargs: ::std::option::Option<&::pyo3::types::PyDict> = #py.from_borrowed_ptr_or_opt(_kwargs);
 
@@ -571,10 +849,13 @@ impl<'a> FnSpec<'a> {
 
     /// Return a `PyMethodDef` constructor for this function, matching the selected
     /// calling convention.
-    pub fn get_methoddef(&self, wrapper: impl ToTokens) -> TokenStream {
+    pub fn get_methoddef(&self, wrapper: impl ToTokens, cls: Option<&syn::Type>) -> TokenStream {
         let python_name = self.null_terminated_python_name();
         let doc = &self.doc;
-        match self.convention {
+        let info = self
+            .function_info(cls)
+            .map(|info| quote!(.info(::pyo3::class::methods::PyFunctionInfoFactory(#info))));
+        let methoddef = match self.convention {
             CallingConvention::Noargs => quote! {
                 ::pyo3::class::methods::PyMethodDef::noargs(
                     #python_name,
@@ -597,8 +878,154 @@ impl<'a> FnSpec<'a> {
                 )
             },
             CallingConvention::TpNew => unreachable!("tp_new cannot get a methoddef"),
+        };
+        quote!(#methoddef #info)
+    }
+
+    /// Generates a `fn() -> pyo3::inspect::FunctionInfo` describing the Python signature of the
+    /// function, for functions whose arguments form a valid Python signature.
+    ///
+    /// Nothing is generated without the `inspect` feature.
+    pub fn function_info(&self, cls: Option<&syn::Type>) -> Option<TokenStream> {
+        if !cfg!(feature = "inspect") {
+            return None;
+        }
+        let kind = match self.tp {
+            FnType::Fn(_) => quote!(Method),
+            FnType::FnNew => quote!(New),
+            FnType::FnClass => quote!(ClassMethod),
+            // `#[pyfunction]`s without `pass_module` are static methods without a class
+            FnType::FnStatic if cls.is_none() => quote!(Function),
+            FnType::FnStatic => quote!(StaticMethod),
+            FnType::FnModule => quote!(Function),
+            FnType::Getter(_) | FnType::Setter(_) | FnType::FnCall(_) | FnType::ClassAttribute => {
+                return None
+            }
+        };
+        let name = self.python_name.unraw().to_string();
+        let parameters = python_parameters(&self.args, &self.attrs)?;
+        let parameters = parameters.iter().map(|parameter| {
+            let name = &parameter.name;
+            let kind = match parameter.kind {
+                ParameterKind::PositionalOnly => quote!(PositionalOnly),
+                ParameterKind::PositionalOrKeyword => quote!(PositionalOrKeyword),
+                ParameterKind::VarPositional => quote!(VarPositional),
+                ParameterKind::KeywordOnly => quote!(KeywordOnly),
+                ParameterKind::VarKeyword => quote!(VarKeyword),
+            };
+            let annotation = match parameter.kind {
+                ParameterKind::VarPositional | ParameterKind::VarKeyword => {
+                    quote!(::pyo3::inspect::types::TypeInfo::Any)
+                }
+                _ if parameter.arg.attrs.from_py_with.is_some() => {
+                    quote!(::pyo3::inspect::types::TypeInfo::Any)
+                }
+                _ => type_hint(parameter.arg.ty, cls, quote!(type_input)),
+            };
+            let default = match &parameter.default {
+                Some(default) => quote!(::std::option::Option::Some(#default)),
+                None => quote!(::std::option::Option::None),
+            };
+            quote! {
+                ::pyo3::inspect::ParameterInfo {
+                    name: #name,
+                    kind: ::pyo3::inspect::ParameterKind::#kind,
+                    annotation: #annotation,
+                    default: #default,
+                }
+            }
+        });
+        let return_type = match (&self.tp, cls) {
+            (FnType::FnNew, Some(cls)) => quote!(::pyo3::inspect::types::TypeInfo::of::<#cls>()),
+            _ if self.asyncness.is_some() => {
+                let output = return_type_hint(&self.output, cls);
+                quote!(::pyo3::inspect::types::TypeInfo::coroutine_of(#output))
+            }
+            _ => return_type_hint(&self.output, cls),
+        };
+        Some(quote! {{
+            fn info() -> ::pyo3::inspect::FunctionInfo {
+                #[allow(unused_imports)]
+                use ::pyo3::inspect::{ExtractTypeHint as _, IntoTypeHint as _};
+                ::pyo3::inspect::FunctionInfo {
+                    name: #name,
+                    kind: ::pyo3::inspect::FunctionKind::#kind,
+                    parameters: ::std::vec![#(#parameters),*],
+                    return_type: #return_type,
+                }
+            }
+            info
+        }})
+    }
+}
+
+/// Generates the `TypeInfo` of a type through `pyo3::inspect::TypeHint`, which falls back to
+/// `typing.Any` for types which don't implement the conversion trait.
+pub fn type_hint(ty: &syn::Type, cls: Option<&syn::Type>, method: TokenStream) -> TokenStream {
+    let ty = replace_self_and_lifetimes(ty.to_token_stream(), cls);
+    quote!((&::pyo3::inspect::TypeHint::<#ty>::new()).#method())
+}
+
+/// Generates the `TypeInfo` of the value returned to Python by a function returning `output`.
+pub fn return_type_hint(output: &syn::Type, cls: Option<&syn::Type>) -> TokenStream {
+    match output {
+        syn::Type::Infer(_) => quote!(::pyo3::inspect::types::TypeInfo::None),
+        syn::Type::Path(syn::TypePath { path, .. }) => {
+            let result_ok_type = path.segments.last().and_then(|seg| {
+                if seg.ident != "PyResult" && seg.ident != "Result" {
+                    return None;
+                }
+                match &seg.arguments {
+                    syn::PathArguments::AngleBracketed(args) => {
+                        args.args.iter().find_map(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                }
+            });
+            match result_ok_type {
+                Some(ty) => return_type_hint(ty, cls),
+                None => type_hint(output, cls, quote!(type_output)),
+            }
+        }
+        _ => type_hint(output, cls, quote!(type_output)),
+    }
+}
+
+/// Makes a type usable in an item nested in the generated code: `Self` is replaced by the class
+/// and lifetimes other than `'static` by `'_`.
+fn replace_self_and_lifetimes(tokens: TokenStream, cls: Option<&syn::Type>) -> TokenStream {
+    use proc_macro2::{Group, TokenTree};
+    let mut result = TokenStream::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => {
+                let stream = replace_self_and_lifetimes(group.stream(), cls);
+                let mut replaced = Group::new(group.delimiter(), stream);
+                replaced.set_span(group.span());
+                result.extend(Some(TokenTree::Group(replaced)));
+            }
+            TokenTree::Ident(ident) if ident == "Self" => match cls {
+                Some(cls) => cls.to_tokens(&mut result),
+                None => result.extend(Some(TokenTree::Ident(ident))),
+            },
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => match tokens.next() {
+                Some(TokenTree::Ident(ident)) if ident != "static" => {
+                    result.extend(quote!('_));
+                }
+                Some(next) => {
+                    result.extend(Some(TokenTree::Punct(punct)));
+                    result.extend(Some(next));
+                }
+                None => result.extend(Some(TokenTree::Punct(punct))),
+            },
+            token => result.extend(Some(token)),
         }
     }
+    result
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -8,7 +8,7 @@ use crate::{
     utils::PythonDoc,
 };
 use crate::{
-    attributes::{is_attribute_ident, take_attributes, NameAttribute},
+    attributes::{is_attribute_ident, take_attributes, NameAttribute, PanicAttribute},
     deprecations::Deprecation,
 };
 use proc_macro2::{Span, TokenStream};
@@ -23,6 +23,8 @@ use syn::{
 
 pub struct PyModuleOptions {
     name: Option<syn::Ident>,
+    panic: Option<PanicAttribute>,
+    multi_phase: Option<attributes::kw::multi_phase>,
     deprecations: Deprecations,
 }
 
@@ -38,12 +40,28 @@ impl PyModuleOptions {
 
         let mut options: PyModuleOptions = PyModuleOptions {
             name: deprecated_pymodule_name_arg,
+            panic: None,
+            multi_phase: None,
             deprecations,
         };
 
         for option in take_pyo3_options(attrs)? {
             match option {
                 PyModulePyO3Option::Name(name) => options.set_name(name.0)?,
+                PyModulePyO3Option::Panic(panic) => {
+                    ensure_spanned!(
+                        options.panic.is_none(),
+                        panic.kw.span() => "`panic` may only be specified once"
+                    );
+                    options.panic = Some(panic);
+                }
+                PyModulePyO3Option::MultiPhase(kw) => {
+                    ensure_spanned!(
+                        options.multi_phase.is_none(),
+                        kw.span() => "`multi_phase` may only be specified once"
+                    );
+                    options.multi_phase = Some(kw);
+                }
             }
         }
 
@@ -66,8 +84,39 @@ impl PyModuleOptions {
 pub fn py_init(fnname: &Ident, options: PyModuleOptions, doc: PythonDoc) -> TokenStream {
     let name = options.name.unwrap_or_else(|| fnname.unraw());
     let deprecations = options.deprecations;
+    let panic = options.panic;
+    let handle_panic = match &panic {
+        Some(panic) => panic.policy.handle_panic(),
+        None => quote!(::pyo3::callback::handle_panic),
+    };
+    // The functions and classes added by the module use its policy, unless they set their own
+    let with_policy = |call: TokenStream| match &panic {
+        Some(panic) => {
+            let policy_type = panic.policy.policy_type();
+            quote!(::pyo3::panic::with_module_policy::<#policy_type, _>(|| #call))
+        }
+        None => call,
+    };
     let cb_name = Ident::new(&format!("PyInit_{}", name), Span::call_site());
 
+    let init = if options.multi_phase.is_some() {
+        let exec = with_policy(quote!(ModuleDef::exec_module(_py, module, #fnname)));
+        quote! {
+            unsafe extern "C" fn __pyo3_exec(
+                module: *mut ::pyo3::ffi::PyObject,
+            ) -> ::std::os::raw::c_int {
+                #handle_panic(|_py| { #exec })
+            }
+
+            MODULE_DEF.init_multi_phase(__pyo3_exec)
+        }
+    } else {
+        let make = with_policy(quote!(MODULE_DEF.make_module(_py, #fnname)));
+        quote! {
+            #handle_panic(|_py| { #make })
+        }
+    };
+
     quote! {
         #[no_mangle]
         #[allow(non_snake_case)]
@@ -81,7 +130,7 @@ pub fn py_init(fnname: &Ident, options: PyModuleOptions, doc: PythonDoc) -> Toke
 
             #deprecations
 
-            ::pyo3::callback::handle_panic(|_py| { MODULE_DEF.make_module(_py, #fnname) })
+            #init
         }
     }
 }
@@ -175,6 +224,8 @@ fn get_pyfn_attr(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<PyFnArgs
 
 enum PyModulePyO3Option {
     Name(NameAttribute),
+    Panic(PanicAttribute),
+    MultiPhase(attributes::kw::multi_phase),
 }
 
 impl Parse for PyModulePyO3Option {
@@ -182,6 +233,10 @@ impl Parse for PyModulePyO3Option {
         let lookahead = input.lookahead1();
         if lookahead.peek(attributes::kw::name) {
             input.parse().map(PyModulePyO3Option::Name)
+        } else if lookahead.peek(attributes::kw::panic) {
+            input.parse().map(PyModulePyO3Option::Panic)
+        } else if lookahead.peek(attributes::kw::multi_phase) {
+            input.parse().map(PyModulePyO3Option::MultiPhase)
         } else {
             Err(lookahead.error())
         }
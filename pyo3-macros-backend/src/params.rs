@@ -104,6 +104,13 @@ pub fn impl_arg_params(
         }
     }
 
+    // All arguments before `/` are positional-only; `signature = (...)` lists them in order.
+    let positional_only_parameters = spec
+        .attrs
+        .iter()
+        .position(|attr| *attr == Argument::PosOnlyArgsSeparator)
+        .unwrap_or(0);
+
     let num_params = positional_parameter_names.len() + keyword_only_parameters.len();
 
     let mut param_conversion = Vec::new();
@@ -154,8 +161,7 @@ pub fn impl_arg_params(
                 cls_name: #cls_name,
                 func_name: stringify!(#python_name),
                 positional_parameter_names: &[#(#positional_parameter_names),*],
-                // TODO: https://github.com/PyO3/pyo3/issues/1439 - support specifying these
-                positional_only_parameters: 0,
+                positional_only_parameters: #positional_only_parameters,
                 required_positional_parameters: #required_positional_parameters,
                 keyword_only_parameters: &[#(#keyword_only_parameters),*],
                 accept_varargs: #accept_args,
@@ -235,7 +241,7 @@ fn impl_arg_param(
     };
 
     let arg_value_or_default = match (spec.default_value(name), arg.optional.is_some()) {
-        (Some(default), true) if default.to_string() != "None" => {
+        (Some(default), true) if !spec.python_signature && default.to_string() != "None" => {
             quote_arg_span! {
                 #arg_value.map_or_else(|| ::std::result::Result::Ok(::std::option::Option::Some(#default)),
                                        |_obj| #extract)?
@@ -284,6 +290,8 @@ fn impl_arg_param(
 
         Ok(quote_arg_span! {
             let #mut_ _tmp: #target_ty = #arg_value_or_default;
+            // `as_deref` does nothing for references which are extracted directly, like `&PyAny`
+            #[allow(clippy::needless_option_as_deref)]
             let #arg_name = #borrow_tmp;
         })
     } else {
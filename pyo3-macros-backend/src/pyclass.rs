@@ -5,17 +5,21 @@ use crate::attributes::{
     TextSignatureAttribute,
 };
 use crate::deprecations::Deprecations;
-use crate::pyimpl::PyClassMethodsType;
-use crate::pymethod::{impl_py_getter_def, impl_py_setter_def, PropertyType};
+use crate::pyfunction::PyFunctionOptions;
+use crate::pyimpl::{impl_methods, PyClassMethodsType};
+use crate::pymethod::{
+    gen_py_method, impl_py_getter_def, impl_py_setter_def, GeneratedPyMethod, PropertyType,
+};
 use crate::utils::{self, unwrap_group, PythonDoc};
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::{parse_quote, spanned::Spanned, Expr, Result, Token};
 
 /// The parsed arguments of the pyclass macro
+#[derive(Clone)]
 pub struct PyClassArgs {
     pub freelist: Option<syn::Expr>,
     pub name: Option<syn::Ident>,
@@ -26,6 +30,12 @@ pub struct PyClassArgs {
     pub is_basetype: bool,
     pub has_extends: bool,
     pub has_unsendable: bool,
+    pub is_frozen: bool,
+    pub has_eq: bool,
+    pub has_ord: bool,
+    pub has_hash: bool,
+    pub get_all: bool,
+    pub set_all: bool,
     pub module: Option<syn::LitStr>,
 }
 
@@ -34,8 +44,26 @@ impl Parse for PyClassArgs {
         let mut slf = PyClassArgs::default();
 
         let vars = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
-        for expr in vars {
-            slf.add_expr(&expr)?;
+        for expr in &vars {
+            slf.add_expr(expr)?;
+        }
+
+        for expr in &vars {
+            if let syn::Expr::Path(exp) = expr {
+                // `__hash__` and ordering must be consistent with `__eq__`
+                if (exp.path.is_ident("ord") || exp.path.is_ident("hash")) && !slf.has_eq {
+                    bail_spanned!(
+                        exp.span() => "the `ord` and `hash` options require the `eq` option"
+                    );
+                }
+                // A hash must not change while the object is in a set or dict
+                if exp.path.is_ident("hash") && !slf.is_frozen {
+                    bail_spanned!(exp.span() => "the `hash` option requires the `frozen` option");
+                }
+                if exp.path.is_ident("set_all") && slf.is_frozen {
+                    bail_spanned!(exp.span() => "cannot use `set_all` on a `frozen` class");
+                }
+            }
         }
         Ok(slf)
     }
@@ -54,6 +82,12 @@ impl Default for PyClassArgs {
             is_basetype: false,
             has_extends: false,
             has_unsendable: false,
+            is_frozen: false,
+            has_eq: false,
+            has_ord: false,
+            has_hash: false,
+            get_all: false,
+            set_all: false,
         }
     }
 }
@@ -158,8 +192,26 @@ impl PyClassArgs {
             "unsendable" => {
                 self.has_unsendable = true;
             }
+            "frozen" => {
+                self.is_frozen = true;
+            }
+            "eq" => {
+                self.has_eq = true;
+            }
+            "ord" => {
+                self.has_ord = true;
+            }
+            "hash" => {
+                self.has_hash = true;
+            }
+            "get_all" => {
+                self.get_all = true;
+            }
+            "set_all" => {
+                self.set_all = true;
+            }
             _ => bail_spanned!(
-                exp.path.span() => "expected one of gc/weakref/subclass/dict/unsendable"
+                exp.path.span() => "expected one of gc/weakref/subclass/dict/unsendable/frozen/eq/ord/hash/get_all/set_all"
             ),
         };
         Ok(())
@@ -229,7 +281,7 @@ pub fn build_py_class(
         options
             .text_signature
             .as_ref()
-            .map(|attr| (get_class_python_name(&class.ident, args), attr)),
+            .map(|attr| (get_class_python_name(&class.ident, args), attr.lit.value())),
     );
 
     ensure_spanned!(
@@ -260,26 +312,210 @@ pub fn build_py_class(
         }
     };
 
-    impl_class(
+    for (_, options) in &field_options {
+        options.validate(args)?;
+    }
+
+    let descriptors = impl_descriptors(
+        &class.ident,
+        field_descriptors(&class.ident, args, field_options)?,
+    );
+
+    let (richcmp, hash) = gen_comparison_methods(args);
+    let (default_methods_impl, default_slots) =
+        gen_default_slots(&class.ident, richcmp.into_iter().chain(hash).collect())?;
+
+    let pyclass = impl_class(
         &class.ident,
         args,
         doc,
-        field_options,
+        descriptors,
+        default_slots,
         methods_type,
         options.deprecations,
-    )
+    )?;
+    let into_pyobject = impl_into_py(&class.ident, args);
+
+    Ok(quote! {
+        #pyclass
+
+        #into_pyobject
+
+        #default_methods_impl
+    })
+}
+
+pub fn build_py_enum(
+    enum_: &mut syn::ItemEnum,
+    args: &PyClassArgs,
+    methods_type: PyClassMethodsType,
+) -> syn::Result<TokenStream> {
+    let options = PyClassPyO3Options::take_pyo3_options(&mut enum_.attrs)?;
+
+    ensure_spanned!(
+        !args.has_extends,
+        enum_.ident.span() => "enums can't extend from other classes"
+    );
+    ensure_spanned!(
+        !args.get_all && !args.set_all,
+        enum_.ident.span() => "`get_all` and `set_all` are not supported on enums"
+    );
+    ensure_spanned!(
+        enum_.generics.params.is_empty(),
+        enum_.generics.span() => "#[pyclass] cannot have generic parameters"
+    );
+    ensure_spanned!(
+        !enum_.variants.is_empty(),
+        enum_.ident.span() => "#[pyclass] can't be used on enums without any variants"
+    );
+
+    let doc = utils::get_doc(
+        &enum_.attrs,
+        options
+            .text_signature
+            .as_ref()
+            .map(|attr| (get_class_python_name(&enum_.ident, args), attr.lit.value())),
+    );
+
+    let variants = enum_
+        .variants
+        .iter_mut()
+        .map(PyClassEnumVariant::parse)
+        .collect::<Result<Vec<_>>>()?;
+
+    if variants.iter().all(PyClassEnumVariant::is_unit) {
+        impl_simple_enum(
+            &enum_.ident,
+            args,
+            variants,
+            doc,
+            methods_type,
+            options.deprecations,
+        )
+    } else {
+        impl_complex_enum(
+            &enum_.ident,
+            &enum_.vis,
+            args,
+            variants,
+            doc,
+            methods_type,
+            options.deprecations,
+        )
+    }
+}
+
+/// A variant of a `#[pyclass]` enum
+struct PyClassEnumVariant<'a> {
+    ident: &'a syn::Ident,
+    fields: &'a syn::Fields,
+    attrs: &'a [syn::Attribute],
+    options: EnumVariantPyO3Options,
+}
+
+impl<'a> PyClassEnumVariant<'a> {
+    fn parse(variant: &'a mut syn::Variant) -> Result<Self> {
+        let options = EnumVariantPyO3Options::take_pyo3_options(&mut variant.attrs)?;
+        let variant: &'a syn::Variant = variant;
+        Ok(PyClassEnumVariant {
+            ident: &variant.ident,
+            fields: &variant.fields,
+            attrs: &variant.attrs,
+            options,
+        })
+    }
+
+    fn is_unit(&self) -> bool {
+        match self.fields {
+            syn::Fields::Unit => true,
+            _ => false,
+        }
+    }
+
+    fn python_name(&self) -> syn::Ident {
+        self.options
+            .name
+            .as_ref()
+            .map_or_else(|| self.ident.unraw(), |name| name.0.clone())
+    }
+
+    /// The Rust identifiers used for the fields of this variant; tuple fields are named `_0`,
+    /// `_1`, etc, which is also how they are exposed to Python.
+    fn field_idents(&self) -> Vec<syn::Ident> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{}", index),
+            })
+            .collect()
+    }
+
+    /// Pattern which destructures this variant, binding each field by its `field_idents` name.
+    fn pattern(&self, cls: &syn::Ident) -> TokenStream {
+        let ident = self.ident;
+        let field_idents = self.field_idents();
+        match self.fields {
+            syn::Fields::Named(_) => quote! { #cls::#ident { #(#field_idents),* } },
+            syn::Fields::Unnamed(_) => quote! { #cls::#ident ( #(#field_idents),* ) },
+            syn::Fields::Unit => quote! { #cls::#ident },
+        }
+    }
+}
+
+/// `#[pyo3()]` options for pyclass enum variants
+struct EnumVariantPyO3Options {
+    name: Option<NameAttribute>,
+}
+
+enum EnumVariantPyO3Option {
+    Name(NameAttribute),
+}
+
+impl Parse for EnumVariantPyO3Option {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(attributes::kw::name) {
+            input.parse().map(EnumVariantPyO3Option::Name)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl EnumVariantPyO3Options {
+    fn take_pyo3_options(attrs: &mut Vec<syn::Attribute>) -> Result<Self> {
+        let mut options = EnumVariantPyO3Options { name: None };
+
+        for option in take_pyo3_options(attrs)? {
+            match option {
+                EnumVariantPyO3Option::Name(name) => {
+                    ensure_spanned!(
+                        options.name.is_none(),
+                        name.0.span() => "`name` may only be specified once"
+                    );
+                    options.name = Some(name);
+                }
+            }
+        }
+
+        Ok(options)
+    }
 }
 
 /// `#[pyo3()]` options for pyclass fields
 struct FieldPyO3Options {
-    get: bool,
-    set: bool,
+    get: Option<attributes::kw::get>,
+    set: Option<attributes::kw::set>,
+    skip: Option<attributes::kw::skip>,
     name: Option<NameAttribute>,
 }
 
 enum FieldPyO3Option {
     Get(attributes::kw::get),
     Set(attributes::kw::set),
+    Skip(attributes::kw::skip),
     Name(NameAttribute),
 }
 
@@ -290,6 +526,8 @@ impl Parse for FieldPyO3Option {
             input.parse().map(FieldPyO3Option::Get)
         } else if lookahead.peek(attributes::kw::set) {
             input.parse().map(FieldPyO3Option::Set)
+        } else if lookahead.peek(attributes::kw::skip) {
+            input.parse().map(FieldPyO3Option::Skip)
         } else if lookahead.peek(attributes::kw::name) {
             input.parse().map(FieldPyO3Option::Name)
         } else {
@@ -301,8 +539,9 @@ impl Parse for FieldPyO3Option {
 impl FieldPyO3Options {
     fn take_pyo3_options(attrs: &mut Vec<syn::Attribute>) -> Result<Self> {
         let mut options = FieldPyO3Options {
-            get: false,
-            set: false,
+            get: None,
+            set: None,
+            skip: None,
             name: None,
         };
 
@@ -310,17 +549,24 @@ impl FieldPyO3Options {
             match option {
                 FieldPyO3Option::Get(kw) => {
                     ensure_spanned!(
-                        !options.get,
+                        options.get.is_none(),
                         kw.span() => "`get` may only be specified once"
                     );
-                    options.get = true;
+                    options.get = Some(kw);
                 }
                 FieldPyO3Option::Set(kw) => {
                     ensure_spanned!(
-                        !options.set,
+                        options.set.is_none(),
                         kw.span() => "`set` may only be specified once"
                     );
-                    options.set = true;
+                    options.set = Some(kw);
+                }
+                FieldPyO3Option::Skip(kw) => {
+                    ensure_spanned!(
+                        options.skip.is_none(),
+                        kw.span() => "`skip` may only be specified once"
+                    );
+                    options.skip = Some(kw);
                 }
                 FieldPyO3Option::Name(name) => {
                     ensure_spanned!(
@@ -334,6 +580,45 @@ impl FieldPyO3Options {
 
         Ok(options)
     }
+
+    /// Checks the field options against the class-level `get_all`, `set_all` and `frozen`.
+    fn validate(&self, args: &PyClassArgs) -> Result<()> {
+        if let Some(get) = &self.get {
+            ensure_spanned!(
+                !args.get_all,
+                get.span() => "useless `get` - the class is already annotated with `get_all`"
+            );
+        }
+        if let Some(set) = &self.set {
+            ensure_spanned!(
+                !args.set_all,
+                set.span() => "useless `set` - the class is already annotated with `set_all`"
+            );
+            ensure_spanned!(
+                !args.is_frozen,
+                set.span() => "cannot use `set` on a `frozen` class"
+            );
+        }
+        if let Some(skip) = &self.skip {
+            ensure_spanned!(
+                args.get_all || args.set_all,
+                skip.span() => "`skip` is useless without `get_all` or `set_all` on the class"
+            );
+            ensure_spanned!(
+                self.get.is_none() && self.set.is_none(),
+                skip.span() => "`skip` cannot be combined with `get` or `set`"
+            );
+        }
+        Ok(())
+    }
+
+    fn has_getter(&self, args: &PyClassArgs) -> bool {
+        self.get.is_some() || (args.get_all && self.skip.is_none())
+    }
+
+    fn has_setter(&self, args: &PyClassArgs) -> bool {
+        self.set.is_some() || (args.set_all && self.skip.is_none())
+    }
 }
 
 /// To allow multiple #[pymethods] block, we define inventory types.
@@ -368,11 +653,421 @@ fn get_class_python_name<'a>(cls: &'a syn::Ident, attr: &'a PyClassArgs) -> &'a
     attr.name.as_ref().unwrap_or(cls)
 }
 
+fn impl_simple_enum(
+    cls: &syn::Ident,
+    args: &PyClassArgs,
+    variants: Vec<PyClassEnumVariant>,
+    doc: PythonDoc,
+    methods_type: PyClassMethodsType,
+    deprecations: Deprecations,
+) -> syn::Result<TokenStream> {
+    let cls_name = get_class_python_name(cls, args).to_string();
+
+    // Each variant is exposed as a class attribute holding an instance of the class
+    let variant_attributes = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        gen_class_attribute(
+            &variant.python_name(),
+            quote! { ::pyo3::IntoPy::into_py(#cls::#variant_ident, py) },
+        )
+    });
+    let descriptors = impl_descriptors(cls, variant_attributes.collect());
+
+    let repr_arms = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let repr = format!("{}.{}", cls_name, variant.python_name());
+        quote! { #cls::#variant_ident => #repr }
+    });
+    let int_arms = variants.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        quote! { #cls::#variant_ident => #cls::#variant_ident as isize }
+    });
+
+    let (richcmp, hash) = gen_comparison_methods(args);
+    // Unless derived from the Rust traits, enums compare and hash by their discriminant
+    let richcmp = richcmp.unwrap_or_else(|| {
+        parse_quote! {
+            #[pyo3(name = "__richcmp__")]
+            fn __pyo3__richcmp__(
+                &self,
+                py: ::pyo3::Python,
+                other: &Self,
+                op: ::pyo3::class::basic::CompareOp,
+            ) -> ::pyo3::PyObject {
+                use ::pyo3::conversion::ToPyObject;
+                match op {
+                    ::pyo3::class::basic::CompareOp::Eq => {
+                        (self.__pyo3__int__() == other.__pyo3__int__()).to_object(py)
+                    }
+                    ::pyo3::class::basic::CompareOp::Ne => {
+                        (self.__pyo3__int__() != other.__pyo3__int__()).to_object(py)
+                    }
+                    _ => py.NotImplemented(),
+                }
+            }
+        }
+    });
+    let hash = hash.unwrap_or_else(|| {
+        parse_quote! {
+            #[pyo3(name = "__hash__")]
+            fn __pyo3__hash__(&self) -> isize {
+                self.__pyo3__int__()
+            }
+        }
+    });
+
+    let default_methods: Vec<syn::ImplItemMethod> = vec![
+        parse_quote! {
+            #[pyo3(name = "__repr__")]
+            fn __pyo3__repr__(&self) -> &'static str {
+                match self {
+                    #(#repr_arms,)*
+                }
+            }
+        },
+        parse_quote! {
+            #[pyo3(name = "__int__")]
+            fn __pyo3__int__(&self) -> isize {
+                match self {
+                    #(#int_arms,)*
+                }
+            }
+        },
+        richcmp,
+        hash,
+    ];
+    let (default_methods_impl, default_slots) = gen_default_slots(cls, default_methods)?;
+
+    let class = impl_class(
+        cls,
+        args,
+        doc,
+        descriptors,
+        default_slots,
+        methods_type,
+        deprecations,
+    )?;
+    let into_pyobject = impl_into_py(cls, args);
+
+    Ok(quote! {
+        #class
+
+        #into_pyobject
+
+        #default_methods_impl
+    })
+}
+
+/// Enums with data are exposed as a base class, with one subclass per variant. The base class
+/// stores the Rust enum; the variant subclasses add no data of their own but provide a
+/// constructor and read-only attributes for the variant's fields.
+fn impl_complex_enum(
+    cls: &syn::Ident,
+    vis: &syn::Visibility,
+    args: &PyClassArgs,
+    variants: Vec<PyClassEnumVariant>,
+    doc: PythonDoc,
+    methods_type: PyClassMethodsType,
+    deprecations: Deprecations,
+) -> syn::Result<TokenStream> {
+    // The variant classes need to be able to subclass the base class. Both are frozen, so that
+    // `&mut self` methods can't replace the variant stored in a variant class object.
+    let base_args = PyClassArgs {
+        is_basetype: true,
+        is_frozen: true,
+        ..args.clone()
+    };
+
+    let variant_cls_idents: Vec<syn::Ident> = variants
+        .iter()
+        .map(|variant| format_ident!("{}_{}", cls.unraw(), variant.ident.unraw()))
+        .collect();
+
+    // Each variant class is accessible as a class attribute of the base class
+    let variant_attributes =
+        variants
+            .iter()
+            .zip(&variant_cls_idents)
+            .map(|(variant, variant_cls)| {
+                gen_class_attribute(
+                    &variant.python_name(),
+                    quote! {
+                        ::pyo3::IntoPy::into_py(
+                            <#variant_cls as ::pyo3::type_object::PyTypeObject>::type_object(py),
+                            py,
+                        )
+                    },
+                )
+            });
+    let descriptors = impl_descriptors(cls, variant_attributes.collect());
+
+    // The comparison slots are inherited by the variant classes
+    let (richcmp, hash) = gen_comparison_methods(args);
+    let (default_methods_impl, default_slots) =
+        gen_default_slots(cls, richcmp.into_iter().chain(hash).collect())?;
+
+    let class = impl_class(
+        cls,
+        &base_args,
+        doc,
+        descriptors,
+        default_slots,
+        methods_type,
+        deprecations,
+    )?;
+
+    // Converting the enum to Python creates an instance of the class for the active variant
+    let into_py_arms = variants
+        .iter()
+        .zip(&variant_cls_idents)
+        .map(|(variant, variant_cls)| {
+            let variant_ident = variant.ident;
+            quote! {
+                #cls::#variant_ident { .. } => {
+                    let initializer = ::pyo3::pyclass_init::PyClassInitializer::from(self)
+                        .add_subclass(#variant_cls);
+                    ::pyo3::Py::new(py, initializer).map(|obj| ::pyo3::IntoPy::into_py(obj, py))
+                }
+            }
+        });
+
+    let variant_classes = variants
+        .iter()
+        .zip(&variant_cls_idents)
+        .map(|(variant, variant_cls)| {
+            impl_complex_enum_variant(cls, vis, args, variant, variant_cls, methods_type)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #class
+
+        #default_methods_impl
+
+        impl ::pyo3::IntoPy<::pyo3::PyObject> for #cls {
+            fn into_py(self, py: ::pyo3::Python) -> ::pyo3::PyObject {
+                ::pyo3::IntoPy::try_into_py(self, py).unwrap()
+            }
+
+            fn try_into_py(self, py: ::pyo3::Python) -> ::pyo3::PyResult<::pyo3::PyObject> {
+                match self {
+                    #(#into_py_arms)*
+                }
+            }
+        }
+
+        #(#variant_classes)*
+    })
+}
+
+fn impl_complex_enum_variant(
+    cls: &syn::Ident,
+    vis: &syn::Visibility,
+    args: &PyClassArgs,
+    variant: &PyClassEnumVariant,
+    variant_cls: &syn::Ident,
+    methods_type: PyClassMethodsType,
+) -> syn::Result<TokenStream> {
+    let variant_args = PyClassArgs {
+        name: Some(variant.python_name()),
+        module: args.module.clone(),
+        base: parse_quote!(#cls),
+        has_extends: true,
+        is_frozen: true,
+        ..Default::default()
+    };
+    let doc = utils::get_doc(variant.attrs, None);
+
+    let field_idents = variant.field_idents();
+    let field_types: Vec<&syn::Type> = variant.fields.iter().map(|field| &field.ty).collect();
+    let pattern = variant.pattern(cls);
+
+    // `__match_args__` lets Python's `match` statement destructure the variant positionally
+    let match_args: Vec<String> = field_idents
+        .iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect();
+    let match_args_attribute = gen_class_attribute(
+        &format_ident!("__match_args__"),
+        quote! {
+            let names: &[&str] = &[#(#match_args),*];
+            ::pyo3::IntoPy::into_py(::pyo3::types::PyTuple::new(py, names), py)
+        },
+    );
+    let descriptors = impl_descriptors(variant_cls, vec![match_args_attribute]);
+
+    let class = impl_class(
+        variant_cls,
+        &variant_args,
+        doc,
+        descriptors,
+        Vec::new(),
+        methods_type,
+        Deprecations::default(),
+    )?;
+
+    let getters = field_idents.iter().zip(&field_types).map(|(ident, ty)| {
+        let getter_ident = format_ident!("__pyo3__get_{}", ident.unraw());
+        let unexpected_variant = format!(
+            "expected {}::{} in the base of a `{}` object",
+            cls,
+            variant.ident,
+            variant.python_name()
+        );
+        quote! {
+            #[getter(#ident)]
+            fn #getter_ident(slf: ::pyo3::PyRef<Self>) -> #ty {
+                match &*slf.into_super() {
+                    #[allow(unused_variables)]
+                    #pattern => ::std::clone::Clone::clone(#ident),
+                    _ => ::std::unreachable!(#unexpected_variant),
+                }
+            }
+        }
+    });
+
+    let mut methods: Vec<syn::ImplItem> = vec![parse_quote! {
+        #[new]
+        fn __pyo3__new__(#(#field_idents: #field_types),*) -> (Self, #cls) {
+            (#variant_cls, #pattern)
+        }
+    }];
+    for getter in getters {
+        methods.push(syn::parse2(getter)?);
+    }
+    let methods_impl = impl_methods(&parse_quote!(#variant_cls), &mut methods, methods_type)?;
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis struct #variant_cls;
+
+        #class
+
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        impl #variant_cls {
+            #(#methods)*
+        }
+
+        #methods_impl
+    })
+}
+
+/// Generates a class attribute whose value is computed by `value`, with `py` in scope.
+fn gen_class_attribute(python_name: &syn::Ident, value: TokenStream) -> TokenStream {
+    let python_name = format!("{}\0", python_name);
+    quote! {
+        ::pyo3::class::PyMethodDefType::ClassAttribute({
+            ::pyo3::class::PyClassAttributeDef::new(
+                #python_name,
+                ::pyo3::class::methods::PyClassAttributeFactory({
+                    fn __wrap(py: ::pyo3::Python<'_>) -> ::pyo3::PyObject {
+                        #value
+                    }
+                    __wrap
+                })
+            )
+        })
+    }
+}
+
+/// Generates `__richcmp__` and `__hash__` from the Rust `PartialEq`, `PartialOrd` and `Hash`
+/// implementations, as requested by the `eq`, `ord` and `hash` options.
+fn gen_comparison_methods(
+    args: &PyClassArgs,
+) -> (Option<syn::ImplItemMethod>, Option<syn::ImplItemMethod>) {
+    let richcmp = if args.has_eq {
+        let ord_arms = if args.has_ord {
+            quote! {
+                ::pyo3::class::basic::CompareOp::Lt => {
+                    ::std::cmp::PartialOrd::lt(self, other).to_object(py)
+                }
+                ::pyo3::class::basic::CompareOp::Le => {
+                    ::std::cmp::PartialOrd::le(self, other).to_object(py)
+                }
+                ::pyo3::class::basic::CompareOp::Gt => {
+                    ::std::cmp::PartialOrd::gt(self, other).to_object(py)
+                }
+                ::pyo3::class::basic::CompareOp::Ge => {
+                    ::std::cmp::PartialOrd::ge(self, other).to_object(py)
+                }
+            }
+        } else {
+            quote! { _ => py.NotImplemented(), }
+        };
+        Some(parse_quote! {
+            #[pyo3(name = "__richcmp__")]
+            fn __pyo3__richcmp__(
+                &self,
+                py: ::pyo3::Python,
+                other: &Self,
+                op: ::pyo3::class::basic::CompareOp,
+            ) -> ::pyo3::PyObject {
+                use ::pyo3::conversion::ToPyObject;
+                match op {
+                    ::pyo3::class::basic::CompareOp::Eq => {
+                        ::std::cmp::PartialEq::eq(self, other).to_object(py)
+                    }
+                    ::pyo3::class::basic::CompareOp::Ne => {
+                        ::std::cmp::PartialEq::ne(self, other).to_object(py)
+                    }
+                    #ord_arms
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let hash = if args.has_hash {
+        Some(parse_quote! {
+            #[pyo3(name = "__hash__")]
+            fn __pyo3__hash__(&self) -> u64 {
+                let mut hasher = ::std::collections::hash_map::DefaultHasher::new();
+                ::std::hash::Hash::hash(self, &mut hasher);
+                ::std::hash::Hasher::finish(&hasher)
+            }
+        })
+    } else {
+        None
+    };
+
+    (richcmp, hash)
+}
+
+/// Generates type slots for dunder methods which `#[pyclass]` provides by default.
+///
+/// Returns the `impl` block containing the Rust methods, along with the slots which call them.
+fn gen_default_slots(
+    cls: &syn::Ident,
+    mut methods: Vec<syn::ImplItemMethod>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let ty: syn::Type = parse_quote!(#cls);
+    let mut slots = Vec::with_capacity(methods.len());
+    for method in &mut methods {
+        let options = PyFunctionOptions::from_attrs(&mut method.attrs)?;
+        match gen_py_method(&ty, &mut method.sig, &mut method.attrs, options)? {
+            GeneratedPyMethod::Proto(slot) => slots.push(slot),
+            _ => unreachable!("default methods of #[pyclass] must be type slots"),
+        }
+    }
+    let methods_impl = quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        impl #cls {
+            #(#methods)*
+        }
+    };
+    Ok((methods_impl, slots))
+}
+
 fn impl_class(
     cls: &syn::Ident,
     attr: &PyClassArgs,
     doc: PythonDoc,
-    field_options: Vec<(&syn::Field, FieldPyO3Options)>,
+    descriptors: TokenStream,
+    default_slots: Vec<TokenStream>,
     methods_type: PyClassMethodsType,
     deprecations: Deprecations,
 ) -> syn::Result<TokenStream> {
@@ -410,8 +1105,6 @@ fn impl_class(
             }
         });
 
-    let descriptors = impl_descriptors(cls, field_options)?;
-
     // insert space for weak ref
     let weakref = if attr.has_weaklist {
         quote! { ::pyo3::pyclass_slots::PyClassWeakRefSlot }
@@ -478,19 +1171,6 @@ fn impl_class(
         quote! { ::pyo3::PyAny }
     };
 
-    // If #cls is not extended type, we allow Self->PyObject conversion
-    let into_pyobject = if !attr.has_extends {
-        quote! {
-            impl ::pyo3::IntoPy<::pyo3::PyObject> for #cls {
-                fn into_py(self, py: ::pyo3::Python) -> ::pyo3::PyObject {
-                    ::pyo3::IntoPy::into_py(::pyo3::Py::new(py, self).unwrap(), py)
-                }
-            }
-        }
-    } else {
-        quote! {}
-    };
-
     let thread_checker = if attr.has_unsendable {
         quote! { ::pyo3::class::impl_::ThreadCheckerImpl<#cls> }
     } else if attr.has_extends {
@@ -501,10 +1181,37 @@ fn impl_class(
         quote! { ::pyo3::class::impl_::ThreadCheckerStub<#cls> }
     };
 
+    // Frozen classes can't be mutably borrowed, so `&mut #cls` arguments are rejected
+    let (frozen, extract_mut) = if attr.is_frozen {
+        (quote! { ::pyo3::pyclass::boolean_struct::True }, None)
+    } else {
+        (
+            quote! { ::pyo3::pyclass::boolean_struct::False },
+            Some(quote! {
+                impl<'a> ::pyo3::derive_utils::ExtractExt<'a> for &'a mut #cls
+                {
+                    type Target = ::pyo3::PyRefMut<'a, #cls>;
+                }
+            }),
+        )
+    };
+
     let is_gc = attr.is_gc;
     let is_basetype = attr.is_basetype;
     let is_subclass = attr.has_extends;
 
+    let get_new_info = if cfg!(feature = "inspect") {
+        quote! {
+            fn get_new_info() -> ::std::option::Option<::pyo3::inspect::FunctionInfo> {
+                use ::pyo3::class::impl_::*;
+                let collector = PyClassImplCollector::<Self>::new();
+                collector.new_info()
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     Ok(quote! {
         unsafe impl ::pyo3::type_object::PyTypeInfo for #cls {
             type AsRefTarget = ::pyo3::PyCell<Self>;
@@ -526,6 +1233,7 @@ fn impl_class(
             type Dict = #dict;
             type WeakRef = #weakref;
             type BaseNativeType = #base_nativetype;
+            type Frozen = #frozen;
         }
 
         impl<'a> ::pyo3::derive_utils::ExtractExt<'a> for &'a #cls
@@ -533,12 +1241,7 @@ fn impl_class(
             type Target = ::pyo3::PyRef<'a, #cls>;
         }
 
-        impl<'a> ::pyo3::derive_utils::ExtractExt<'a> for &'a mut #cls
-        {
-            type Target = ::pyo3::PyRefMut<'a, #cls>;
-        }
-
-        #into_pyobject
+        #extract_mut
 
         #impl_inventory
 
@@ -569,6 +1272,12 @@ fn impl_class(
                 let collector = PyClassImplCollector::<Self>::new();
                 collector.new_impl()
             }
+            fn get_new_text_signature() -> ::std::option::Option<&'static str> {
+                use ::pyo3::class::impl_::*;
+                let collector = PyClassImplCollector::<Self>::new();
+                collector.new_text_signature()
+            }
+            #get_new_info
             fn get_alloc() -> ::std::option::Option<::pyo3::ffi::allocfunc> {
                 use ::pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<Self>::new();
@@ -589,6 +1298,8 @@ fn impl_class(
                 // Implementation which uses dtolnay specialization to load all slots.
                 use ::pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<Self>::new();
+                // Default slots come first so that user implementations take precedence
+                visitor(&[#(#default_slots),*]);
                 visitor(collector.object_protocol_slots());
                 visitor(collector.number_protocol_slots());
                 visitor(collector.iter_protocol_slots());
@@ -616,22 +1327,48 @@ fn impl_class(
     })
 }
 
-fn impl_descriptors(
+/// If #cls is not extended type, we allow Self->PyObject conversion
+fn impl_into_py(cls: &syn::Ident, attr: &PyClassArgs) -> Option<TokenStream> {
+    if attr.has_extends {
+        return None;
+    }
+    Some(quote! {
+        impl ::pyo3::IntoPy<::pyo3::PyObject> for #cls {
+            fn into_py(self, py: ::pyo3::Python) -> ::pyo3::PyObject {
+                ::pyo3::IntoPy::into_py(::pyo3::Py::new(py, self).unwrap(), py)
+            }
+
+            fn try_into_py(self, py: ::pyo3::Python) -> ::pyo3::PyResult<::pyo3::PyObject> {
+                ::pyo3::Py::new(py, self).map(|obj| ::pyo3::IntoPy::into_py(obj, py))
+            }
+
+            fn type_output() -> ::pyo3::inspect::types::TypeInfo {
+                ::pyo3::inspect::types::TypeInfo::of::<Self>()
+            }
+        }
+    })
+}
+
+fn field_descriptors(
     cls: &syn::Ident,
+    args: &PyClassArgs,
     field_options: Vec<(&syn::Field, FieldPyO3Options)>,
-) -> syn::Result<TokenStream> {
+) -> syn::Result<Vec<TokenStream>> {
     let ty = syn::parse_quote!(#cls);
-    let py_methods: Vec<TokenStream> = field_options
+    field_options
         .into_iter()
         .enumerate()
         .flat_map(|(field_index, (field, options))| {
-            let name_err = if options.name.is_some() && !options.get && !options.set {
+            let has_getter = options.has_getter(args);
+            let has_setter = options.has_setter(args);
+
+            let name_err = if options.name.is_some() && !has_getter && !has_setter {
                 Some(Err(err_spanned!(options.name.as_ref().unwrap().0.span() => "`name` is useless without `get` or `set`")))
             } else {
                 None
             };
 
-            let getter = if options.get {
+            let getter = if has_getter {
                 Some(impl_py_getter_def(&ty, PropertyType::Descriptor {
                     field_index,
                     field,
@@ -641,7 +1378,7 @@ fn impl_descriptors(
                 None
             };
 
-            let setter = if options.set {
+            let setter = if has_setter {
                 Some(impl_py_setter_def(&ty, PropertyType::Descriptor {
                     field_index,
                     field,
@@ -653,9 +1390,11 @@ fn impl_descriptors(
 
             name_err.into_iter().chain(getter).chain(setter)
         })
-        .collect::<syn::Result<_>>()?;
+        .collect::<syn::Result<_>>()
+}
 
-    Ok(quote! {
+fn impl_descriptors(cls: &syn::Ident, py_methods: Vec<TokenStream>) -> TokenStream {
+    quote! {
         impl ::pyo3::class::impl_::PyClassDescriptors<#cls>
             for ::pyo3::class::impl_::PyClassImplCollector<#cls>
         {
@@ -664,5 +1403,5 @@ fn impl_descriptors(
                 METHODS
             }
         }
-    })
+    }
 }
@@ -3,39 +3,111 @@
 use crate::{
     attributes::{
         self, get_deprecated_name_attribute, get_deprecated_text_signature_attribute,
-        get_pyo3_options, take_attributes, FromPyWithAttribute, NameAttribute,
+        get_pyo3_options, take_attributes, FromPyWithAttribute, NameAttribute, PanicAttribute,
         TextSignatureAttribute,
     },
     deprecations::Deprecations,
     method::{self, CallingConvention, FnArg},
     pymethod::check_generic,
-    utils::{self, ensure_not_async_fn},
+    utils,
 };
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::punctuated::Punctuated;
-use syn::{ext::IdentExt, spanned::Spanned, Ident, NestedMeta, Path, Result};
+use syn::{ext::IdentExt, spanned::Spanned, Ident, NestedMeta, Path, Result, Token};
 use syn::{
+    parenthesized,
     parse::{Parse, ParseBuffer, ParseStream},
     token::Comma,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Argument {
+    PosOnlyArgsSeparator,
     VarArgsSeparator,
     VarArgs(syn::Path),
     KeywordArgs(syn::Path),
-    Arg(syn::Path, Option<String>),
-    Kwarg(syn::Path, Option<String>),
+    Arg(syn::Path, Option<syn::Expr>),
+    Kwarg(syn::Path, Option<syn::Expr>),
 }
 
 /// The attributes of the pyfunction macro
 #[derive(Default)]
 pub struct PyFunctionSignature {
     pub arguments: Vec<Argument>,
+    /// Set for `signature = (...)`, which must list every argument of the Rust function.
+    pub python_syntax: bool,
     has_kw: bool,
     has_varargs: bool,
     has_kwargs: bool,
+    has_posonly_separator: bool,
+}
+
+/// A single entry of `signature = (...)`.
+enum SignatureItem {
+    PosOnlyArgsSeparator(Token![/]),
+    VarArgsSeparator(Token![*]),
+    VarArgs(Ident),
+    KeywordArgs(Ident),
+    Arg(Ident, Option<syn::Expr>),
+}
+
+impl Parse for SignatureItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(Token![/]) {
+            input.parse().map(SignatureItem::PosOnlyArgsSeparator)
+        } else if lookahead.peek(Token![*]) {
+            let star: Token![*] = input.parse()?;
+            if input.peek(Token![*]) {
+                let _: Token![*] = input.parse()?;
+                Ident::parse_any(input).map(SignatureItem::KeywordArgs)
+            } else if input.peek(Ident::peek_any) {
+                Ident::parse_any(input).map(SignatureItem::VarArgs)
+            } else {
+                Ok(SignatureItem::VarArgsSeparator(star))
+            }
+        } else if lookahead.peek(Ident::peek_any) {
+            let name = Ident::parse_any(input)?;
+            let default = if input.peek(Token![=]) {
+                let _: Token![=] = input.parse()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            Ok(SignatureItem::Arg(name, default))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+/// `signature = (a, b = 1, /, c = None, *args, d, **kwargs)`, with the same meaning as in a
+/// Python `def` statement and default values given as Rust expressions.
+pub struct SignatureAttribute {
+    pub kw: attributes::kw::signature,
+    pub signature: PyFunctionSignature,
+}
+
+impl Parse for SignatureAttribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let kw: attributes::kw::signature = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        let content;
+        let _ = parenthesized!(content in input);
+        let items = Punctuated::<SignatureItem, Token![,]>::parse_terminated(&content)?;
+        let mut signature = PyFunctionSignature {
+            python_syntax: true,
+            ..Default::default()
+        };
+        for item in items {
+            signature.add_signature_item(item)?;
+        }
+        if let Some(Argument::VarArgsSeparator) = signature.arguments.last() {
+            bail_spanned!(kw.span() => "named arguments must follow bare `*`");
+        }
+        Ok(SignatureAttribute { kw, signature })
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -117,6 +189,111 @@ impl PyFunctionSignature {
         Ok(())
     }
 
+    fn add_signature_item(&mut self, item: SignatureItem) -> syn::Result<()> {
+        match item {
+            SignatureItem::PosOnlyArgsSeparator(slash) => {
+                ensure_spanned!(
+                    !self.has_posonly_separator,
+                    slash.span() => "`/` may only be specified once"
+                );
+                ensure_spanned!(
+                    !(self.has_varargs || self.has_kwargs),
+                    slash.span() => "`/` must come before `*`, `*args` and `**kwargs`"
+                );
+                ensure_spanned!(
+                    !self.arguments.is_empty(),
+                    slash.span() => "at least one argument must precede `/`"
+                );
+                self.has_posonly_separator = true;
+                self.arguments.push(Argument::PosOnlyArgsSeparator);
+            }
+            SignatureItem::VarArgsSeparator(star) => {
+                ensure_spanned!(
+                    !(self.has_varargs || self.has_kwargs),
+                    star.span() => "`*` is not allowed after `*`, `*args` or `**kwargs`"
+                );
+                self.has_varargs = true;
+                self.arguments.push(Argument::VarArgsSeparator);
+            }
+            SignatureItem::VarArgs(name) => {
+                ensure_spanned!(
+                    !(self.has_varargs || self.has_kwargs),
+                    name.span() => "`*args` is not allowed after `*`, `*args` or `**kwargs`"
+                );
+                self.has_varargs = true;
+                self.arguments.push(Argument::VarArgs(name.into()));
+            }
+            SignatureItem::KeywordArgs(name) => {
+                ensure_spanned!(
+                    !self.has_kwargs,
+                    name.span() => "`**kwargs` may only be specified once"
+                );
+                self.has_kwargs = true;
+                self.arguments.push(Argument::KeywordArgs(name.into()));
+            }
+            SignatureItem::Arg(name, default) => {
+                ensure_spanned!(
+                    !self.has_kwargs,
+                    name.span() => "arguments are not allowed after `**kwargs`"
+                );
+                if self.has_varargs {
+                    self.arguments.push(Argument::Kwarg(name.into(), default));
+                } else {
+                    ensure_spanned!(
+                        default.is_some() || !self.has_kw,
+                        name.span() => "non-default argument follows default argument"
+                    );
+                    self.has_kw |= default.is_some();
+                    self.arguments.push(Argument::Arg(name.into(), default));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a `signature = (...)` lists exactly the arguments of the Rust function, in
+    /// the same order. `py: Python` arguments are not part of the signature.
+    pub fn validate(&self, args: &[FnArg<'_>]) -> syn::Result<()> {
+        if !self.python_syntax {
+            return Ok(());
+        }
+        let mut rust_args = args.iter().filter(|arg| !arg.py);
+        for argument in &self.arguments {
+            let path = match argument {
+                Argument::Arg(path, _)
+                | Argument::Kwarg(path, _)
+                | Argument::VarArgs(path)
+                | Argument::KeywordArgs(path) => path,
+                Argument::PosOnlyArgsSeparator | Argument::VarArgsSeparator => continue,
+            };
+            match rust_args.next() {
+                Some(arg) => ensure_spanned!(
+                    path.is_ident(arg.name),
+                    path.span() => format!(
+                        "expected argument from function definition `{}` but got argument `{}`",
+                        arg.name.unraw(),
+                        path.get_ident().unwrap().unraw()
+                    )
+                ),
+                None => bail_spanned!(
+                    path.span() => format!(
+                        "signature entry `{}` does not have a corresponding function argument",
+                        path.get_ident().unwrap().unraw()
+                    )
+                ),
+            }
+        }
+        if let Some(arg) = rust_args.next() {
+            bail_spanned!(
+                arg.name.span() => format!(
+                    "missing signature entry for argument `{}`",
+                    arg.name.unraw()
+                )
+            );
+        }
+        Ok(())
+    }
+
     fn add_literal(&mut self, item: &NestedMeta, lit: &syn::Lit) -> syn::Result<()> {
         match lit {
             syn::Lit::Str(lits) if lits.value() == "*" => {
@@ -163,7 +340,7 @@ impl PyFunctionSignature {
         &mut self,
         item: &NestedMeta,
         name: &syn::Path,
-        value: String,
+        value: syn::Expr,
     ) -> syn::Result<()> {
         self.kw_arg_is_ok(item)?;
         if self.has_varargs {
@@ -192,14 +369,14 @@ impl PyFunctionSignature {
                     self.has_kwargs = true;
                     self.arguments.push(Argument::KeywordArgs(nv.path.clone()));
                 } else {
-                    self.add_nv_common(item, &nv.path, litstr.value())?;
+                    self.add_nv_common(item, &nv.path, litstr.parse()?)?;
                 }
             }
             syn::Lit::Int(litint) => {
-                self.add_nv_common(item, &nv.path, format!("{}", litint))?;
+                self.add_nv_common(item, &nv.path, syn::parse_quote!(#litint))?;
             }
             syn::Lit::Bool(litb) => {
-                self.add_nv_common(item, &nv.path, format!("{}", litb.value))?;
+                self.add_nv_common(item, &nv.path, syn::parse_quote!(#litb))?;
             }
             _ => bail_spanned!(nv.lit.span() => "expected a string literal"),
         };
@@ -213,6 +390,7 @@ pub struct PyFunctionOptions {
     pub name: Option<NameAttribute>,
     pub signature: Option<PyFunctionSignature>,
     pub text_signature: Option<TextSignatureAttribute>,
+    pub panic: Option<PanicAttribute>,
     pub deprecations: Deprecations,
 }
 
@@ -223,6 +401,7 @@ impl Parse for PyFunctionOptions {
             name: None,
             signature: None,
             text_signature: None,
+            panic: None,
             deprecations: Deprecations::new(),
         };
 
@@ -232,6 +411,7 @@ impl Parse for PyFunctionOptions {
                 || lookahead.peek(attributes::kw::pass_module)
                 || lookahead.peek(attributes::kw::signature)
                 || lookahead.peek(attributes::kw::text_signature)
+                || lookahead.peek(attributes::kw::panic)
             {
                 options.add_attributes(std::iter::once(input.parse()?))?;
                 if !input.is_empty() {
@@ -253,8 +433,9 @@ impl Parse for PyFunctionOptions {
 pub enum PyFunctionOption {
     Name(NameAttribute),
     PassModule(attributes::kw::pass_module),
-    Signature(PyFunctionSignature),
+    Signature(SignatureAttribute),
     TextSignature(TextSignatureAttribute),
+    Panic(PanicAttribute),
 }
 
 impl Parse for PyFunctionOption {
@@ -268,6 +449,8 @@ impl Parse for PyFunctionOption {
             input.parse().map(PyFunctionOption::Signature)
         } else if lookahead.peek(attributes::kw::text_signature) {
             input.parse().map(PyFunctionOption::TextSignature)
+        } else if lookahead.peek(attributes::kw::panic) {
+            input.parse().map(PyFunctionOption::Panic)
         } else {
             Err(lookahead.error())
         }
@@ -322,10 +505,9 @@ impl PyFunctionOptions {
                 PyFunctionOption::Signature(signature) => {
                     ensure_spanned!(
                         self.signature.is_none(),
-                        // FIXME: improve the span of this error message
-                        Span::call_site() => "`signature` may only be specified once"
+                        signature.kw.span() => "`signature` may only be specified once"
                     );
-                    self.signature = Some(signature);
+                    self.signature = Some(signature.signature);
                 }
                 PyFunctionOption::TextSignature(text_signature) => {
                     ensure_spanned!(
@@ -334,6 +516,13 @@ impl PyFunctionOptions {
                     );
                     self.text_signature = Some(text_signature);
                 }
+                PyFunctionOption::Panic(panic) => {
+                    ensure_spanned!(
+                        self.panic.is_none(),
+                        panic.kw.span() => "`panic` may only be specified once"
+                    );
+                    self.panic = Some(panic);
+                }
             }
         }
         Ok(())
@@ -370,7 +559,6 @@ pub fn impl_wrap_pyfunction(
     options: PyFunctionOptions,
 ) -> syn::Result<(Ident, TokenStream)> {
     check_generic(&func.sig)?;
-    ensure_not_async_fn(&func.sig)?;
 
     let python_name = options
         .name
@@ -398,37 +586,45 @@ pub fn impl_wrap_pyfunction(
         );
     }
 
+    signature.validate(&arguments)?;
+
     let ty = method::get_return_info(&func.sig.output);
 
+    let tp = if options.pass_module.is_some() {
+        method::FnType::FnModule
+    } else {
+        method::FnType::FnStatic
+    };
+    let text_signature = match &options.text_signature {
+        Some(attr) => Some(attr.lit.value()),
+        None if utils::doc_has_text_signature(&func.attrs, &python_name) => None,
+        None => method::text_signature(&tp, &arguments, &signature.arguments),
+    };
     let doc = utils::get_doc(
         &func.attrs,
-        options
-            .text_signature
-            .as_ref()
-            .map(|attr| (&python_name, attr)),
+        text_signature.map(|signature| (&python_name, signature)),
     );
 
     let function_wrapper_ident = function_wrapper_ident(&func.sig.ident);
 
     let spec = method::FnSpec {
-        tp: if options.pass_module.is_some() {
-            method::FnType::FnModule
-        } else {
-            method::FnType::FnStatic
-        },
+        tp,
         name: &func.sig.ident,
         convention: CallingConvention::from_args(&arguments, &signature.arguments),
         python_name,
+        python_signature: signature.python_syntax,
         attrs: signature.arguments,
         args: arguments,
         output: ty,
         doc,
         deprecations: options.deprecations,
+        panic: options.panic,
+        asyncness: func.sig.asyncness,
     };
 
     let wrapper_ident = format_ident!("__pyo3_raw_{}", spec.name);
     let wrapper = spec.get_wrapper_function(&wrapper_ident, None)?;
-    let methoddef = spec.get_methoddef(wrapper_ident);
+    let methoddef = spec.get_methoddef(wrapper_ident, None);
 
     let wrapped_pyfunction = quote! {
         #wrapper
@@ -460,7 +656,7 @@ fn type_is_pymodule(ty: &syn::Type) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{Argument, PyFunctionSignature};
+    use super::{Argument, PyFunctionSignature, SignatureAttribute};
     use proc_macro2::TokenStream;
     use quote::quote;
     use syn::parse_quote;
@@ -485,7 +681,7 @@ mod tests {
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
                 Argument::Arg(parse_quote! {test2}, None),
-                Argument::Arg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test3}, Some(parse_quote! {None})),
             ]
         );
     }
@@ -496,9 +692,9 @@ mod tests {
         assert!(
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
-                Argument::Arg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test2}, Some(parse_quote! {None})),
                 Argument::VarArgsSeparator,
-                Argument::Kwarg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test3}, Some(parse_quote! {None})),
             ]
         );
 
@@ -516,7 +712,7 @@ mod tests {
             args == vec![
                 Argument::VarArgsSeparator,
                 Argument::Kwarg(parse_quote! {test1}, None),
-                Argument::Kwarg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test2}, Some(parse_quote! {None})),
             ]
         );
 
@@ -524,7 +720,7 @@ mod tests {
         assert!(
             args == vec![
                 Argument::VarArgsSeparator,
-                Argument::Kwarg(parse_quote! {test1}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test1}, Some(parse_quote! {None})),
                 Argument::Kwarg(parse_quote! {test2}, None),
             ]
         );
@@ -537,11 +733,51 @@ mod tests {
         assert!(
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
-                Argument::Arg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test2}, Some(parse_quote! {None})),
+                Argument::VarArgs(parse_quote! {args}),
+                Argument::Kwarg(parse_quote! {test3}, Some(parse_quote! {None})),
+                Argument::KeywordArgs(parse_quote! {kwargs}),
+            ]
+        );
+    }
+
+    fn signature(input: TokenStream) -> syn::Result<Vec<Argument>> {
+        let attr: SignatureAttribute = syn::parse2(quote! {signature = (#input)})?;
+        Ok(attr.signature.arguments)
+    }
+
+    #[test]
+    fn test_signature() {
+        let args = signature(quote! {a, b = 1, /, c = None, *args, d, **kwargs}).unwrap();
+        assert!(
+            args == vec![
+                Argument::Arg(parse_quote! {a}, None),
+                Argument::Arg(parse_quote! {b}, Some(parse_quote! {1})),
+                Argument::PosOnlyArgsSeparator,
+                Argument::Arg(parse_quote! {c}, Some(parse_quote! {None})),
                 Argument::VarArgs(parse_quote! {args}),
-                Argument::Kwarg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {d}, None),
                 Argument::KeywordArgs(parse_quote! {kwargs}),
             ]
         );
+
+        let args = signature(quote! {*, a = vec![1, 2]}).unwrap();
+        assert!(
+            args == vec![
+                Argument::VarArgsSeparator,
+                Argument::Kwarg(parse_quote! {a}, Some(parse_quote! {vec![1, 2]})),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_errs() {
+        assert!(signature(quote! {a = 1, b}).is_err());
+        assert!(signature(quote! {/, a}).is_err());
+        assert!(signature(quote! {a, /, /}).is_err());
+        assert!(signature(quote! {*args, a, /}).is_err());
+        assert!(signature(quote! {*, *args}).is_err());
+        assert!(signature(quote! {a, *}).is_err());
+        assert!(signature(quote! {**kwargs, a}).is_err());
     }
 }
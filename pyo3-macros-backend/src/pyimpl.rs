@@ -13,6 +13,7 @@ use quote::quote;
 use syn::spanned::Spanned;
 
 /// The mechanism used to collect `#[pymethods]` into the type object
+#[derive(Clone, Copy)]
 pub enum PyClassMethodsType {
     Specialization,
     Inventory,
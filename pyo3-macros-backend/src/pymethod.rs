@@ -2,12 +2,12 @@
 
 use std::borrow::Cow;
 
-use crate::attributes::NameAttribute;
+use crate::attributes::{handle_panic, NameAttribute};
 use crate::method::ExtractErrorMode;
-use crate::utils::{ensure_not_async_fn, unwrap_ty_group, PythonDoc};
+use crate::utils::{unwrap_ty_group, PythonDoc};
 use crate::{deprecations::Deprecations, utils};
 use crate::{
-    method::{FnArg, FnSpec, FnType, SelfType},
+    method::{return_type_hint, text_signature, type_hint, FnArg, FnSpec, FnType, SelfType},
     pyfunction::PyFunctionOptions,
 };
 use proc_macro2::{Span, TokenStream};
@@ -29,12 +29,18 @@ pub fn gen_py_method(
     options: PyFunctionOptions,
 ) -> Result<GeneratedPyMethod> {
     check_generic(sig)?;
-    ensure_not_async_fn(sig)?;
     ensure_function_options_valid(&options)?;
     let spec = FnSpec::parse(sig, &mut *meth_attrs, options)?;
 
     let method_name = spec.python_name.to_string();
 
+    if let Some(asyncness) = &spec.asyncness {
+        ensure_spanned!(
+            pyproto(&method_name).is_none() && pyproto_fragment(&method_name).is_none(),
+            asyncness.span() => "`async fn` is not allowed for protocol methods"
+        );
+    }
+
     if let Some(slot_def) = pyproto(&method_name) {
         let slot = slot_def.generate_type_slot(cls, &spec)?;
         return Ok(GeneratedPyMethod::Proto(slot));
@@ -115,7 +121,7 @@ pub fn impl_py_method_def(
         FnType::FnClass => quote!(Class),
         _ => quote!(Method),
     };
-    let methoddef = spec.get_methoddef(quote! {{ #wrapper_def #wrapper_ident }});
+    let methoddef = spec.get_methoddef(quote! {{ #wrapper_def #wrapper_ident }}, Some(cls));
     Ok(quote! {
         ::pyo3::class::PyMethodDefType::#methoddef_type(#methoddef #add_flags)
     })
@@ -124,6 +130,23 @@ pub fn impl_py_method_def(
 fn impl_py_method_def_new(cls: &syn::Type, spec: &FnSpec) -> Result<TokenStream> {
     let wrapper_ident = syn::Ident::new("__wrap", Span::call_site());
     let wrapper = spec.get_wrapper_function(&wrapper_ident, Some(cls))?;
+    let text_signature = match text_signature(&spec.tp, &spec.args, &spec.attrs) {
+        Some(text_signature) => quote!(::std::option::Option::Some(#text_signature)),
+        None => quote!(::std::option::Option::None),
+    };
+    let info = if cfg!(feature = "inspect") {
+        let info = match spec.function_info(Some(cls)) {
+            Some(info) => quote!(::std::option::Option::Some((#info)())),
+            None => quote!(::std::option::Option::None),
+        };
+        quote! {
+            fn new_info(self) -> ::std::option::Option<::pyo3::inspect::FunctionInfo> {
+                #info
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
     Ok(quote! {
         impl ::pyo3::class::impl_::PyClassNewImpl<#cls> for ::pyo3::class::impl_::PyClassImplCollector<#cls> {
             fn new_impl(self) -> ::std::option::Option<::pyo3::ffi::newfunc> {
@@ -132,6 +155,10 @@ fn impl_py_method_def_new(cls: &syn::Type, spec: &FnSpec) -> Result<TokenStream>
                     #wrapper_ident
                 })
             }
+            fn new_text_signature(self) -> ::std::option::Option<&'static str> {
+                #text_signature
+            }
+            #info
         }
     })
 }
@@ -215,7 +242,28 @@ pub fn impl_py_setter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
         }
         PropertyType::Function { spec, .. } => impl_call_setter(cls, spec)?,
     };
+    let value_type = match property_type {
+        PropertyType::Descriptor { field, .. } => &field.ty,
+        PropertyType::Function { spec, .. } => split_off_python_arg(&spec.args).1[0].ty,
+    };
+    let type_info = if cfg!(feature = "inspect") {
+        let type_info = type_hint(value_type, Some(cls), quote!(type_input));
+        quote! {
+            .type_info(::pyo3::class::methods::PyTypeInfoFactory({
+                fn type_info() -> ::pyo3::inspect::types::TypeInfo {
+                    #[allow(unused_imports)]
+                    use ::pyo3::inspect::ExtractTypeHint as _;
+                    #type_info
+                }
+                type_info
+            }))
+        }
+    } else {
+        TokenStream::new()
+    };
 
+    let handle_panic =
+        property_type.handle_panic(cls, &syn::Ident::new("__wrap", Span::call_site()));
     let slf = match property_type {
         PropertyType::Descriptor { .. } => {
             SelfType::Receiver { mutable: true }.receiver(cls, ExtractErrorMode::Raise)
@@ -235,7 +283,7 @@ pub fn impl_py_setter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
                         _value: *mut ::pyo3::ffi::PyObject,
                         _: *mut ::std::os::raw::c_void
                     ) -> ::std::os::raw::c_int {
-                        ::pyo3::callback::handle_panic(|_py| {
+                        #handle_panic(|_py| {
                             #slf
                             let _value = _py
                                 .from_borrowed_ptr_or_opt(_value)
@@ -251,6 +299,7 @@ pub fn impl_py_setter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
                 }),
                 #doc
             )
+            #type_info
         })
     })
 }
@@ -295,7 +344,29 @@ pub fn impl_py_getter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
         }
         PropertyType::Function { spec, .. } => impl_call_getter(cls, spec)?,
     };
+    let type_info = if cfg!(feature = "inspect") {
+        let type_info = match property_type {
+            PropertyType::Descriptor { field, .. } => {
+                type_hint(&field.ty, Some(cls), quote!(type_output))
+            }
+            PropertyType::Function { spec, .. } => return_type_hint(&spec.output, Some(cls)),
+        };
+        quote! {
+            .type_info(::pyo3::class::methods::PyTypeInfoFactory({
+                fn type_info() -> ::pyo3::inspect::types::TypeInfo {
+                    #[allow(unused_imports)]
+                    use ::pyo3::inspect::IntoTypeHint as _;
+                    #type_info
+                }
+                type_info
+            }))
+        }
+    } else {
+        TokenStream::new()
+    };
 
+    let handle_panic =
+        property_type.handle_panic(cls, &syn::Ident::new("__wrap", Span::call_site()));
     let slf = match property_type {
         PropertyType::Descriptor { .. } => {
             SelfType::Receiver { mutable: false }.receiver(cls, ExtractErrorMode::Raise)
@@ -314,7 +385,7 @@ pub fn impl_py_getter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
                         _slf: *mut ::pyo3::ffi::PyObject,
                         _: *mut ::std::os::raw::c_void
                     ) -> *mut ::pyo3::ffi::PyObject {
-                        ::pyo3::callback::handle_panic(|_py| {
+                        #handle_panic(|_py| {
                             #slf
                             ::pyo3::callback::convert(_py, #getter_impl)
                         })
@@ -323,6 +394,7 @@ pub fn impl_py_getter_def(cls: &syn::Type, property_type: PropertyType) -> Resul
                 }),
                 #doc
             )
+            #type_info
         })
     })
 }
@@ -378,6 +450,14 @@ impl PropertyType<'_> {
         }
     }
 
+    fn handle_panic(&self, cls: &syn::Type, wrapper: &syn::Ident) -> TokenStream {
+        let panic = match self {
+            PropertyType::Descriptor { .. } => None,
+            PropertyType::Function { spec, .. } => spec.panic.as_ref(),
+        };
+        handle_panic(panic, Some(cls), wrapper)
+    }
+
     fn doc(&self) -> Cow<PythonDoc> {
         match self {
             PropertyType::Descriptor { field, .. } => {
@@ -751,6 +831,11 @@ impl SlotDef {
             return_mode,
         } = self;
         let py = syn::Ident::new("_py", Span::call_site());
+        let handle_panic = handle_panic(
+            spec.panic.as_ref(),
+            Some(cls),
+            &syn::Ident::new("__wrap", Span::call_site()),
+        );
         let method_arguments = generate_method_arguments(arguments);
         let ret_ty = ret_ty.ffi_type();
         let body = generate_method_body(
@@ -765,7 +850,7 @@ impl SlotDef {
             unsafe extern "C" fn __wrap(_raw_slf: *mut ::pyo3::ffi::PyObject, #(#method_arguments),*) -> #ret_ty {
                 let _slf = _raw_slf;
                 #before_call_method
-                ::pyo3::callback::handle_panic(|#py| {
+                #handle_panic(|#py| {
                     #body
                 })
             }
@@ -6,8 +6,8 @@ use crate::proto_method::impl_method_proto;
 use crate::pyfunction::PyFunctionOptions;
 use crate::pymethod;
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
 use quote::ToTokens;
+use quote::{quote, quote_spanned};
 use std::collections::HashSet;
 use syn::spanned::Spanned;
 
@@ -55,6 +55,20 @@ fn impl_proto_impl(
 
     for iimpl in impls.iter_mut() {
         if let syn::ImplItem::Method(met) = iimpl {
+            if let Some(span) = mutable_receiver_span(&met.sig) {
+                // frozen classes can't be mutably borrowed
+                let assert_mutable = quote_spanned! { span =>
+                    ::pyo3::class::impl_::assert_mutable(slf)
+                };
+                quote! {
+                    const _: () = {
+                        fn check(slf: &#ty) {
+                            #assert_mutable
+                        }
+                    };
+                }
+                .to_tokens(&mut trait_impls);
+            }
             // impl Py~Protocol<'p> { type = ... }
             if let Some(m) = proto.get_proto(&met.sig.ident) {
                 impl_method_proto(ty, &mut met.sig, &module, m)?.to_tokens(&mut trait_impls);
@@ -94,6 +108,21 @@ fn impl_proto_impl(
     })
 }
 
+/// Returns the span of the receiver if `sig` takes `&mut self`.
+///
+/// (`slf: PyRefMut<Self>` receivers are already rejected for frozen classes by the bounds of the
+/// protocol traits.)
+fn mutable_receiver_span(sig: &syn::Signature) -> Option<Span> {
+    match sig.inputs.first()? {
+        syn::FnArg::Receiver(receiver)
+            if receiver.reference.is_some() && receiver.mutability.is_some() =>
+        {
+            Some(receiver.span())
+        }
+        _ => None,
+    }
+}
+
 fn impl_normal_methods(
     py_methods: Vec<TokenStream>,
     ty: &syn::Type,
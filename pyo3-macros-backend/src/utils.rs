@@ -1,9 +1,6 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use syn::spanned::Spanned;
-
-use crate::attributes::TextSignatureAttribute;
 
 /// Macro inspired by `anyhow::anyhow!` to create a compiler error with the given span.
 macro_rules! err_spanned {
@@ -68,7 +65,7 @@ pub struct PythonDoc(TokenStream);
 /// e.g. concat!("...", "\n", "\0")
 pub fn get_doc(
     attrs: &[syn::Attribute],
-    text_signature: Option<(&syn::Ident, &TextSignatureAttribute)>,
+    text_signature: Option<(&syn::Ident, String)>,
 ) -> PythonDoc {
     let mut tokens = TokenStream::new();
     let comma = syn::token::Comma(Span::call_site());
@@ -79,11 +76,7 @@ pub fn get_doc(
     syn::token::Bracket(Span::call_site()).surround(&mut tokens, |tokens| {
         if let Some((python_name, text_signature)) = text_signature {
             // create special doc string lines to set `__text_signature__`
-            let signature_lines = format!(
-                "{}{}\n--\n\n",
-                python_name.to_string(),
-                text_signature.lit.value()
-            );
+            let signature_lines = format!("{}{}\n--\n\n", python_name.to_string(), text_signature);
             signature_lines.to_tokens(tokens);
             comma.to_tokens(tokens);
         }
@@ -127,6 +120,23 @@ pub fn get_doc(
     PythonDoc(tokens)
 }
 
+/// Returns true if the docstring starts with a signature line such as `name(a, b)`, which
+/// CPython reads as the `__text_signature__`.
+pub fn doc_has_text_signature(attrs: &[syn::Attribute], python_name: &syn::Ident) -> bool {
+    let first_line = attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("doc"))
+        .and_then(|attr| syn::parse2::<DocArgs>(attr.tokens.clone()).ok())
+        .and_then(|doc| syn::parse2::<syn::LitStr>(doc.token_stream).ok());
+    match first_line {
+        Some(line) => line
+            .value()
+            .trim_start()
+            .starts_with(&format!("{}(", python_name)),
+        None => false,
+    }
+}
+
 impl quote::ToTokens for PythonDoc {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.0.to_tokens(tokens)
@@ -149,17 +159,6 @@ impl syn::parse::Parse for DocArgs {
     }
 }
 
-pub fn ensure_not_async_fn(sig: &syn::Signature) -> syn::Result<()> {
-    if let Some(asyncness) = &sig.asyncness {
-        bail_spanned!(
-            asyncness.span() => "`async fn` is not yet supported for Python functions.\n\n\
-            Additional crates such as `pyo3-asyncio` can be used to integrate async Rust and \
-            Python. For more information, see https://github.com/PyO3/pyo3/issues/1632"
-        );
-    };
-    Ok(())
-}
-
 pub fn unwrap_group(mut expr: &syn::Expr) -> &syn::Expr {
     while let syn::Expr::Group(g) = expr {
         expr = &*g.expr;
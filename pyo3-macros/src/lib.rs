@@ -7,9 +7,10 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use pyo3_macros_backend::{
-    build_derive_from_pyobject, build_py_class, build_py_function, build_py_methods,
-    build_py_proto, get_doc, process_functions_in_module, py_init, PyClassArgs, PyClassMethodsType,
-    PyFunctionOptions, PyModuleOptions,
+    build_derive_from_pyobject, build_derive_into_pyobject, build_derive_py_exception,
+    build_py_class, build_py_enum, build_py_function, build_py_methods, build_py_proto, get_doc,
+    process_functions_in_module, py_init, PyClassArgs, PyClassMethodsType, PyFunctionOptions,
+    PyModuleOptions,
 };
 use quote::quote;
 use syn::parse_macro_input;
@@ -26,6 +27,8 @@ use syn::parse_macro_input;
 /// |  Annotation  |  Description |
 /// | :-  | :- |
 /// | `#[pyo3(name = "...")]` | Defines the name of the module in Python. |
+/// | `#[pyo3(panic = "...")]` | Sets how panics are handled when initializing the module, and in the functions and the methods of the classes it adds which don't set their own `panic` option, see [`#[pyfunction]`](macro@crate::pyfunction). |
+/// | `#[pyo3(multi_phase)]` | Uses multi-phase initialization (PEP 489), which creates a new module object with room for a `ModuleState` on each import. |
 ///
 /// For more on creating Python modules see the [module section of the guide][1].
 ///
@@ -85,7 +88,7 @@ pub fn pyproto(_: TokenStream, input: TokenStream) -> TokenStream {
     .into()
 }
 
-/// A proc macro used to expose Rust structs as Python objects.
+/// A proc macro used to expose Rust structs and enums as Python objects.
 ///
 /// `#[pyclass]` accepts the following [parameters][2]:
 ///
@@ -99,6 +102,20 @@ pub fn pyproto(_: TokenStream, input: TokenStream) -> TokenStream {
 /// | `subclass` | Allows other Python classes and `#[pyclass]` to inherit from this class.  |
 /// | `unsendable` | Required if your struct is not [`Send`][3]. Rather than using `unsendable`, consider implementing your struct in a threadsafe way by e.g. substituting [`Rc`][8] with [`Arc`][9]. By using `unsendable`, your class will panic when accessed by another thread.|
 /// | <span style="white-space: pre">`module = "module_name"`</span> |  Python code will see the class as being defined in this module. Defaults to `builtins`. |
+/// | `frozen` | Declares the class to be immutable. `&mut self` methods and setters are rejected at compile time, and borrowing the class no longer needs to update a borrow flag. |
+/// | `eq` | Implements `__eq__` and `__ne__` using the [`PartialEq`] implementation of the struct. |
+/// | `ord` | Implements `__lt__`, `__le__`, `__gt__` and `__ge__` using the [`PartialOrd`] implementation of the struct. Requires `eq`. |
+/// | `hash` | Implements `__hash__` using the [`Hash`](std::hash::Hash) implementation of the struct. Requires `eq` and `frozen`. |
+/// | `get_all` | Generates getters for all fields of the struct. Fields can opt out with `#[pyo3(skip)]`. |
+/// | `set_all` | Generates setters for all fields of the struct. Fields can opt out with `#[pyo3(skip)]`. |
+///
+/// When used on a fieldless enum, each variant becomes a class attribute holding an instance of
+/// the class, and `__repr__`, `__int__`, `__eq__` and `__hash__` are generated. `extends` is not
+/// supported for enums.
+///
+/// When used on an enum with fields, the enum becomes a base class with one subclass per variant.
+/// Each variant class has a constructor taking the variant's fields, and exposes the fields as
+/// read-only attributes.
 ///
 /// For more on creating Python classes,
 /// see the [class section of the guide][1].
@@ -172,8 +189,10 @@ pub fn pymethods(_: TokenStream, input: TokenStream) -> TokenStream {
 /// |  Annotation  |  Description |
 /// | :-  | :- |
 /// | `#[pyo3(name = "...")]` | Defines the name of the function in Python. |
-/// | `#[pyo3(text_signature = "...")]` | Defines the `__text_signature__` attribute of the function in Python. |
+/// | `#[pyo3(signature = (...))]` | Defines the arguments of the function in Python syntax, e.g. `(a, /, b = 1, *args, **kwargs)`. |
+/// | `#[pyo3(text_signature = "...")]` | Overrides the `__text_signature__` attribute of the function in Python, which is otherwise generated from its arguments. |
 /// | `#[pyo3(pass_module)]` | Passes the module containing the function as a `&PyModule` first argument to the function. |
+/// | `#[pyo3(panic = "...")]` | Sets how a panic in the function is handled: `"raise"` raises a `PanicException` (the default), `"raise_as = ExceptionType"` raises the given exception type instead and `"abort"` aborts the process. Also available on methods in `#[pymethods]`. |
 ///
 /// For more on exposing functions see the [function section of the guide][1].
 ///
@@ -202,15 +221,42 @@ pub fn derive_from_py_object(item: TokenStream) -> TokenStream {
     .into()
 }
 
+#[proc_macro_derive(IntoPyObject, attributes(pyo3))]
+pub fn derive_into_py_object(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    let expanded = build_derive_into_pyobject(&ast).unwrap_or_else(|e| e.to_compile_error());
+    quote!(
+        #expanded
+    )
+    .into()
+}
+
+#[proc_macro_derive(PyException, attributes(pyo3))]
+pub fn derive_py_exception(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    let expanded = build_derive_py_exception(&ast).unwrap_or_else(|e| e.to_compile_error());
+    quote!(
+        #expanded
+    )
+    .into()
+}
+
 fn pyclass_impl(
     attr: TokenStream,
     input: TokenStream,
     methods_type: PyClassMethodsType,
 ) -> TokenStream {
-    let mut ast = parse_macro_input!(input as syn::ItemStruct);
+    let mut ast = parse_macro_input!(input as syn::Item);
     let args = parse_macro_input!(attr as PyClassArgs);
-    let expanded =
-        build_py_class(&mut ast, &args, methods_type).unwrap_or_else(|e| e.to_compile_error());
+    let expanded = match &mut ast {
+        syn::Item::Struct(struct_) => build_py_class(struct_, &args, methods_type),
+        syn::Item::Enum(enum_) => build_py_enum(enum_, &args, methods_type),
+        unsupported => Err(syn::Error::new_spanned(
+            unsupported,
+            "#[pyclass] only supports structs and enums.",
+        )),
+    }
+    .unwrap_or_else(|e| e.to_compile_error());
 
     quote!(
         #ast
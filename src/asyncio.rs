@@ -0,0 +1,237 @@
+//! Conversions between Python awaitables and Rust futures, for code which runs `asyncio` event
+//! loops alongside Rust async runtimes.
+//!
+//! [`into_future`] schedules an awaitable on an event loop and returns a [`PyFuture`], which can be
+//! awaited by any Rust executor. In the other direction, [`future_into_py`] runs a Rust
+//! future on an event loop and returns an `asyncio.Future` for its result. Both can be called from
+//! any thread, not only the one running the event loop.
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//!
+//! /// Calls a Python `async def` function from a Rust async runtime, while `event_loop` runs in
+//! /// another thread.
+//! async fn call_python(event_loop: PyObject, function: PyObject) -> PyResult<PyObject> {
+//!     let future = Python::with_gil(|py| {
+//!         let coroutine = function.call0(py)?;
+//!         pyo3::asyncio::into_future(coroutine.as_ref(py), event_loop.as_ref(py))
+//!     })?;
+//!     // The GIL is not held while waiting
+//!     future.await
+//! }
+//! ```
+
+use crate::callback::IntoPyCallbackOutput;
+use crate::coroutine::Coroutine;
+use crate::once_cell::GILOnceCell;
+use crate::panic::{PanicPolicy, RaisePanic};
+use crate::types::PyModule;
+use crate::{pyclass, pymethods, Py, PyAny, PyObject, PyResult, Python};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+const HELPERS: &str = r#"
+import asyncio
+import concurrent.futures
+import inspect
+
+
+async def _await(awaitable):
+    return await awaitable
+
+
+def run_awaitable(awaitable, loop, callback):
+    if not inspect.isawaitable(awaitable):
+        raise TypeError(
+            "object {} can't be used in 'await' expression".format(type(awaitable).__name__)
+        )
+    future = asyncio.run_coroutine_threadsafe(_await(awaitable), loop)
+    future.add_done_callback(callback)
+    return future
+
+
+def result(future):
+    try:
+        return future.result()
+    except concurrent.futures.CancelledError:
+        raise asyncio.CancelledError() from None
+
+
+def spawn(loop, coroutine):
+    future = loop.create_future()
+
+    def start():
+        if future.cancelled():
+            coroutine.close()
+            return
+        task = loop.create_task(coroutine)
+
+        def on_task_done(task):
+            if future.cancelled():
+                return
+            if task.cancelled():
+                future.cancel()
+            elif task.exception() is not None:
+                future.set_exception(task.exception())
+            else:
+                future.set_result(task.result())
+
+        def on_future_done(future):
+            if future.cancelled():
+                task.cancel()
+
+        task.add_done_callback(on_task_done)
+        future.add_done_callback(on_future_done)
+
+    loop.call_soon_threadsafe(start)
+    return future
+"#;
+
+/// Returns a module running `HELPERS`, which isn't added to `sys.modules`, so it can't replace
+/// or be replaced by an importable module.
+fn helpers<'py>(py: Python<'py>) -> PyResult<&'py PyModule> {
+    static HELPERS_MODULE: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+    HELPERS_MODULE.get_or_try_init_in_interpreter(py, || {
+        let module = PyModule::new(py, "pyo3._asyncio_helpers")?;
+        py.run(HELPERS, Some(module.dict()), None)?;
+        Ok(module.into())
+    })
+}
+
+/// Schedules `awaitable`, e.g. a coroutine or an `asyncio.Future`, on `event_loop` and returns a
+/// Rust future for its result.
+///
+/// The awaitable starts running on the event loop right away, whether or not the returned future
+/// is polled. Polling it only acquires the GIL once the awaitable is complete, to fetch its
+/// result.
+///
+/// Dropping the [`PyFuture`] before it completes cancels the awaitable. If the awaitable is
+/// cancelled on the Python side, the future resolves to an
+/// [`asyncio.CancelledError`](crate::exceptions::asyncio::CancelledError).
+///
+/// Returns a `TypeError` if `awaitable` is not awaitable.
+pub fn into_future(awaitable: &PyAny, event_loop: &PyAny) -> PyResult<PyFuture> {
+    let py = awaitable.py();
+    let state = Arc::new(Mutex::new(PyFutureState {
+        done: false,
+        waker: None,
+    }));
+    let callback = Py::new(
+        py,
+        WakeCallback {
+            state: state.clone(),
+        },
+    )?;
+    let future = helpers(py)?.call_method1("run_awaitable", (awaitable, event_loop, callback))?;
+    Ok(PyFuture {
+        future: future.into(),
+        state,
+    })
+}
+
+/// A Rust future for the result of a Python awaitable, created by [`into_future`].
+///
+/// The awaitable runs on an `asyncio` event loop; this future only waits for it to complete.
+pub struct PyFuture {
+    // The `concurrent.futures.Future` returned by `asyncio.run_coroutine_threadsafe`
+    future: PyObject,
+    state: Arc<Mutex<PyFutureState>>,
+}
+
+struct PyFutureState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+impl Future for PyFuture {
+    type Output = PyResult<PyObject>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        {
+            let mut state = self.state.lock();
+            if !state.done {
+                state.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+        Poll::Ready(Python::with_gil(|py| {
+            Ok(helpers(py)?.call_method1("result", (&self.future,))?.into())
+        }))
+    }
+}
+
+impl Drop for PyFuture {
+    fn drop(&mut self) {
+        if !self.state.lock().done {
+            Python::with_gil(|py| {
+                if let Err(err) = self.future.call_method0(py, "cancel") {
+                    err.print(py);
+                }
+            })
+        }
+    }
+}
+
+/// Marks a [`PyFuture`] as complete, called by the event loop when its awaitable is done.
+#[pyclass]
+struct WakeCallback {
+    state: Arc<Mutex<PyFutureState>>,
+}
+
+#[pymethods]
+impl WakeCallback {
+    #[call]
+    fn __call__(&self, _future: &PyAny) {
+        let waker = {
+            let mut state = self.state.lock();
+            state.done = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Runs `future` on `event_loop` and returns an `asyncio.Future` for its output, which is converted
+/// to a Python object like the return value of a `#[pyfunction]`.
+///
+/// The future is polled by a task of the event loop, like the coroutine of an `async fn` (see
+/// [`Coroutine`]), so it runs as long as the event loop does. To run it on a Rust runtime instead,
+/// spawn it there and pass the runtime's join handle to this function. Cancelling the returned
+/// `asyncio.Future` drops `future`.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::wrap_pyfunction;
+///
+/// #[pyfunction]
+/// fn answer(event_loop: &PyAny) -> PyResult<&PyAny> {
+///     pyo3::asyncio::future_into_py(event_loop, async { 42 })
+/// }
+///
+/// Python::with_gil(|py| {
+///     let answer = wrap_pyfunction!(answer)(py).unwrap();
+///     pyo3::py_run!(py, answer, r#"
+///         import asyncio
+///         event_loop = asyncio.new_event_loop()
+///         assert event_loop.run_until_complete(answer(event_loop)) == 42
+///         event_loop.close()
+///     "#);
+/// });
+/// ```
+pub fn future_into_py<F, R>(event_loop: &PyAny, future: F) -> PyResult<&PyAny>
+where
+    F: Future<Output = R> + Send + 'static,
+    R: IntoPyCallbackOutput<PyObject>,
+{
+    let py = event_loop.py();
+    let coroutine = Py::new(
+        py,
+        Coroutine::new("future_into_py", None, RaisePanic::panic_into_err, future),
+    )?;
+    helpers(py)?.call_method1("spawn", (event_loop, coroutine))
+}
@@ -5,13 +5,13 @@
 use crate::err::{PyErr, PyResult};
 use crate::exceptions::PyOverflowError;
 use crate::ffi::{self, Py_hash_t};
-use crate::panic::PanicException;
+use crate::panic::{PanicPolicy, RaisePanic};
 use crate::{GILPool, IntoPyPointer};
 use crate::{IntoPy, PyObject, Python};
 use std::any::Any;
+use std::isize;
 use std::os::raw::c_int;
 use std::panic::{AssertUnwindSafe, UnwindSafe};
-use std::{isize, panic};
 
 /// A type which can be the return type of a python C-API callback
 pub trait PyCallbackOutput: Copy {
@@ -57,7 +57,7 @@ where
 {
     #[inline]
     fn convert(self, py: Python) -> PyResult<*mut ffi::PyObject> {
-        Ok(self.into_py(py).into_ptr())
+        self.try_into_py(py).map(IntoPyPointer::into_ptr)
     }
 }
 
@@ -122,7 +122,7 @@ where
 {
     #[inline]
     fn convert(self, py: Python) -> PyResult<PyObject> {
-        Ok(self.into_py(py))
+        self.try_into_py(py)
     }
 }
 
@@ -189,7 +189,8 @@ where
 /// It sets up the GILPool and converts the output into a Python object. It also restores
 /// any python error returned as an Err variant from the body.
 ///
-/// Finally, any panics inside the callback body will be caught and translated into PanicExceptions.
+/// Finally, any panics inside the callback body will be caught and translated into PanicExceptions,
+/// or handled according to the `panic` option of the module which added the class `$cls`.
 ///
 /// # Safety
 /// This macro assumes the GIL is held. (It makes use of unsafe code, so usage of it is only
@@ -197,8 +198,9 @@ where
 #[doc(hidden)]
 #[macro_export]
 macro_rules! callback_body {
-    ($py:ident, $body:expr) => {
-        $crate::callback::handle_panic(|$py| $crate::callback::convert($py, $body))
+    ($py:ident, $cls:ty, $body:expr) => {
+        $crate::panic::PanicScope::class::<$cls>()
+            .handle_panic(|$py| $crate::callback::convert($py, $body))
     };
 }
 
@@ -225,7 +227,7 @@ macro_rules! callback_body {
 /// If callback_body was used instead:
 ///
 /// ```ignore
-/// pyo3::callback_body!(py, {
+/// pyo3::callback_body!(py, Self, {
 ///     let _slf = #slf;
 ///     #foo
 /// })
@@ -240,26 +242,40 @@ where
     F: FnOnce(Python) -> PyResult<R> + UnwindSafe,
     R: PyCallbackOutput,
 {
-    let pool = GILPool::new();
-    let unwind_safe_py = AssertUnwindSafe(pool.python());
-    let panic_result = panic::catch_unwind(move || -> PyResult<_> {
-        let py = *unwind_safe_py;
-        body(py)
-    });
+    handle_panic_with::<RaisePanic, F, R>(body)
+}
 
-    panic_result_into_callback_output(pool.python(), panic_result)
+/// Like [`handle_panic`], with panics handled according to the `#[pyo3(panic = "...")]` option.
+#[doc(hidden)]
+#[inline]
+pub unsafe fn handle_panic_with<P, F, R>(body: F) -> R
+where
+    P: PanicPolicy,
+    F: FnOnce(Python) -> PyResult<R> + UnwindSafe,
+    R: PyCallbackOutput,
+{
+    handle_panic_in(body, P::panic_into_err)
 }
 
-fn panic_result_into_callback_output<R>(
-    py: Python,
-    panic_result: Result<PyResult<R>, Box<dyn Any + Send + 'static>>,
-) -> R
+/// Like [`handle_panic`], with caught panics converted to exceptions by `panic_into_err`.
+#[inline]
+pub(crate) unsafe fn handle_panic_in<F, R, E>(body: F, panic_into_err: E) -> R
 where
+    F: FnOnce(Python) -> PyResult<R> + UnwindSafe,
     R: PyCallbackOutput,
+    E: FnOnce(Python, Box<dyn Any + Send + 'static>) -> PyErr,
 {
+    let pool = GILPool::new();
+    let unwind_safe_py = AssertUnwindSafe(pool.python());
+    let panic_result = crate::panic::catch_unwind(move || -> PyResult<_> {
+        let py = *unwind_safe_py;
+        body(py)
+    });
+
+    let py = pool.python();
     let py_result = match panic_result {
         Ok(py_result) => py_result,
-        Err(payload) => Err(PanicException::from_panic_payload(payload)),
+        Err(payload) => Err(panic_into_err(py, payload)),
     };
 
     py_result.unwrap_or_else(|py_err| {
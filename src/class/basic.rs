@@ -173,7 +173,7 @@ pub unsafe extern "C" fn getattr<T>(
 where
     T: for<'p> PyObjectGetAttrProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         // Behave like python's __getattr__ (as opposed to __getattribute__) and check
         // for existing fields and methods first
         let existing = ffi::PyObject_GenericGetAttr(slf, arg);
@@ -199,7 +199,7 @@ pub unsafe extern "C" fn richcmp<T>(
 where
     T: for<'p> PyObjectRichcmpProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<crate::PyCell<T>>(slf);
         let arg = extract_or_return_not_implemented!(py, arg);
         let op = match op {
@@ -42,9 +42,9 @@ pub unsafe extern "C" fn getbuffer<T>(
 where
     T: for<'p> PyBufferGetBufferProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
-        T::bf_getbuffer(slf.try_borrow_mut()?, arg1, arg2).convert(py)
+        T::bf_getbuffer(slf.try_borrow_mut_dynamic()?, arg1, arg2).convert(py)
     })
 }
 
@@ -53,8 +53,8 @@ pub unsafe extern "C" fn releasebuffer<T>(slf: *mut ffi::PyObject, arg1: *mut ff
 where
     T: for<'p> PyBufferReleaseBufferProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<crate::PyCell<T>>(slf);
-        T::bf_releasebuffer(slf.try_borrow_mut()?, arg1).convert(py)
+        T::bf_releasebuffer(slf.try_borrow_mut_dynamic()?, arg1).convert(py)
     })
 }
@@ -7,7 +7,7 @@ use crate::{ffi, AsPyPointer, PyCell, PyClass, Python};
 use std::os::raw::{c_int, c_void};
 
 #[repr(transparent)]
-pub struct PyTraverseError(c_int);
+pub struct PyTraverseError(pub(crate) c_int);
 
 /// GC support
 #[allow(clippy::upper_case_acronyms)]
@@ -35,11 +35,7 @@ where
     let py = pool.python();
     let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
 
-    let visit = PyVisit {
-        visit,
-        arg,
-        _py: py,
-    };
+    let visit = PyVisit::new(visit, arg, py);
     let borrow = slf.try_borrow();
     if let Ok(borrow) = borrow {
         match borrow.__traverse__(visit) {
@@ -59,7 +55,10 @@ where
     let pool = crate::GILPool::new();
     let slf = pool.python().from_borrowed_ptr::<PyCell<T>>(slf);
 
-    slf.borrow_mut().__clear__();
+    // `__clear__` takes `&mut self`, which `#[pyproto]` rejects for frozen classes
+    slf.try_borrow_mut_dynamic()
+        .expect("Already borrowed")
+        .__clear__();
     0
 }
 
@@ -75,6 +74,14 @@ pub struct PyVisit<'p> {
 }
 
 impl<'p> PyVisit<'p> {
+    pub(crate) fn new(visit: ffi::visitproc, arg: *mut c_void, py: Python<'p>) -> Self {
+        PyVisit {
+            visit,
+            arg,
+            _py: py,
+        }
+    }
+
     /// Visit `obj`.
     pub fn call<T>(&self, obj: &T) -> Result<(), PyTraverseError>
     where
@@ -11,6 +11,9 @@ use crate::{
 };
 use std::{marker::PhantomData, os::raw::c_void, ptr::NonNull, thread};
 
+#[cfg(feature = "inspect")]
+use crate::inspect::FunctionInfo;
+
 /// This type is used as a "dummy" type on which dtolnay specializations are
 /// applied to apply implementations from `#[pymethods]` & `#[pyproto]`
 pub struct PyClassImplCollector<T>(PhantomData<T>);
@@ -71,6 +74,15 @@ pub trait PyClassImpl: Sized {
     fn get_new() -> Option<ffi::newfunc> {
         None
     }
+    /// `__text_signature__` generated from `#[new]`, if the class has no `text_signature` itself.
+    fn get_new_text_signature() -> Option<&'static str> {
+        None
+    }
+    /// Signature of `#[new]`, see [`inspect`](crate::inspect).
+    #[cfg(feature = "inspect")]
+    fn get_new_info() -> Option<FunctionInfo> {
+        None
+    }
     fn get_call() -> Option<ffi::PyCFunctionWithKeywords> {
         None
     }
@@ -90,12 +102,22 @@ pub trait PyClassImpl: Sized {
 
 pub trait PyClassNewImpl<T> {
     fn new_impl(self) -> Option<ffi::newfunc>;
+    fn new_text_signature(self) -> Option<&'static str>;
+    #[cfg(feature = "inspect")]
+    fn new_info(self) -> Option<FunctionInfo>;
 }
 
 impl<T> PyClassNewImpl<T> for &'_ PyClassImplCollector<T> {
     fn new_impl(self) -> Option<ffi::newfunc> {
         None
     }
+    fn new_text_signature(self) -> Option<&'static str> {
+        None
+    }
+    #[cfg(feature = "inspect")]
+    fn new_info(self) -> Option<FunctionInfo> {
+        None
+    }
 }
 
 pub trait PyClassCallImpl<T> {
@@ -178,7 +200,7 @@ macro_rules! define_pyclass_setattr_slot {
                     use ::std::option::Option::*;
                     use $crate::callback::IntoPyCallbackOutput;
                     use $crate::class::impl_::*;
-                    $crate::callback::handle_panic(|py| {
+                    $crate::panic::PanicScope::class::<$cls>().handle_panic(|py| {
                         let collector = PyClassImplCollector::<$cls>::new();
                         if let Some(value) = ::std::ptr::NonNull::new(value) {
                             collector.$set(py, _slf, attr, value).convert(py)
@@ -284,7 +306,7 @@ macro_rules! define_pyclass_binary_operator_slot {
                     _slf: *mut $crate::ffi::PyObject,
                     _other: *mut $crate::ffi::PyObject,
                 ) -> *mut $crate::ffi::PyObject {
-                    $crate::callback::handle_panic(|py| {
+                    $crate::panic::PanicScope::class::<$cls>().handle_panic(|py| {
                         use ::pyo3::class::impl_::*;
                         let collector = PyClassImplCollector::<$cls>::new();
                         let lhs_result = collector.$lhs(py, _slf, _other)?;
@@ -456,7 +478,7 @@ macro_rules! generate_pyclass_pow_slot {
             _other: *mut $crate::ffi::PyObject,
             _mod: *mut $crate::ffi::PyObject,
         ) -> *mut $crate::ffi::PyObject {
-            $crate::callback::handle_panic(|py| {
+            $crate::panic::PanicScope::class::<$cls>().handle_panic(|py| {
                 use ::pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<$cls>::new();
                 let lhs_result = collector.__pow__(py, _slf, _other, _mod)?;
@@ -685,6 +707,12 @@ impl<T> PyBufferProtocolProcs<T> for &'_ PyClassImplCollector<T> {
     }
 }
 
+/// Compiles only for classes which can be mutably borrowed, i.e. which aren't
+/// `#[pyclass(frozen)]`. Used to reject `#[pyproto]` methods taking `&mut self` on frozen
+/// classes, as those are implemented for all classes.
+#[doc(hidden)]
+pub fn assert_mutable<T: PyClass<Frozen = crate::pyclass::boolean_struct::False>>(_: &T) {}
+
 // Thread checkers
 
 #[doc(hidden)]
@@ -777,8 +805,8 @@ pub(crate) unsafe extern "C" fn fallback_new(
     _args: *mut ffi::PyObject,
     _kwds: *mut ffi::PyObject,
 ) -> *mut ffi::PyObject {
-    crate::callback_body!(py, {
-        Err::<(), _>(crate::exceptions::PyTypeError::new_err(
+    crate::callback::handle_panic(|_py| {
+        Err::<*mut ffi::PyObject, _>(crate::exceptions::PyTypeError::new_err(
             "No constructor defined",
         ))
     })
@@ -786,5 +814,5 @@ pub(crate) unsafe extern "C" fn fallback_new(
 
 /// Implementation of tp_dealloc for all pyclasses
 pub(crate) unsafe extern "C" fn tp_dealloc<T: PyClass>(obj: *mut ffi::PyObject) {
-    crate::callback_body!(py, T::Layout::tp_dealloc(obj, py))
+    crate::callback_body!(py, T, T::Layout::tp_dealloc(obj, py))
 }
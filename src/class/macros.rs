@@ -7,7 +7,7 @@ macro_rules! py_unary_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 $call!(slf, $f).convert(py)
             })
@@ -37,7 +37,7 @@ macro_rules! py_unarys_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 let borrow =
                     <T::Receiver as $crate::derive_utils::TryFromPyCell<_>>::try_from_pycell(slf)
@@ -66,7 +66,7 @@ macro_rules! py_binary_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 let arg = py.from_borrowed_ptr::<$crate::PyAny>(arg);
                 $call!(slf, $f, arg).convert(py)
@@ -91,7 +91,7 @@ macro_rules! py_binary_num_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let lhs = py.from_borrowed_ptr::<$crate::PyAny>(lhs);
                 let rhs = extract_or_return_not_implemented!(py, rhs);
                 T::$f(lhs.extract()?, rhs).convert(py)
@@ -110,7 +110,7 @@ macro_rules! py_binary_reversed_num_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 // Swap lhs <-> rhs
                 let slf: &$crate::PyCell<T> = extract_or_return_not_implemented!(py, rhs);
                 let arg = extract_or_return_not_implemented!(py, lhs);
@@ -130,7 +130,7 @@ macro_rules! py_binary_fallback_num_func {
         where
             T: for<'p> $lop_trait<'p> + for<'p> $rop_trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let lhs = py.from_borrowed_ptr::<$crate::PyAny>(lhs);
                 let rhs = py.from_borrowed_ptr::<$crate::PyAny>(rhs);
                 // First, try the left hand method (e.g., __add__)
@@ -159,7 +159,7 @@ macro_rules! py_binary_self_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf_ = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 let arg = py.from_borrowed_ptr::<$crate::PyAny>(arg);
                 call_operator_mut!(py, slf_, $f, arg).convert(py)?;
@@ -184,7 +184,7 @@ macro_rules! py_ssizearg_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 $call!(slf, $f; arg.into()).convert(py)
             })
@@ -203,7 +203,7 @@ macro_rules! py_ternarys_func {
         where
             T: for<'p> $trait<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 let slf =
                     <T::Receiver as $crate::derive_utils::TryFromPyCell<_>>::try_from_pycell(slf)
@@ -235,7 +235,7 @@ macro_rules! py_func_set {
         where
             T: for<'p> $trait_name<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
 
                 if value.is_null() {
@@ -264,13 +264,13 @@ macro_rules! py_func_del {
         where
             T: for<'p> $trait_name<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 if value.is_null() {
                     let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                     let name = py
                         .from_borrowed_ptr::<$crate::types::PyAny>(name)
                         .extract()?;
-                    slf.try_borrow_mut()?.$fn_del(name).convert(py)
+                    slf.try_borrow_mut_dynamic()?.$fn_del(name).convert(py)
                 } else {
                     Err($crate::exceptions::PyNotImplementedError::new_err(
                         "Subscript assignment not supported",
@@ -292,7 +292,7 @@ macro_rules! py_func_set_del {
         where
             T: for<'p> $trait1<'p> + for<'p> $trait2<'p>,
         {
-            $crate::callback_body!(py, {
+            $crate::callback_body!(py, T, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
                 let name = py.from_borrowed_ptr::<$crate::PyAny>(name);
 
@@ -358,12 +358,12 @@ macro_rules! call_ref {
 /// Call `slf.try_borrow_mut()?.$fn(...)`
 macro_rules! call_mut {
     ($slf: expr, $fn: ident $(,$raw_args: expr)* $(; $args: expr)*) => {
-        _call_impl!($slf.try_borrow_mut()?, $fn $(,$raw_args)* $(;$args)*)
+        _call_impl!($slf.try_borrow_mut_dynamic()?, $fn $(,$raw_args)* $(;$args)*)
     };
 }
 
 macro_rules! call_operator_mut {
     ($py:ident, $slf: expr, $fn: ident $(,$raw_args: expr)* $(; $args: expr)*) => {
-        _call_impl!(op $py; $slf.try_borrow_mut()?, $fn $(,$raw_args)* $(;$args)*)
+        _call_impl!(op $py; $slf.try_borrow_mut_dynamic()?, $fn $(,$raw_args)* $(;$args)*)
     };
 }
@@ -1,5 +1,7 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
+#[cfg(feature = "inspect")]
+use crate::inspect::{types::TypeInfo, FunctionInfo};
 use crate::internal_tricks::{extract_cstr_or_leak_cstring, NulByteInString};
 use crate::{ffi, PyObject, Python};
 use std::ffi::CStr;
@@ -47,6 +49,12 @@ pub struct PyGetter(pub ffi::getter);
 pub struct PySetter(pub ffi::setter);
 #[derive(Clone, Copy)]
 pub struct PyClassAttributeFactory(pub for<'p> fn(Python<'p>) -> PyObject);
+#[cfg(feature = "inspect")]
+#[derive(Clone, Copy, Debug)]
+pub struct PyFunctionInfoFactory(pub fn() -> FunctionInfo);
+#[cfg(feature = "inspect")]
+#[derive(Clone, Copy, Debug)]
+pub struct PyTypeInfoFactory(pub fn() -> TypeInfo);
 
 // TODO: it would be nice to use CStr in these types, but then the constructors can't be const fn
 // until `CStr::from_bytes_with_nul_unchecked` is const fn.
@@ -57,6 +65,8 @@ pub struct PyMethodDef {
     pub(crate) ml_meth: PyMethodType,
     pub(crate) ml_flags: c_int,
     pub(crate) ml_doc: &'static str,
+    #[cfg(feature = "inspect")]
+    pub(crate) info: Option<PyFunctionInfoFactory>,
 }
 
 #[derive(Copy, Clone)]
@@ -70,6 +80,8 @@ pub struct PyGetterDef {
     pub(crate) name: &'static str,
     pub(crate) meth: PyGetter,
     doc: &'static str,
+    #[cfg(feature = "inspect")]
+    pub(crate) type_info: Option<PyTypeInfoFactory>,
 }
 
 #[derive(Clone, Debug)]
@@ -77,6 +89,8 @@ pub struct PySetterDef {
     pub(crate) name: &'static str,
     pub(crate) meth: PySetter,
     doc: &'static str,
+    #[cfg(feature = "inspect")]
+    pub(crate) type_info: Option<PyTypeInfoFactory>,
 }
 
 unsafe impl Sync for PyMethodDef {}
@@ -93,6 +107,8 @@ impl PyMethodDef {
             ml_meth: PyMethodType::PyCFunction(cfunction),
             ml_flags: ffi::METH_NOARGS,
             ml_doc: doc,
+            #[cfg(feature = "inspect")]
+            info: None,
         }
     }
 
@@ -107,6 +123,8 @@ impl PyMethodDef {
             ml_meth: PyMethodType::PyCFunctionWithKeywords(cfunction),
             ml_flags: ffi::METH_VARARGS | ffi::METH_KEYWORDS,
             ml_doc: doc,
+            #[cfg(feature = "inspect")]
+            info: None,
         }
     }
 
@@ -122,6 +140,8 @@ impl PyMethodDef {
             ml_meth: PyMethodType::PyCFunctionFastWithKeywords(cfunction),
             ml_flags: ffi::METH_FASTCALL | ffi::METH_KEYWORDS,
             ml_doc: doc,
+            #[cfg(feature = "inspect")]
+            info: None,
         }
     }
 
@@ -130,6 +150,13 @@ impl PyMethodDef {
         self
     }
 
+    /// Attach the signature of the function, see [`inspect`](crate::inspect).
+    #[cfg(feature = "inspect")]
+    pub const fn info(mut self, info: PyFunctionInfoFactory) -> Self {
+        self.info = Some(info);
+        self
+    }
+
     /// Convert `PyMethodDef` to Python method definition struct `ffi::PyMethodDef`
     pub(crate) fn as_method_def(&self) -> Result<ffi::PyMethodDef, NulByteInString> {
         let meth = match self.ml_meth {
@@ -174,9 +201,18 @@ impl PyGetterDef {
             name,
             meth: getter,
             doc,
+            #[cfg(feature = "inspect")]
+            type_info: None,
         }
     }
 
+    /// Attach the type of the property, see [`inspect`](crate::inspect).
+    #[cfg(feature = "inspect")]
+    pub const fn type_info(mut self, type_info: PyTypeInfoFactory) -> Self {
+        self.type_info = Some(type_info);
+        self
+    }
+
     /// Copy descriptor information to `ffi::PyGetSetDef`
     pub fn copy_to(&self, dst: &mut ffi::PyGetSetDef) {
         if dst.name.is_null() {
@@ -196,9 +232,18 @@ impl PySetterDef {
             name,
             meth: setter,
             doc,
+            #[cfg(feature = "inspect")]
+            type_info: None,
         }
     }
 
+    /// Attach the type of the property, see [`inspect`](crate::inspect).
+    #[cfg(feature = "inspect")]
+    pub const fn type_info(mut self, type_info: PyTypeInfoFactory) -> Self {
+        self.type_info = Some(type_info);
+        self
+    }
+
     /// Copy descriptor information to `ffi::PyGetSetDef`
     pub fn copy_to(&self, dst: &mut ffi::PyGetSetDef) {
         if dst.name.is_null() {
@@ -633,7 +633,7 @@ pub unsafe extern "C" fn pow_rpow<T>(
 where
     T: for<'p> PyNumberPowProtocol<'p> + for<'p> PyNumberRPowProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let lhs = py.from_borrowed_ptr::<crate::PyAny>(lhs);
         let rhs = py.from_borrowed_ptr::<crate::PyAny>(rhs);
         let modulo = py.from_borrowed_ptr::<crate::PyAny>(modulo);
@@ -660,7 +660,7 @@ pub unsafe extern "C" fn pow<T>(
 where
     T: for<'p> PyNumberPowProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let lhs = extract_or_return_not_implemented!(py, lhs);
         let rhs = extract_or_return_not_implemented!(py, rhs);
         let modulo = extract_or_return_not_implemented!(py, modulo);
@@ -677,7 +677,7 @@ pub unsafe extern "C" fn rpow<T>(
 where
     T: for<'p> PyNumberRPowProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf: &crate::PyCell<T> = extract_or_return_not_implemented!(py, slf);
         let arg = extract_or_return_not_implemented!(py, arg);
         let modulo = extract_or_return_not_implemented!(py, modulo);
@@ -747,7 +747,7 @@ where
 {
     // NOTE: Somehow __ipow__ causes SIGSEGV in Python < 3.8 when we extract,
     // so we ignore it. It's the same as what CPython does.
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf_cell = py.from_borrowed_ptr::<crate::PyCell<T>>(slf);
         let other = py.from_borrowed_ptr::<crate::PyAny>(other);
         call_operator_mut!(py, slf_cell, __ipow__, other).convert(py)?;
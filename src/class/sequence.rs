@@ -142,7 +142,7 @@ pub unsafe extern "C" fn setitem<T>(
 where
     T: for<'p> PySequenceSetItemProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
 
         if value.is_null() {
@@ -152,7 +152,7 @@ where
             )));
         }
 
-        let mut slf = slf.try_borrow_mut()?;
+        let mut slf = slf.try_borrow_mut_dynamic()?;
         let value = py.from_borrowed_ptr::<PyAny>(value);
         let value = value.extract()?;
         crate::callback::convert(py, slf.__setitem__(key.into(), value))
@@ -168,11 +168,11 @@ pub unsafe extern "C" fn delitem<T>(
 where
     T: for<'p> PySequenceDelItemProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
 
         if value.is_null() {
-            crate::callback::convert(py, slf.borrow_mut().__delitem__(key.into()))
+            crate::callback::convert(py, slf.try_borrow_mut_dynamic()?.__delitem__(key.into()))
         } else {
             Err(PyErr::new::<exceptions::PyNotImplementedError, _>(format!(
                 "Item assignment not supported by {:?}",
@@ -191,14 +191,14 @@ pub unsafe extern "C" fn setdelitem<T>(
 where
     T: for<'p> PySequenceSetItemProtocol<'p> + for<'p> PySequenceDelItemProtocol<'p>,
 {
-    crate::callback_body!(py, {
+    crate::callback_body!(py, T, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
 
         if value.is_null() {
             call_mut!(slf, __delitem__; key.into()).convert(py)
         } else {
             let value = py.from_borrowed_ptr::<PyAny>(value);
-            let mut slf_ = slf.try_borrow_mut()?;
+            let mut slf_ = slf.try_borrow_mut_dynamic()?;
             let value = value.extract()?;
             slf_.__setitem__(key.into(), value).convert(py)
         }
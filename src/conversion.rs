@@ -2,6 +2,7 @@
 
 //! Defines conversions between Rust and Python types.
 use crate::err::{self, PyDowncastError, PyResult};
+use crate::inspect::types::TypeInfo;
 use crate::type_object::PyTypeInfo;
 use crate::types::PyTuple;
 use crate::{
@@ -201,6 +202,24 @@ where
 pub trait IntoPy<T>: Sized {
     /// Performs the conversion.
     fn into_py(self, py: Python) -> T;
+
+    /// Performs the conversion, returning an error if the Python object can't be created.
+    ///
+    /// Values returned to Python from `#[pyfunction]`s and `#[pymethods]` are converted with this
+    /// method, so that the error is raised instead of a panic. `#[pyclass]` types implement it, as
+    /// creating their objects fails in a sub-interpreter, and so do `Option`, `Vec` and tuples by
+    /// converting their elements with it. The default implementation calls
+    /// [`into_py`](IntoPy::into_py).
+    fn try_into_py(self, py: Python) -> PyResult<T> {
+        Ok(self.into_py(py))
+    }
+
+    /// The Python type of the converted value, as used in type stubs.
+    ///
+    /// See [`inspect`](crate::inspect) for how it is used.
+    fn type_output() -> TypeInfo {
+        TypeInfo::Any
+    }
 }
 
 /// `FromPyObject` is implemented by various types that can be extracted from
@@ -231,6 +250,13 @@ pub trait IntoPy<T>: Sized {
 pub trait FromPyObject<'source>: Sized {
     /// Extracts `Self` from the source `PyObject`.
     fn extract(ob: &'source PyAny) -> PyResult<Self>;
+
+    /// The Python type accepted by `extract`, as used in type stubs.
+    ///
+    /// See [`inspect`](crate::inspect) for how it is used.
+    fn type_input() -> TypeInfo {
+        TypeInfo::Any
+    }
 }
 
 /// Identity conversion: allows using existing `PyObject` instances where
@@ -261,6 +287,14 @@ where
     fn into_py(self, py: Python) -> PyObject {
         self.map_or_else(|| py.None(), |val| val.into_py(py))
     }
+
+    fn try_into_py(self, py: Python) -> PyResult<PyObject> {
+        self.map_or_else(|| Ok(py.None()), |val| val.try_into_py(py))
+    }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::optional_of(T::type_output())
+    }
 }
 
 /// `()` is converted to Python `None`.
@@ -274,6 +308,10 @@ impl IntoPy<PyObject> for () {
     fn into_py(self, py: Python) -> PyObject {
         py.None()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::None
+    }
 }
 
 impl<T> IntoPy<PyObject> for &'_ T
@@ -293,6 +331,10 @@ where
     fn extract(obj: &'a PyAny) -> PyResult<Self> {
         PyTryFrom::try_from(obj).map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for T
@@ -303,6 +345,10 @@ where
         let cell: &PyCell<Self> = PyTryFrom::try_from(obj)?;
         Ok(unsafe { cell.try_borrow_unguarded()?.clone() })
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for PyRef<'a, T>
@@ -313,16 +359,24 @@ where
         let cell: &PyCell<T> = PyTryFrom::try_from(obj)?;
         cell.try_borrow().map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for PyRefMut<'a, T>
 where
-    T: PyClass,
+    T: PyClass<Frozen = crate::pyclass::boolean_struct::False>,
 {
     fn extract(obj: &'a PyAny) -> PyResult<Self> {
         let cell: &PyCell<T> = PyTryFrom::try_from(obj)?;
         cell.try_borrow_mut().map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for Option<T>
@@ -336,6 +390,10 @@ where
             T::extract(obj).map(Some)
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::optional_of(T::type_input())
+    }
 }
 
 /// Trait implemented by Python object types that allow a checked downcast.
@@ -3,6 +3,7 @@ use crate::{exceptions, PyErr};
 #[cfg(min_const_generics)]
 mod min_const_generics {
     use super::invalid_sequence_length;
+    use crate::inspect::types::TypeInfo;
     use crate::{FromPyObject, IntoPy, PyAny, PyObject, PyResult, PyTryFrom, Python, ToPyObject};
 
     impl<T, const N: usize> IntoPy<PyObject> for [T; N]
@@ -12,6 +13,10 @@ mod min_const_generics {
         fn into_py(self, py: Python) -> PyObject {
             self.as_ref().to_object(py)
         }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::builtin("list")
+        }
     }
 
     impl<'a, T, const N: usize> FromPyObject<'a> for [T; N]
@@ -27,6 +32,10 @@ mod min_const_generics {
         default fn extract(obj: &'a PyAny) -> PyResult<Self> {
             create_array_from_obj(obj)
         }
+
+        fn type_input() -> TypeInfo {
+            TypeInfo::sequence_of(T::type_input())
+        }
     }
 
     #[cfg(feature = "nightly")]
@@ -167,6 +176,7 @@ mod min_const_generics {
 #[cfg(not(min_const_generics))]
 mod array_impls {
     use super::invalid_sequence_length;
+    use crate::inspect::types::TypeInfo;
     use crate::{FromPyObject, IntoPy, PyAny, PyObject, PyResult, PyTryFrom, Python, ToPyObject};
 
     macro_rules! array_impls {
@@ -179,6 +189,10 @@ mod array_impls {
                     fn into_py(self, py: Python) -> PyObject {
                         self.as_ref().to_object(py)
                     }
+
+                    fn type_output() -> TypeInfo {
+                        TypeInfo::builtin("list")
+                    }
                 }
 
                 impl<'a, T> FromPyObject<'a> for [T; $N]
@@ -198,6 +212,10 @@ mod array_impls {
                         extract_sequence_into_slice(obj, &mut array)?;
                         Ok(array)
                     }
+
+                    fn type_input() -> TypeInfo {
+                        TypeInfo::sequence_of(T::type_input())
+                    }
                 }
 
                 #[cfg(feature = "nightly")]
@@ -22,6 +22,7 @@
 //!
 //! Note that you must use compatible versions of hashbrown and PyO3.
 //! The required hashbrown version may vary based on the version of PyO3.
+use crate::inspect::types::TypeInfo;
 use crate::{
     types::{IntoPyDict, PyDict, PySet},
     FromPyObject, IntoPy, PyAny, PyErr, PyObject, PyResult, PyTryFrom, Python, ToPyObject,
@@ -51,6 +52,10 @@ where
             .map(|(k, v)| (k.into_py(py), v.into_py(py)));
         IntoPyDict::into_py_dict(iter, py).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::dict_of(K::type_output(), V::type_output())
+    }
 }
 
 impl<'source, K, V, S> FromPyObject<'source> for hashbrown::HashMap<K, V, S>
@@ -67,6 +72,10 @@ where
         }
         Ok(ret)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::dict_of(K::type_input(), V::type_input())
+    }
 }
 
 impl<T> ToPyObject for hashbrown::HashSet<T>
@@ -98,6 +107,10 @@ where
         }
         set.into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::set_of(K::type_output())
+    }
 }
 
 impl<'source, K, S> FromPyObject<'source> for hashbrown::HashSet<K, S>
@@ -109,6 +122,10 @@ where
         let set: &PySet = ob.downcast()?;
         set.iter().map(K::extract).collect()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::set_of(K::type_input())
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +94,7 @@
 //! # if another hash table was used, the order could be random
 //! ```
 
+use crate::inspect::types::TypeInfo;
 use crate::types::*;
 use crate::{FromPyObject, IntoPy, PyErr, PyObject, PyTryFrom, Python, ToPyObject};
 use std::{cmp, hash};
@@ -121,6 +122,10 @@ where
             .map(|(k, v)| (k.into_py(py), v.into_py(py)));
         IntoPyDict::into_py_dict(iter, py).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::dict_of(K::type_output(), V::type_output())
+    }
 }
 
 impl<'source, K, V, S> FromPyObject<'source> for indexmap::IndexMap<K, V, S>
@@ -137,6 +142,10 @@ where
         }
         Ok(ret)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::dict_of(K::type_input(), V::type_input())
+    }
 }
 
 #[cfg(test)]
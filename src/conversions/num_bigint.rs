@@ -62,6 +62,7 @@
 //! assert n + 1 == value
 //! ```
 
+use crate::inspect::types::TypeInfo;
 use crate::{
     err, ffi, types::*, AsPyPointer, FromPyObject, IntoPy, Py, PyAny, PyErr, PyObject, PyResult,
     Python, ToPyObject,
@@ -104,6 +105,10 @@ macro_rules! bigint_conversion {
             fn into_py(self, py: Python) -> PyObject {
                 self.to_object(py)
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
         impl<'source> FromPyObject<'source> for $rust_ty {
             fn extract(ob: &'source PyAny) -> PyResult<$rust_ty> {
@@ -133,6 +138,10 @@ macro_rules! bigint_conversion {
                     }
                 }
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
     };
 }
@@ -99,6 +99,7 @@
 //! result = get_eigenvalues(m11,m12,m21,m22)
 //! assert result == [complex(1,-1), complex(-2,0)]
 //! ```
+use crate::inspect::types::TypeInfo;
 use crate::{
     ffi, types::PyComplex, AsPyPointer, FromPyObject, PyAny, PyErr, PyObject, PyResult, Python,
     ToPyObject,
@@ -132,6 +133,10 @@ macro_rules! complex_conversion {
                     PyObject::from_owned_ptr(py, raw_obj)
                 }
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::builtin("complex")
+            }
         }
         #[cfg(not(any(Py_LIMITED_API, PyPy)))]
         #[allow(clippy::float_cmp)] // The comparison is for an error value
@@ -146,6 +151,10 @@ macro_rules! complex_conversion {
                     }
                 }
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("complex")
+            }
         }
         #[cfg(any(Py_LIMITED_API, PyPy))]
         #[allow(clippy::float_cmp)] // The comparison is for an error value
@@ -161,6 +170,10 @@ macro_rules! complex_conversion {
                     Ok(Complex::new(real as $float, imag as $float))
                 }
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("complex")
+            }
         }
     };
 }
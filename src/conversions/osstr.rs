@@ -1,3 +1,4 @@
+use crate::inspect::types::TypeInfo;
 use crate::types::PyString;
 #[cfg(windows)]
 use crate::PyErr;
@@ -108,6 +109,10 @@ impl FromPyObject<'_> for OsString {
             Ok(os_string)
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl IntoPy<PyObject> for &'_ OsStr {
@@ -115,6 +120,10 @@ impl IntoPy<PyObject> for &'_ OsStr {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl ToPyObject for Cow<'_, OsStr> {
@@ -129,6 +138,10 @@ impl IntoPy<PyObject> for Cow<'_, OsStr> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl ToPyObject for OsString {
@@ -142,12 +155,20 @@ impl IntoPy<PyObject> for OsString {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a OsString {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 #[cfg(test)]
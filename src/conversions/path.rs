@@ -1,3 +1,4 @@
+use crate::inspect::types::TypeInfo;
 use crate::types::PyType;
 use crate::{FromPyObject, IntoPy, PyAny, PyObject, PyResult, Python, ToPyObject};
 use std::borrow::Cow;
@@ -30,6 +31,13 @@ impl FromPyObject<'_> for PathBuf {
         };
         Ok(PathBuf::from(os_str))
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::union_of(vec![
+            TypeInfo::builtin("str"),
+            TypeInfo::builtin("os.PathLike"),
+        ])
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a Path {
@@ -37,6 +45,10 @@ impl<'a> IntoPy<PyObject> for &'a Path {
     fn into_py(self, py: Python) -> PyObject {
         self.as_os_str().to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl<'a> ToPyObject for Cow<'a, Path> {
@@ -51,6 +63,10 @@ impl<'a> IntoPy<PyObject> for Cow<'a, Path> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl ToPyObject for PathBuf {
@@ -64,12 +80,20 @@ impl IntoPy<PyObject> for PathBuf {
     fn into_py(self, py: Python) -> PyObject {
         self.into_os_string().to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a PathBuf {
     fn into_py(self, py: Python) -> PyObject {
         self.as_os_str().to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,419 @@
+//! Python coroutines driving Rust futures, which implement `async fn` in `#[pyfunction]` and
+//! `#[pymethods]`.
+//!
+//! Calling an `async fn` from Python returns a [`Coroutine`], which can be awaited by Python code
+//! running under `asyncio`:
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//! use pyo3::wrap_pyfunction;
+//!
+//! #[pyfunction]
+//! async fn add(a: u64, b: u64) -> u64 {
+//!     a + b
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let add = wrap_pyfunction!(add)(py).unwrap();
+//!     pyo3::py_run!(py, add, r#"
+//!         import asyncio
+//!         assert asyncio.run(add(1, 2)) == 3
+//!     "#);
+//! });
+//! ```
+
+use crate::callback::IntoPyCallbackOutput;
+use crate::class::iter::IterNextOutput;
+use crate::class::{PyAsyncProtocol, PyIterProtocol};
+use crate::exceptions::{PyRuntimeError, PyStopIteration, PyTypeError};
+use crate::once_cell::GILOnceCell;
+use crate::pyclass::boolean_struct::False;
+use crate::types::{PyTuple, PyType};
+use crate::{
+    pyclass, pyfunction, pymethods, pyproto, wrap_pyfunction, AsPyPointer, Py, PyAny, PyCell,
+    PyClass, PyErr, PyObject, PyRef, PyRefMut, PyResult, Python,
+};
+use std::any::Any;
+use std::future::Future;
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+type BoxFuture = Pin<Box<dyn Future<Output = PyResult<PyObject>> + Send>>;
+type PanicIntoErr = Box<dyn Fn(Python, Box<dyn Any + Send + 'static>) -> PyErr + Send>;
+
+/// A Python coroutine which drives a Rust future.
+///
+/// The coroutine implements `send`, `throw` and `close` like a native Python coroutine. While the
+/// future is pending, it yields an `asyncio.Future` which is completed when the future's waker is
+/// woken, so the coroutine can be awaited under `asyncio` without any Rust async runtime. Futures
+/// which depend on such a runtime, for instance for timers or IO, still need the runtime to be
+/// running in the background.
+///
+/// Outside of a running event loop the coroutine yields `None` instead, so it can also be driven
+/// by calling `send(None)` until it raises `StopIteration`.
+///
+/// As Rust futures don't receive values when they are resumed, the values passed to `send` are
+/// ignored. An exception passed to `throw` drops the future, cancelling it, and is raised from
+/// the coroutine.
+#[pyclass]
+pub struct Coroutine {
+    name: &'static str,
+    qualname_prefix: Option<&'static str>,
+    future: Option<BoxFuture>,
+    panic_into_err: PanicIntoErr,
+    waker: Option<Arc<AsyncioWaker>>,
+}
+
+impl Coroutine {
+    /// Wraps `future` into a coroutine, whose output is converted to a Python object like the
+    /// return value of a `#[pyfunction]`.
+    ///
+    /// `name` is the `__name__` of the coroutine; its `__qualname__` is prefixed with
+    /// `qualname_prefix` when given, e.g. the name of the class of a method. A panic in the future
+    /// is converted to the exception raised from the coroutine by `panic_into_err`, e.g. the
+    /// `panic_into_err` of a `PanicPolicy`.
+    pub fn new<F, R, E>(
+        name: &'static str,
+        qualname_prefix: Option<&'static str>,
+        panic_into_err: E,
+        future: F,
+    ) -> Self
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: IntoPyCallbackOutput<PyObject>,
+        E: Fn(Python, Box<dyn Any + Send + 'static>) -> PyErr + Send + 'static,
+    {
+        let future = async move {
+            let output = future.await;
+            Python::with_gil(|py| output.convert(py))
+        };
+        Coroutine {
+            name,
+            qualname_prefix,
+            future: Some(Box::pin(future)),
+            panic_into_err: Box::new(panic_into_err),
+            waker: None,
+        }
+    }
+
+    fn poll(
+        &mut self,
+        py: Python,
+        throw: Option<PyErr>,
+    ) -> PyResult<IterNextOutput<PyObject, PyObject>> {
+        if let Some(err) = throw {
+            self.future = None;
+            return Err(err);
+        }
+        let future = match &mut self.future {
+            Some(future) => future,
+            None => {
+                return Err(PyRuntimeError::new_err(
+                    "cannot reuse already awaited coroutine",
+                ))
+            }
+        };
+
+        // The waker can be reused if the future didn't keep a clone of it
+        match self.waker.as_mut().and_then(Arc::get_mut) {
+            Some(waker) => waker.reset(),
+            None => self.waker = Some(Arc::new(AsyncioWaker::new())),
+        }
+        let waker = self.waker.clone().unwrap();
+        let waker = unsafe { Waker::from_raw(raw_waker(waker)) };
+        let poll = crate::panic::catch_unwind(AssertUnwindSafe(|| {
+            future.as_mut().poll(&mut Context::from_waker(&waker))
+        }));
+        match poll {
+            Ok(Poll::Ready(result)) => {
+                self.future = None;
+                return result.map(IterNextOutput::Return);
+            }
+            Ok(Poll::Pending) => {}
+            Err(payload) => {
+                self.future = None;
+                return Err((self.panic_into_err)(py, payload));
+            }
+        }
+
+        // If the waker was woken while the future was polled, no `asyncio.Future` is created and
+        // the coroutine yields `None`, which makes asyncio resume it right away.
+        if let Some(future) = self.waker.as_ref().unwrap().initialize_future(py)? {
+            // `asyncio.Future.__await__` yields the future itself, flagged as awaited
+            if let Some(future) = future.iter()?.next() {
+                return Ok(IterNextOutput::Yield(future?.into()));
+            }
+        }
+        Ok(IterNextOutput::Yield(py.None()))
+    }
+}
+
+#[pymethods]
+impl Coroutine {
+    #[getter]
+    fn __name__(&self) -> &'static str {
+        self.name
+    }
+
+    #[getter]
+    fn __qualname__(&self) -> String {
+        match self.qualname_prefix {
+            Some(prefix) => format!("{}.{}", prefix, self.name),
+            None => self.name.to_string(),
+        }
+    }
+
+    /// Resumes the coroutine, returning the next value it yields.
+    ///
+    /// Raises `StopIteration` with the result of the future when it is complete.
+    fn send(&mut self, py: Python, _value: &PyAny) -> PyResult<PyObject> {
+        match self.poll(py, None)? {
+            IterNextOutput::Yield(value) => Ok(value),
+            IterNextOutput::Return(value) => Err(PyStopIteration::new_err((value,))),
+        }
+    }
+
+    /// Cancels the coroutine and raises an exception, with the arguments of
+    /// [`generator.throw`](https://docs.python.org/3/reference/expressions.html#generator.throw).
+    #[args(value = "None", traceback = "None")]
+    fn throw(
+        &mut self,
+        py: Python,
+        typ: &PyAny,
+        value: Option<&PyAny>,
+        traceback: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let exc = thrown_exception(typ, value, traceback)?;
+        match self.poll(py, Some(PyErr::from_instance(exc)))? {
+            IterNextOutput::Yield(value) => Ok(value),
+            IterNextOutput::Return(value) => Err(PyStopIteration::new_err((value,))),
+        }
+    }
+
+    /// Cancels the coroutine by dropping its future.
+    fn close(&mut self) {
+        self.future = None;
+    }
+}
+
+/// Builds the exception thrown by `throw(type, value=None, traceback=None)` like generators do:
+/// `type` is an exception instance, or a class instantiated with `value` unless `value` already
+/// is an instance of it.
+fn thrown_exception<'py>(
+    typ: &'py PyAny,
+    value: Option<&'py PyAny>,
+    traceback: Option<&'py PyAny>,
+) -> PyResult<&'py PyAny> {
+    let exc = match typ.downcast::<PyType>() {
+        Ok(typ) => match value {
+            None => typ.call0()?,
+            Some(value) if typ.is_instance(value)? => value,
+            Some(value) => match value.downcast::<PyTuple>() {
+                Ok(args) => typ.call1(args)?,
+                Err(_) => typ.call1((value,))?,
+            },
+        },
+        Err(_) if value.is_some() => {
+            return Err(PyTypeError::new_err(
+                "instance exception may not have a separate value",
+            ))
+        }
+        Err(_) => typ,
+    };
+    match traceback {
+        Some(traceback) => exc.call_method1("with_traceback", (traceback,)),
+        None => Ok(exc),
+    }
+}
+
+#[pyproto]
+impl PyAsyncProtocol for Coroutine {
+    fn __await__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+}
+
+#[pyproto]
+impl PyIterProtocol for Coroutine {
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<IterNextOutput<PyObject, PyObject>> {
+        Python::with_gil(|py| slf.poll(py, None))
+    }
+}
+
+/// Wakes a [`Coroutine`] by completing the `asyncio.Future` it yielded.
+///
+/// The cell is set when the coroutine yields after polling its future, or when the waker is woken
+/// before that, in which case it holds `None`.
+struct AsyncioWaker(GILOnceCell<Option<LoopAndFuture>>);
+
+impl AsyncioWaker {
+    fn new() -> Self {
+        AsyncioWaker(GILOnceCell::new())
+    }
+
+    fn reset(&mut self) {
+        *self = AsyncioWaker::new();
+    }
+
+    fn initialize_future<'a>(&'a self, py: Python<'a>) -> PyResult<Option<&'a PyAny>> {
+        if self.0.get(py).is_none() {
+            let _ = self.0.set(py, LoopAndFuture::new(py)?);
+        }
+        let loop_and_future = self.0.get(py).unwrap();
+        Ok(loop_and_future
+            .as_ref()
+            .map(|loop_and_future| loop_and_future.future.as_ref(py)))
+    }
+
+    fn wake(&self) {
+        Python::with_gil(|py| {
+            if let Some(loop_and_future) = self.0.get_or_init(py, || None) {
+                if let Err(err) = loop_and_future.set_result(py) {
+                    err.print(py);
+                }
+            }
+        })
+    }
+}
+
+struct LoopAndFuture {
+    event_loop: PyObject,
+    future: PyObject,
+}
+
+impl LoopAndFuture {
+    /// Creates a future on the running event loop, if there is one.
+    fn new(py: Python) -> PyResult<Option<Self>> {
+        let event_loop = match py.import("asyncio")?.call_method0("get_running_loop") {
+            Ok(event_loop) => event_loop,
+            Err(err) if err.is_instance::<PyRuntimeError>(py) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let future = event_loop.call_method0("create_future")?;
+        Ok(Some(LoopAndFuture {
+            event_loop: event_loop.into(),
+            future: future.into(),
+        }))
+    }
+
+    /// Completes the future from the event loop's thread, as wakers can be woken from any thread.
+    fn set_result(&self, py: Python) -> PyResult<()> {
+        static RELEASE_WAITER: GILOnceCell<PyObject> = GILOnceCell::new();
+        let release_waiter = RELEASE_WAITER.get_or_try_init_in_interpreter(py, || {
+            wrap_pyfunction!(release_waiter)(py).map(Into::into)
+        })?;
+        // The coroutine may have been cancelled with its event loop
+        if self.event_loop.call_method0(py, "is_closed")?.is_true(py)? {
+            return Ok(());
+        }
+        self.event_loop.call_method1(
+            py,
+            "call_soon_threadsafe",
+            (release_waiter, self.future.clone_ref(py)),
+        )?;
+        Ok(())
+    }
+}
+
+/// Completes the future yielded by a coroutine, unless it was cancelled in the meantime.
+#[pyfunction]
+fn release_waiter(future: &PyAny) -> PyResult<()> {
+    if !future.call_method0("done")?.is_true()? {
+        future.call_method1("set_result", (future.py().None(),))?;
+    }
+    Ok(())
+}
+
+static WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+fn raw_waker(waker: Arc<AsyncioWaker>) -> RawWaker {
+    RawWaker::new(Arc::into_raw(waker) as *const (), &WAKER_VTABLE)
+}
+
+unsafe fn clone_waker(ptr: *const ()) -> RawWaker {
+    let waker = ManuallyDrop::new(Arc::from_raw(ptr as *const AsyncioWaker));
+    raw_waker(Arc::clone(&waker))
+}
+
+unsafe fn wake(ptr: *const ()) {
+    Arc::from_raw(ptr as *const AsyncioWaker).wake();
+}
+
+unsafe fn wake_by_ref(ptr: *const ()) {
+    ManuallyDrop::new(Arc::from_raw(ptr as *const AsyncioWaker)).wake();
+}
+
+unsafe fn drop_waker(ptr: *const ()) {
+    drop(Arc::from_raw(ptr as *const AsyncioWaker));
+}
+
+/// A shared borrow of a `#[pyclass]` value which isn't bound to the GIL lifetime, used by the
+/// futures of `async fn` methods taking `&self`.
+///
+/// The [`Coroutine`] driving the future can be resumed from any thread, so the value must be
+/// `Send` and `Sync`, which rules out `#[pyclass(unsendable)]` classes.
+#[doc(hidden)]
+pub struct RefGuard<T: PyClass + Send + Sync>(Py<T>);
+
+impl<T: PyClass + Send + Sync> RefGuard<T> {
+    pub fn new(cell: &PyCell<T>) -> PyResult<Self> {
+        mem::forget(cell.try_borrow()?);
+        Ok(RefGuard(cell.into()))
+    }
+}
+
+impl<T: PyClass + Send + Sync> Deref for RefGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the guard holds a shared borrow of the cell, which it keeps alive
+        unsafe { &*(*(self.0.as_ptr() as *const PyCell<T>)).get_ptr() }
+    }
+}
+
+impl<T: PyClass + Send + Sync> Drop for RefGuard<T> {
+    fn drop(&mut self) {
+        Python::with_gil(|py| unsafe { self.0.as_ref(py).release_ref() })
+    }
+}
+
+/// A mutable borrow of a `#[pyclass]` value which isn't bound to the GIL lifetime, used by the
+/// futures of `async fn` methods taking `&mut self`.
+///
+/// Like for [`RefGuard`], the value must be `Send`.
+#[doc(hidden)]
+pub struct RefMutGuard<T: PyClass<Frozen = False> + Send>(Py<T>);
+
+impl<T: PyClass<Frozen = False> + Send> RefMutGuard<T> {
+    pub fn new(cell: &PyCell<T>) -> PyResult<Self> {
+        mem::forget(cell.try_borrow_mut()?);
+        Ok(RefMutGuard(cell.into()))
+    }
+}
+
+impl<T: PyClass<Frozen = False> + Send> Deref for RefMutGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the guard holds the mutable borrow of the cell, which it keeps alive
+        unsafe { &*(*(self.0.as_ptr() as *const PyCell<T>)).get_ptr() }
+    }
+}
+
+impl<T: PyClass<Frozen = False> + Send> DerefMut for RefMutGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds the mutable borrow of the cell, which it keeps alive
+        unsafe { &mut *(*(self.0.as_ptr() as *const PyCell<T>)).get_ptr() }
+    }
+}
+
+impl<T: PyClass<Frozen = False> + Send> Drop for RefMutGuard<T> {
+    fn drop(&mut self) {
+        Python::with_gil(|py| unsafe { self.0.as_ref(py).release_mut() })
+    }
+}
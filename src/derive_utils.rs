@@ -5,11 +5,14 @@
 //! Functionality for the code generated by the derive backend
 
 use crate::err::{PyErr, PyResult};
-use crate::exceptions::PyTypeError;
+use crate::exceptions::{PyImportError, PyTypeError};
 use crate::pyclass::PyClass;
 use crate::types::{PyAny, PyDict, PyModule, PyString, PyTuple};
-use crate::{ffi, PyCell, Python};
+use crate::{ffi, module_state, AsPyPointer, PyCell, PyObject, Python};
 use std::cell::UnsafeCell;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+use std::sync::Once;
 
 #[derive(Debug)]
 pub struct KeywordOnlyParameterDescription {
@@ -285,7 +288,13 @@ pub fn argument_extraction_error(py: Python, arg_name: &str, error: PyErr) -> Py
 }
 
 /// `Sync` wrapper of `ffi::PyModuleDef`.
-pub struct ModuleDef(UnsafeCell<ffi::PyModuleDef>);
+pub struct ModuleDef {
+    ffi_def: UnsafeCell<ffi::PyModuleDef>,
+    // The `Py_mod_exec` slot and the terminating zeroed slot of multi-phase modules.
+    slots: UnsafeCell<[ffi::PyModuleDef_Slot; 2]>,
+    // Guards the setup of the fields used by multi-phase initialization.
+    multi_phase: Once,
+}
 
 unsafe impl Sync for ModuleDef {}
 
@@ -307,23 +316,156 @@ impl ModuleDef {
             m_free: None,
         };
 
-        ModuleDef(UnsafeCell::new(ffi::PyModuleDef {
-            m_name: name.as_ptr() as *const _,
-            m_doc: doc.as_ptr() as *const _,
-            ..INIT
-        }))
+        const NO_SLOT: ffi::PyModuleDef_Slot = ffi::PyModuleDef_Slot {
+            slot: 0,
+            value: std::ptr::null_mut(),
+        };
+
+        ModuleDef {
+            ffi_def: UnsafeCell::new(ffi::PyModuleDef {
+                m_name: name.as_ptr() as *const _,
+                m_doc: doc.as_ptr() as *const _,
+                ..INIT
+            }),
+            slots: UnsafeCell::new([NO_SLOT; 2]),
+            multi_phase: Once::new(),
+        }
     }
     /// Builds a module using user given initializer. Used for `#[pymodule]`.
+    ///
+    /// Fails with `ImportError` in sub-interpreters, because the module's classes and statics
+    /// would be shared with the main interpreter.
     pub fn make_module(
         &'static self,
         py: Python,
         initializer: impl Fn(Python, &PyModule) -> PyResult<()>,
     ) -> PyResult<*mut ffi::PyObject> {
-        let module =
-            unsafe { py.from_owned_ptr_or_err::<PyModule>(ffi::PyModule_Create(self.0.get()))? };
+        if crate::gil::in_sub_interpreter() {
+            return Err(PyImportError::new_err(
+                "PyO3 modules can't be imported in sub-interpreters, unless they use \
+                 #[pyo3(multi_phase)]",
+            ));
+        }
+        let module = unsafe {
+            py.from_owned_ptr_or_err::<PyModule>(ffi::PyModule_Create(self.ffi_def.get()))?
+        };
         initializer(py, module)?;
         Ok(crate::IntoPyPointer::into_ptr(module))
     }
+
+    /// Returns the definition of a module using multi-phase initialization (PEP 489), which runs
+    /// `exec` on each new module object and has room for a
+    /// [`ModuleState`](crate::module_state::ModuleState). Used for `#[pymodule]` with
+    /// `#[pyo3(multi_phase)]`.
+    ///
+    /// # Safety
+    /// The GIL must be held.
+    pub unsafe fn init_multi_phase(
+        &'static self,
+        exec: unsafe extern "C" fn(*mut ffi::PyObject) -> c_int,
+    ) -> *mut ffi::PyObject {
+        let def = self.ffi_def.get();
+        // `PyInit_*` runs again in each interpreter importing the module, which must not rewrite
+        // a definition already in use.
+        self.multi_phase.call_once(|| {
+            let slots = &mut *self.slots.get();
+            slots[0] = ffi::PyModuleDef_Slot {
+                slot: ffi::Py_mod_exec,
+                value: exec as *mut c_void,
+            };
+            (*def).m_slots = slots.as_mut_ptr();
+            (*def).m_size = module_state::STATE_SIZE;
+            (*def).m_traverse = Some(module_state::traverse);
+            (*def).m_clear = Some(module_state::clear);
+            (*def).m_free = Some(module_state::free);
+        });
+        ffi::PyModuleDef_Init(def)
+    }
+
+    /// Runs the user given initializer on a module using multi-phase initialization. Used for
+    /// `#[pymodule]` with `#[pyo3(multi_phase)]`.
+    ///
+    /// # Safety
+    /// `module` must be a valid pointer to a module object.
+    pub unsafe fn exec_module(
+        py: Python,
+        module: *mut ffi::PyObject,
+        initializer: impl Fn(Python, &PyModule) -> PyResult<()>,
+    ) -> PyResult<c_int> {
+        initializer(py, py.from_borrowed_ptr(module))?;
+        Ok(0)
+    }
+}
+
+/// Turns the result of a `PyInit_` function into a module object. Used by `wrap_pymodule!`.
+///
+/// Modules using multi-phase initialization return their definition instead of a module, from
+/// which the module is created and executed here. An error raised by the `PyInit_` function or
+/// while executing the module is returned.
+///
+/// # Safety
+/// `init_result` must be the result of a `PyInit_` function.
+pub unsafe fn module_from_init(py: Python, init_result: *mut ffi::PyObject) -> PyResult<PyObject> {
+    if init_result.is_null() {
+        return Err(PyErr::api_call_failed(py));
+    }
+    #[cfg(addr_of)]
+    let def_type = std::ptr::addr_of_mut!(ffi::PyModuleDef_Type);
+    #[cfg(not(addr_of))]
+    let def_type = &mut ffi::PyModuleDef_Type as *mut _;
+    if ffi::PyObject_TypeCheck(init_result, def_type) == 0 {
+        return Ok(PyObject::from_owned_ptr(py, init_result));
+    }
+    // The definition is static, and not owned by its caller.
+    create_module(py, init_result as *mut ffi::PyModuleDef)
+}
+
+unsafe fn create_module(py: Python, def: *mut ffi::PyModuleDef) -> PyResult<PyObject> {
+    let name = CStr::from_ptr((*def).m_name).to_str()?;
+    // Only the name of the spec is used by modules which don't provide `Py_mod_create`.
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("name", name)?;
+    let spec = py
+        .import("types")?
+        .getattr("SimpleNamespace")?
+        .call((), Some(kwargs))?;
+    let module =
+        PyObject::from_owned_ptr_or_err(py, ffi::PyModule_FromDefAndSpec(def, spec.as_ptr()))?;
+    if ffi::PyModule_ExecDef(module.as_ptr(), def) != 0 {
+        return Err(PyErr::api_call_failed(py));
+    }
+    Ok(module)
+}
+
+/// Returns the object built by `#[derive(IntoPyObject)]`.
+///
+/// `IntoPy::into_py` cannot fail, so an error raised while building the object is printed and
+/// turned into a panic, like other failed Python API calls. Values returned to Python are
+/// converted with the derived `IntoPy::try_into_py` instead, which raises the error.
+pub fn unwrap_into_py(py: Python, result: PyResult<PyObject>) -> PyObject {
+    result.unwrap_or_else(|err| {
+        err.restore(py);
+        crate::err::panic_after_error(py)
+    })
+}
+
+/// Stores the fields of a type converted to a dict by `#[derive(IntoPyObject)]`.
+///
+/// This is what lets `#[pyo3(flatten)]` fields and the inner value of a newtype variant be merged
+/// into the dict of the containing type.
+#[doc(hidden)]
+pub trait IntoPyDictFields {
+    fn set_dict_fields(self, py: Python, dict: &PyDict) -> PyResult<()>;
+}
+
+/// Stores the attributes of a type converted to a `types.SimpleNamespace` by
+/// `#[derive(IntoPyObject)]` in `dict`, the keyword arguments of the namespace.
+///
+/// This is what lets `#[pyo3(flatten)]` fields be merged into the namespace of the containing
+/// type.
+#[doc(hidden)]
+pub trait IntoPyNamespaceFields {
+    fn set_namespace_fields(self, py: Python, dict: &PyDict) -> PyResult<()>;
 }
 
 /// Utility trait to enable &PyClass as a pymethod/function argument
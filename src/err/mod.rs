@@ -19,9 +19,11 @@ use std::ptr::NonNull;
 
 mod err_state;
 mod impls;
+mod traceback;
 
 pub use err_state::PyErrArguments;
 use err_state::{boxed_args, PyErrState, PyErrStateNormalized};
+pub use traceback::TracebackFrame;
 
 /// Represents a Python exception that was raised.
 pub struct PyErr {
@@ -107,7 +109,8 @@ impl PyErr {
 
     /// Creates a new PyErr.
     ///
-    /// `obj` must be an Python exception instance, the PyErr will use that instance.
+    /// `obj` must be an Python exception instance, the PyErr will use that instance, along with
+    /// its `__traceback__` like Python's `raise obj` does.
     /// If `obj` is a Python exception type object, the PyErr will (lazily) create a new
     /// instance of that type.
     /// Otherwise, a `TypeError` is created instead.
@@ -138,7 +141,9 @@ impl PyErr {
                     Py::from_borrowed_ptr(obj.py(), ffi::PyExceptionInstance_Class(ptr))
                 },
                 pvalue: unsafe { Py::from_borrowed_ptr(obj.py(), obj.as_ptr()) },
-                ptraceback: None,
+                ptraceback: unsafe {
+                    Py::from_owned_ptr_or_opt(obj.py(), ffi::PyException_GetTraceback(ptr))
+                },
             })
         } else if unsafe { ffi::PyExceptionClass_Check(obj.as_ptr()) } != 0 {
             PyErrState::FfiTuple {
@@ -250,6 +255,18 @@ impl PyErr {
         }
     }
 
+    /// Retrieves the current error like [`PyErr::fetch`], without resuming panics.
+    ///
+    /// # Safety
+    /// The error indicator must be set.
+    pub(crate) unsafe fn fetch_raw(py: Python) -> PyErr {
+        let mut ptype: *mut ffi::PyObject = std::ptr::null_mut();
+        let mut pvalue: *mut ffi::PyObject = std::ptr::null_mut();
+        let mut ptraceback: *mut ffi::PyObject = std::ptr::null_mut();
+        ffi::PyErr_Fetch(&mut ptype, &mut pvalue, &mut ptraceback);
+        PyErr::new_from_ffi_tuple(py, ptype, pvalue, ptraceback)
+    }
+
     /// Retrieves the current error from the Python interpreter's global state.
     ///
     /// The error is cleared from the Python interpreter.
@@ -336,6 +353,63 @@ impl PyErr {
         unsafe { ffi::PyErr_PrintEx(1) }
     }
 
+    /// Formats the exception with its traceback, the same as Python's
+    /// `"".join(traceback.format_exception(...))`.
+    ///
+    /// Like a traceback printed by Python, the result includes the exceptions this one was raised
+    /// from or while handling, and ends with a newline. This is the text written by
+    /// [`PyErr::print`], for when it should go somewhere other than `sys.stderr`, like a log.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use pyo3::Python;
+    /// Python::with_gil(|py| {
+    ///     let err = py.run("1 / 0", None, None).unwrap_err();
+    ///     assert_eq!(
+    ///         err.format_exception(py).unwrap(),
+    ///         "Traceback (most recent call last):\n  \
+    ///            File \"<string>\", line 1, in <module>\n\
+    ///          ZeroDivisionError: division by zero\n"
+    ///     );
+    /// });
+    /// ```
+    pub fn format_exception(&self, py: Python) -> PyResult<String> {
+        traceback::format_exception(py, self.ptype(py), self.pvalue(py), self.ptraceback(py))
+    }
+
+    /// Returns the frames of the traceback of this exception, from the outermost call to the
+    /// frame in which the exception was raised.
+    ///
+    /// The iterator is empty if the exception has no traceback, e.g. because it was created in
+    /// Rust and never raised in Python.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use pyo3::Python;
+    /// Python::with_gil(|py| {
+    ///     let err = py.run("def f():\n    1 / 0\nf()", None, None).unwrap_err();
+    ///     let functions: Vec<_> = err
+    ///         .traceback_frames(py)
+    ///         .unwrap()
+    ///         .map(|frame| (frame.function, frame.lineno))
+    ///         .collect();
+    ///     assert_eq!(
+    ///         functions,
+    ///         [("<module>".to_string(), Some(3)), ("f".to_string(), Some(2))]
+    ///     );
+    /// });
+    /// ```
+    pub fn traceback_frames(
+        &self,
+        py: Python,
+    ) -> PyResult<impl ExactSizeIterator<Item = TracebackFrame>> {
+        let frames = match self.ptraceback(py) {
+            Some(ptraceback) => traceback::extract_frames(py, ptraceback)?,
+            None => Vec::new(),
+        };
+        Ok(frames.into_iter())
+    }
+
     /// Returns true if the current exception matches the exception in `exc`.
     ///
     /// If `exc` is a class object, this also returns `true` when `self` is an instance of a subclass.
@@ -492,21 +566,38 @@ impl PyErr {
     }
 }
 
+/// The traceback is shown formatted like by Python, or as the traceback object if formatting fails.
 impl std::fmt::Debug for PyErr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         Python::with_gil(|py| {
-            f.debug_struct("PyErr")
+            let mut debug = f.debug_struct("PyErr");
+            debug
                 .field("type", self.ptype(py))
-                .field("value", self.pvalue(py))
-                .field("traceback", &self.ptraceback(py))
-                .finish()
+                .field("value", self.pvalue(py));
+            match self.ptraceback(py) {
+                Some(ptraceback) => match traceback::format_traceback(py, ptraceback) {
+                    Ok(formatted) => debug.field("traceback", &Some(formatted)),
+                    Err(_) => debug.field("traceback", &Some(ptraceback)),
+                },
+                None => debug.field("traceback", &None::<String>),
+            };
+            debug.finish()
         })
     }
 }
 
+/// Shows the type and message of the exception, e.g. `ValueError: invalid literal`.
+///
+/// The alternate form (`{:#}`) shows the whole [`PyErr::format_exception`] output instead, without
+/// the final newline.
 impl std::fmt::Display for PyErr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         Python::with_gil(|py| {
+            if f.alternate() {
+                if let Ok(formatted) = self.format_exception(py) {
+                    return f.write_str(formatted.trim_end_matches('\n'));
+                }
+            }
             let instance = self.instance(py);
             let type_name = instance.get_type().name().map_err(|_| std::fmt::Error)?;
             write!(f, "{}", type_name)?;
@@ -585,7 +676,8 @@ fn exceptions_must_derive_from_base_exception(py: Python) -> PyErr {
 mod tests {
     use super::PyErrState;
     use crate::exceptions;
-    use crate::{PyErr, Python};
+    use crate::AsPyPointer;
+    use crate::{PyErr, Python, TracebackFrame};
 
     #[test]
     fn no_error() {
@@ -653,7 +745,7 @@ mod tests {
         // PyErr {
         //     type: <class 'Exception'>,
         //     value: Exception('banana'),
-        //     traceback: Some(<traceback object at 0x..)"
+        //     traceback: Some("Traceback (most recent call last):\n  File \"<string>\", ...")
         // }
 
         Python::with_gil(|py| {
@@ -666,7 +758,7 @@ mod tests {
             assert!(debug_str.ends_with(" }"));
 
             // strip "PyErr { " and " }"
-            let mut fields = debug_str["PyErr { ".len()..debug_str.len() - 2].split(", ");
+            let mut fields = debug_str["PyErr { ".len()..debug_str.len() - 2].splitn(3, ", ");
 
             assert_eq!(fields.next().unwrap(), "type: <class 'Exception'>");
             if py.version_info() >= (3, 7) {
@@ -676,14 +768,124 @@ mod tests {
                 assert_eq!(fields.next().unwrap(), ("value: Exception('banana',)"));
             }
 
-            let traceback = fields.next().unwrap();
-            assert!(traceback.starts_with("traceback: Some(<traceback object at 0x"));
-            assert!(traceback.ends_with(">)"));
+            assert_eq!(
+                fields.next().unwrap(),
+                "traceback: Some(\"Traceback (most recent call last):\\n  \
+                 File \\\"<string>\\\", line 1, in <module>\\n\")"
+            );
 
             assert!(fields.next().is_none());
         });
     }
 
+    #[test]
+    fn err_format_exception() {
+        Python::with_gil(|py| {
+            let globals = crate::types::PyDict::new(py);
+            py.run(
+                "def fail(message):\n    raise ValueError(message)",
+                Some(globals),
+                None,
+            )
+            .unwrap();
+            let err = py
+                .run(
+                    "try:\n    fail('inner')\nexcept ValueError:\n    fail('outer')",
+                    Some(globals),
+                    None,
+                )
+                .expect_err("raising should have given us an error");
+            globals.set_item("err", err.instance(py)).unwrap();
+            let expected: String = py
+                .eval(
+                    "''.join(__import__('traceback').format_exception(type(err), err, err.__traceback__))",
+                    Some(globals),
+                    None,
+                )
+                .unwrap()
+                .extract()
+                .unwrap();
+            let formatted = err.format_exception(py).unwrap();
+            assert_eq!(formatted, expected);
+            assert!(formatted.contains("ValueError: inner"));
+            assert!(formatted.ends_with("ValueError: outer\n"));
+            assert_eq!(format!("{:#}", err), formatted.trim_end());
+
+            // an exception which was never raised has no traceback
+            let err = exceptions::PyValueError::new_err("banana");
+            assert_eq!(err.format_exception(py).unwrap(), "ValueError: banana\n");
+            assert_eq!(format!("{:#}", err), "ValueError: banana");
+            assert_eq!(err.traceback_frames(py).unwrap().len(), 0);
+        });
+    }
+
+    #[test]
+    fn err_traceback_frames() {
+        Python::with_gil(|py| {
+            let module = crate::types::PyModule::from_code(
+                py,
+                "def outer():\n    inner()\n\ndef inner():\n    raise KeyError('banana')\n",
+                "frames.py",
+                "frames",
+            )
+            .unwrap();
+            let err = module.call_method0("outer").unwrap_err();
+            let frames: Vec<TracebackFrame> = err.traceback_frames(py).unwrap().collect();
+            assert_eq!(
+                frames,
+                [
+                    TracebackFrame {
+                        filename: "frames.py".to_string(),
+                        lineno: Some(2),
+                        function: "outer".to_string(),
+                        line: None,
+                    },
+                    TracebackFrame {
+                        filename: "frames.py".to_string(),
+                        lineno: Some(5),
+                        function: "inner".to_string(),
+                        line: None,
+                    },
+                ]
+            );
+            assert_eq!(
+                frames[1].to_string(),
+                "File \"frames.py\", line 5, in inner"
+            );
+            let unknown_line = TracebackFrame {
+                lineno: None,
+                ..frames[1].clone()
+            };
+            assert_eq!(
+                unknown_line.to_string(),
+                "File \"frames.py\", line None, in inner"
+            );
+        });
+    }
+
+    #[test]
+    fn err_from_instance_keeps_traceback() {
+        Python::with_gil(|py| {
+            let module = crate::types::PyModule::from_code(
+                py,
+                "def fail():\n    raise KeyError('banana')\n\ndef caught():\n    try:\n        fail()\n    except KeyError as e:\n        return e\n",
+                "from_instance.py",
+                "from_instance",
+            )
+            .unwrap();
+            let exc = module.call_method0("caught").unwrap();
+            let err = PyErr::from_instance(exc);
+            let traceback = exc.getattr("__traceback__").unwrap();
+            assert_eq!(err.ptraceback(py).unwrap().as_ptr(), traceback.as_ptr());
+            let functions: Vec<String> = err
+                .traceback_frames(py)
+                .unwrap()
+                .map(|frame| frame.function)
+                .collect();
+            assert_eq!(functions, ["caught", "fail"]);
+        });
+    }
+
     #[test]
     fn err_display() {
         Python::with_gil(|py| {
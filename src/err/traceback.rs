@@ -0,0 +1,84 @@
+use crate::types::{PyList, PyModule};
+use crate::{PyAny, PyResult, Python};
+
+/// A frame of a Python traceback, as returned by [`PyErr::traceback_frames`](crate::PyErr::traceback_frames).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracebackFrame {
+    /// Name of the file the code was loaded from, e.g. `<string>` for code run by [`Python::run`].
+    pub filename: String,
+    /// Line number being executed in the frame, if Python knows it.
+    pub lineno: Option<usize>,
+    /// Name of the function, or `<module>` for code at the top level of a module.
+    pub function: String,
+    /// The source code of the line, without surrounding whitespace, if it could be read.
+    pub line: Option<String>,
+}
+
+impl TracebackFrame {
+    /// Extracts a frame from a `traceback.FrameSummary`.
+    fn from_summary(summary: &PyAny) -> PyResult<Self> {
+        let line: Option<String> = summary.getattr("line")?.extract()?;
+        Ok(TracebackFrame {
+            filename: summary.getattr("filename")?.extract()?,
+            lineno: summary.getattr("lineno")?.extract()?,
+            function: summary.getattr("name")?.extract()?,
+            line: line.filter(|line| !line.is_empty()),
+        })
+    }
+}
+
+impl std::fmt::Display for TracebackFrame {
+    /// Formats the frame like the `traceback` module does, e.g.
+    /// `File "script.py", line 3, in main` followed by the source line on the next line.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "File \"{}\", line ", self.filename)?;
+        match self.lineno {
+            Some(lineno) => write!(f, "{}", lineno)?,
+            None => f.write_str("None")?,
+        }
+        write!(f, ", in {}", self.function)?;
+        if let Some(line) = &self.line {
+            write!(f, "\n    {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+fn traceback_module<'py>(py: Python<'py>) -> PyResult<&'py PyModule> {
+    py.import("traceback")
+}
+
+/// Joins the strings returned by one of the `traceback.format_*` functions.
+fn join_lines(lines: &PyAny) -> PyResult<String> {
+    let lines: &PyList = lines.downcast()?;
+    let mut formatted = String::new();
+    for line in lines {
+        formatted.push_str(line.extract()?);
+    }
+    Ok(formatted)
+}
+
+/// `traceback.format_exception(ptype, pvalue, ptraceback)`, as a single string.
+pub(crate) fn format_exception(
+    py: Python,
+    ptype: &PyAny,
+    pvalue: &PyAny,
+    ptraceback: Option<&PyAny>,
+) -> PyResult<String> {
+    join_lines(traceback_module(py)?.call_method1("format_exception", (ptype, pvalue, ptraceback))?)
+}
+
+/// The frames of a traceback object, oldest first.
+pub(crate) fn extract_frames(py: Python, ptraceback: &PyAny) -> PyResult<Vec<TracebackFrame>> {
+    traceback_module(py)?
+        .call_method1("extract_tb", (ptraceback,))?
+        .iter()?
+        .map(|summary| TracebackFrame::from_summary(summary?))
+        .collect()
+}
+
+/// The traceback part of `format_exception`, starting with `Traceback (most recent call last):`.
+pub(crate) fn format_traceback(py: Python, ptraceback: &PyAny) -> PyResult<String> {
+    let frames = join_lines(traceback_module(py)?.call_method1("format_tb", (ptraceback,))?)?;
+    Ok(format!("Traceback (most recent call last):\n{}", frames))
+}
@@ -8,16 +8,39 @@
 //! The latter are created with the [`import_exception`] macro, which you can use yourself
 //! to import Python exceptions.
 
-use crate::{ffi, PyResult, Python};
+use crate::type_object::PyTypeObject;
+use crate::{ffi, PyClass, PyResult, Python};
 use std::ffi::CStr;
 use std::ops;
 use std::os::raw::c_char;
 
+// `NameError` and `AttributeError` only have their own instance layouts since Python 3.10.
+#[cfg(all(Py_3_10, not(Py_LIMITED_API)))]
+use ffi::{PyAttributeErrorObject, PyNameErrorObject};
+#[cfg(all(not(Py_3_10), not(Py_LIMITED_API)))]
+use ffi::{
+    PyBaseExceptionObject as PyAttributeErrorObject, PyBaseExceptionObject as PyNameErrorObject,
+};
+
+/// Marker for the Rust types of Python exceptions: the types of this module, the ones defined
+/// with [`create_exception!`](crate::create_exception) and
+/// [`import_exception!`](crate::import_exception), and `#[pyclass]` types extending one of them.
+pub trait PyExceptionType: PyTypeObject {}
+
+impl<T> PyExceptionType for T
+where
+    T: PyClass,
+    T::BaseNativeType: PyExceptionType,
+{
+}
+
 /// The boilerplate to convert between a Rust type and a Python exception.
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_exception_boilerplate {
     ($name: ident) => {
+        impl $crate::exceptions::PyExceptionType for $name {}
+
         impl ::std::convert::From<&$name> for $crate::PyErr {
             #[inline]
             fn from(err: &$name) -> $crate::PyErr {
@@ -107,7 +130,7 @@ macro_rules! import_exception {
                     GILOnceCell::new();
 
                 TYPE_OBJECT
-                    .get_or_init(py, || {
+                    .get_or_init_in_interpreter(py, || {
                         let imp = py
                             .import(stringify!($module))
                             .expect(concat!("Can not import module: ", stringify!($module)));
@@ -193,7 +216,7 @@ macro_rules! create_exception_type_object {
                     GILOnceCell::new();
 
                 TYPE_OBJECT
-                    .get_or_init(py, || unsafe {
+                    .get_or_init_in_interpreter(py, || unsafe {
                         $crate::Py::from_owned_ptr(
                             py,
                             $crate::PyErr::new_type(
@@ -329,7 +352,8 @@ impl_native_exception!(
 impl_native_exception!(
     PyAttributeError,
     PyExc_AttributeError,
-    native_doc!("AttributeError")
+    native_doc!("AttributeError"),
+    PyAttributeErrorObject
 );
 impl_native_exception!(PyBufferError, PyExc_BufferError, native_doc!("BufferError"));
 impl_native_exception!(PyEOFError, PyExc_EOFError, native_doc!("EOFError"));
@@ -344,12 +368,18 @@ impl_native_exception!(
     native_doc!("OSError"),
     ffi::PyOSErrorObject
 );
-impl_native_exception!(PyImportError, PyExc_ImportError, native_doc!("ImportError"));
+impl_native_exception!(
+    PyImportError,
+    PyExc_ImportError,
+    native_doc!("ImportError"),
+    ffi::PyImportErrorObject
+);
 
 impl_native_exception!(
     PyModuleNotFoundError,
     PyExc_ModuleNotFoundError,
-    native_doc!("ModuleNotFoundError")
+    native_doc!("ModuleNotFoundError"),
+    ffi::PyImportErrorObject
 );
 
 impl_native_exception!(PyIndexError, PyExc_IndexError, native_doc!("IndexError"));
@@ -360,7 +390,12 @@ impl_native_exception!(
     native_doc!("KeyboardInterrupt")
 );
 impl_native_exception!(PyMemoryError, PyExc_MemoryError, native_doc!("MemoryError"));
-impl_native_exception!(PyNameError, PyExc_NameError, native_doc!("NameError"));
+impl_native_exception!(
+    PyNameError,
+    PyExc_NameError,
+    native_doc!("NameError"),
+    PyNameErrorObject
+);
 impl_native_exception!(
     PyOverflowError,
     PyExc_OverflowError,
@@ -403,29 +438,32 @@ impl_native_exception!(PyTypeError, PyExc_TypeError, native_doc!("TypeError"));
 impl_native_exception!(
     PyUnboundLocalError,
     PyExc_UnboundLocalError,
-    native_doc!("UnboundLocalError")
+    native_doc!("UnboundLocalError"),
+    PyNameErrorObject
 );
 impl_native_exception!(
     PyUnicodeError,
     PyExc_UnicodeError,
-    native_doc!("UnicodeError"),
-    ffi::PyUnicodeErrorObject
+    native_doc!("UnicodeError")
 );
 // these three errors need arguments, so they're too annoying to write tests for using macros...
 impl_native_exception!(
     PyUnicodeDecodeError,
     PyExc_UnicodeDecodeError,
-    native_doc!("UnicodeDecodeError", "")
+    native_doc!("UnicodeDecodeError", ""),
+    ffi::PyUnicodeErrorObject
 );
 impl_native_exception!(
     PyUnicodeEncodeError,
     PyExc_UnicodeEncodeError,
-    native_doc!("UnicodeEncodeError", "")
+    native_doc!("UnicodeEncodeError", ""),
+    ffi::PyUnicodeErrorObject
 );
 impl_native_exception!(
     PyUnicodeTranslateError,
     PyExc_UnicodeTranslateError,
-    native_doc!("UnicodeTranslateError", "")
+    native_doc!("UnicodeTranslateError", ""),
+    ffi::PyUnicodeErrorObject
 );
 impl_native_exception!(PyValueError, PyExc_ValueError, native_doc!("ValueError"));
 impl_native_exception!(
@@ -437,90 +475,112 @@ impl_native_exception!(
 impl_native_exception!(
     PyBlockingIOError,
     PyExc_BlockingIOError,
-    native_doc!("BlockingIOError")
+    native_doc!("BlockingIOError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyBrokenPipeError,
     PyExc_BrokenPipeError,
-    native_doc!("BrokenPipeError")
+    native_doc!("BrokenPipeError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyChildProcessError,
     PyExc_ChildProcessError,
-    native_doc!("ChildProcessError")
+    native_doc!("ChildProcessError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyConnectionError,
     PyExc_ConnectionError,
-    native_doc!("ConnectionError")
+    native_doc!("ConnectionError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyConnectionAbortedError,
     PyExc_ConnectionAbortedError,
-    native_doc!("ConnectionAbortedError")
+    native_doc!("ConnectionAbortedError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyConnectionRefusedError,
     PyExc_ConnectionRefusedError,
-    native_doc!("ConnectionRefusedError")
+    native_doc!("ConnectionRefusedError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyConnectionResetError,
     PyExc_ConnectionResetError,
-    native_doc!("ConnectionResetError")
+    native_doc!("ConnectionResetError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyFileExistsError,
     PyExc_FileExistsError,
-    native_doc!("FileExistsError")
+    native_doc!("FileExistsError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyFileNotFoundError,
     PyExc_FileNotFoundError,
-    native_doc!("FileNotFoundError")
+    native_doc!("FileNotFoundError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyInterruptedError,
     PyExc_InterruptedError,
-    native_doc!("InterruptedError")
+    native_doc!("InterruptedError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyIsADirectoryError,
     PyExc_IsADirectoryError,
-    native_doc!("IsADirectoryError")
+    native_doc!("IsADirectoryError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyNotADirectoryError,
     PyExc_NotADirectoryError,
-    native_doc!("NotADirectoryError")
+    native_doc!("NotADirectoryError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyPermissionError,
     PyExc_PermissionError,
-    native_doc!("PermissionError")
+    native_doc!("PermissionError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyProcessLookupError,
     PyExc_ProcessLookupError,
-    native_doc!("ProcessLookupError")
+    native_doc!("ProcessLookupError"),
+    ffi::PyOSErrorObject
 );
 impl_native_exception!(
     PyTimeoutError,
     PyExc_TimeoutError,
-    native_doc!("TimeoutError")
+    native_doc!("TimeoutError"),
+    ffi::PyOSErrorObject
 );
 
 impl_native_exception!(
     PyEnvironmentError,
     PyExc_EnvironmentError,
-    native_doc!("EnvironmentError")
+    native_doc!("EnvironmentError"),
+    ffi::PyOSErrorObject
+);
+impl_native_exception!(
+    PyIOError,
+    PyExc_IOError,
+    native_doc!("IOError"),
+    ffi::PyOSErrorObject
 );
-impl_native_exception!(PyIOError, PyExc_IOError, native_doc!("IOError"));
 #[cfg(windows)]
 impl_native_exception!(
     PyWindowsError,
     PyExc_WindowsError,
-    native_doc!("WindowsError")
+    native_doc!("WindowsError"),
+    ffi::PyOSErrorObject
 );
 
 impl PyUnicodeDecodeError {
@@ -822,6 +882,50 @@ mod tests {
         });
     }
 
+    #[test]
+    #[cfg(not(any(Py_LIMITED_API, PyPy)))]
+    fn native_exception_layouts() {
+        use crate::class::impl_::PyClassBaseType;
+        use crate::pycell::BorrowFlag;
+        use crate::type_object::PyTypeInfo;
+
+        // `#[pyclass(extends = ...)]` places its borrow flag right after the base layout
+        fn check_layout<T: PyClassBaseType + PyTypeInfo>(py: Python) {
+            let basicsize = unsafe { (*T::type_object_raw(py)).tp_basicsize } as usize;
+            assert_eq!(
+                std::mem::size_of::<T::LayoutAsBase>(),
+                basicsize + std::mem::size_of::<BorrowFlag>(),
+                "wrong layout for {}",
+                T::NAME
+            );
+        }
+
+        macro_rules! check_layouts {
+            ($($exc_ty:ident),*) => {
+                Python::with_gil(|py| { $(check_layout::<$exc_ty>(py);)* })
+            };
+        }
+
+        check_layouts!(
+            PyBaseException,
+            PyException,
+            PyStopIteration,
+            PyAttributeError,
+            PyOSError,
+            PyImportError,
+            PyModuleNotFoundError,
+            PyNameError,
+            PySyntaxError,
+            PySystemExit,
+            PyUnboundLocalError,
+            PyUnicodeError,
+            PyUnicodeDecodeError,
+            PyValueError,
+            PyFileNotFoundError,
+            PyIOError
+        );
+    }
+
     test_exception!(PyBaseException);
     test_exception!(PyException);
     test_exception!(PyStopAsyncIteration);
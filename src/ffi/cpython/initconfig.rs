@@ -77,6 +77,9 @@ extern "C" {
 
 /* --- PyConfig ---------------------------------------------- */
 
+// The layout of `PyConfig` changes with each Python version, and is only known up to Python 3.11.
+// It is left out for later versions rather than declared wrongly.
+#[cfg(not(Py_3_12))]
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct PyConfig {
@@ -92,10 +95,14 @@ pub struct PyConfig {
     pub _use_peg_parser: c_int,
     pub tracemalloc: c_int,
     pub import_time: c_int,
+    #[cfg(Py_3_11)]
+    pub code_debug_ranges: c_int,
     pub show_ref_count: c_int,
     #[cfg(not(Py_3_9))]
     pub show_alloc_count: c_int,
     pub dump_refs: c_int,
+    #[cfg(Py_3_11)]
+    pub dump_refs_file: *mut wchar_t,
     pub malloc_stats: c_int,
     pub filesystem_encoding: *mut wchar_t,
     pub filesystem_errors: *mut wchar_t,
@@ -110,6 +117,8 @@ pub struct PyConfig {
     pub warnoptions: PyWideStringList,
     pub site_import: c_int,
     pub bytes_warning: c_int,
+    #[cfg(Py_3_10)]
+    pub warn_default_encoding: c_int,
     pub inspect: c_int,
     pub interactive: c_int,
     pub optimization_level: c_int,
@@ -127,9 +136,13 @@ pub struct PyConfig {
     pub legacy_windows_stdio: c_int,
 
     pub check_hash_pycs_mode: *mut wchar_t,
+    #[cfg(Py_3_11)]
+    pub use_frozen_modules: c_int,
+    #[cfg(Py_3_11)]
+    pub safe_path: c_int,
+    pub pathconfig_warnings: c_int,
     #[cfg(Py_3_10)]
     pub program_name: *mut wchar_t,
-    pub pathconfig_warnings: c_int,
     pub pythonpath_env: *mut wchar_t,
     pub home: *mut wchar_t,
     #[cfg(Py_3_10)]
@@ -137,6 +150,8 @@ pub struct PyConfig {
 
     pub module_search_paths_set: c_int,
     pub module_search_paths: PyWideStringList,
+    #[cfg(Py_3_11)]
+    pub stdlib_dir: *mut wchar_t,
     pub executable: *mut wchar_t,
     pub base_executable: *mut wchar_t,
     pub prefix: *mut wchar_t,
@@ -153,10 +168,13 @@ pub struct PyConfig {
     pub _init_main: c_int,
     #[cfg(Py_3_9)]
     pub _isolated_interpreter: c_int,
+    #[cfg(Py_3_11)]
+    pub _is_python_build: c_int,
     #[cfg(all(Py_3_9, not(Py_3_10)))]
     pub orig_argv: PyWideStringList,
 }
 
+#[cfg(not(Py_3_12))]
 extern "C" {
     pub fn PyConfig_InitPythonConfig(config: *mut PyConfig);
     pub fn PyConfig_InitIsolatedConfig(config: *mut PyConfig);
@@ -1,4 +1,6 @@
-use crate::ffi::{PyConfig, PyPreConfig, PyStatus, Py_ssize_t};
+#[cfg(not(Py_3_12))]
+use crate::ffi::PyConfig;
+use crate::ffi::{PyPreConfig, PyStatus, Py_ssize_t};
 use libc::wchar_t;
 use std::os::raw::{c_char, c_int};
 
@@ -18,6 +20,7 @@ extern "C" {
     ) -> PyStatus;
     pub fn _Py_IsCoreInitialized() -> c_int;
 
+    #[cfg(not(Py_3_12))]
     pub fn Py_InitializeFromConfig(config: *const PyConfig) -> PyStatus;
     pub fn _Py_InitializeMain() -> PyStatus;
 
@@ -8,6 +8,8 @@ pub struct PyBaseExceptionObject {
     pub ob_base: PyObject,
     pub dict: *mut PyObject,
     pub args: *mut PyObject,
+    #[cfg(Py_3_11)]
+    pub notes: *mut PyObject,
     pub traceback: *mut PyObject,
     pub context: *mut PyObject,
     pub cause: *mut PyObject,
@@ -22,6 +24,10 @@ pub struct PySyntaxErrorObject {
     pub filename: *mut PyObject,
     pub lineno: *mut PyObject,
     pub offset: *mut PyObject,
+    #[cfg(Py_3_10)]
+    pub end_lineno: *mut PyObject,
+    #[cfg(Py_3_10)]
+    pub end_offset: *mut PyObject,
     pub text: *mut PyObject,
     pub print_file_and_line: *mut PyObject,
 }
@@ -33,6 +39,25 @@ pub struct PyImportErrorObject {
     pub msg: *mut PyObject,
     pub name: *mut PyObject,
     pub path: *mut PyObject,
+    #[cfg(Py_3_12)]
+    pub name_from: *mut PyObject,
+}
+
+#[cfg(Py_3_10)]
+#[repr(C)]
+#[derive(Debug)]
+pub struct PyNameErrorObject {
+    pub exception_base: PyBaseExceptionObject,
+    pub name: *mut PyObject,
+}
+
+#[cfg(Py_3_10)]
+#[repr(C)]
+#[derive(Debug)]
+pub struct PyAttributeErrorObject {
+    pub exception_base: PyBaseExceptionObject,
+    pub obj: *mut PyObject,
+    pub name: *mut PyObject,
 }
 
 #[repr(C)]
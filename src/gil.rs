@@ -2,12 +2,14 @@
 
 //! Interaction with Python's global interpreter lock
 
-use crate::{ffi, internal_tricks::Unsendable, Python};
+#[cfg(all(Py_3_9, not(PyPy)))]
+use crate::PyTryFrom;
+use crate::{ffi, internal_tricks::Unsendable, types::PyDict, Python};
 use parking_lot::{const_mutex, Mutex, Once};
 use std::cell::{Cell, RefCell};
 use std::{
     mem::{self, ManuallyDrop},
-    ptr::NonNull,
+    ptr::{self, NonNull},
     sync::atomic,
 };
 
@@ -24,6 +26,12 @@ thread_local! {
     /// pub(crate) because it is manipulated temporarily by `Python::allow_threads`.
     pub(crate) static GIL_COUNT: Cell<usize> = Cell::new(0);
 
+    /// The cache dict of the sub-interpreter entered by the innermost `SubInterpreter::run` call
+    /// on this thread, or null outside of `run`.
+    ///
+    /// While this is non-null, the GIL is held through a sub-interpreter's thread state.
+    static SUB_INTERPRETER_CACHE: Cell<*mut ffi::PyObject> = Cell::new(ptr::null_mut());
+
     /// Temporarily hold objects that will be released when the GILPool drops.
     static OWNED_OBJECTS: RefCell<Vec<NonNull<ffi::PyObject>>> = RefCell::new(Vec::with_capacity(256));
 }
@@ -38,6 +46,84 @@ pub(crate) fn gil_is_acquired() -> bool {
     GIL_COUNT.try_with(|c| c.get() > 0).unwrap_or(false)
 }
 
+/// Checks whether the current thread state belongs to a sub-interpreter.
+///
+/// Sub-interpreters entered through `SubInterpreter::run` are always detected; on Python 3.9 and
+/// up, so are sub-interpreters created by other users of the C API.
+pub(crate) fn in_sub_interpreter() -> bool {
+    if entered_sub_interpreter() {
+        return true;
+    }
+    #[cfg(all(Py_3_9, not(PyPy)))]
+    unsafe {
+        // the main interpreter always has the ID 0
+        if ffi::PyInterpreterState_GetID(ffi::PyInterpreterState_Get()) != 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Checks whether this thread is inside `SubInterpreter::run`, without calling into Python.
+#[inline]
+pub(crate) fn entered_sub_interpreter() -> bool {
+    !entered_cache().is_null()
+}
+
+#[inline]
+fn entered_cache() -> *mut ffi::PyObject {
+    SUB_INTERPRETER_CACHE
+        .try_with(|c| c.get())
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Marks this thread as running code in a sub-interpreter until `leave_sub_interpreter`, and
+/// returns the cache of the sub-interpreter entered before, if any.
+#[cfg(not(PyPy))]
+pub(crate) fn enter_sub_interpreter(cache: NonNull<ffi::PyObject>) -> *mut ffi::PyObject {
+    SUB_INTERPRETER_CACHE.with(|c| c.replace(cache.as_ptr()))
+}
+
+#[cfg(not(PyPy))]
+pub(crate) fn leave_sub_interpreter(previous: *mut ffi::PyObject) {
+    SUB_INTERPRETER_CACHE.with(|c| c.set(previous));
+}
+
+/// Returns the dict in which pyo3 caches Python objects for the current sub-interpreter, or
+/// `None` in the main interpreter.
+///
+/// Sub-interpreters created by other users of the C API are only detected on Python 3.9 and up,
+/// like in `in_sub_interpreter`; they keep the cache in their interpreter state dict.
+pub(crate) fn sub_interpreter_cache<'py>(py: Python<'py>) -> Option<&'py PyDict> {
+    let cache = entered_cache();
+    if !cache.is_null() {
+        return Some(unsafe { py.from_borrowed_ptr(cache) });
+    }
+    #[cfg(all(Py_3_9, not(PyPy)))]
+    unsafe {
+        let interp = ffi::PyInterpreterState_Get();
+        if ffi::PyInterpreterState_GetID(interp) != 0 {
+            let state = ffi::PyInterpreterState_GetDict(interp);
+            if state.is_null() {
+                // nothing can be cached, so every lookup creates the objects anew
+                return Some(PyDict::new(py));
+            }
+            let state: &PyDict = py.from_borrowed_ptr(state);
+            const KEY: &str = "pyo3.cache";
+            return Some(match state.get_item(KEY) {
+                Some(cache) => PyDict::try_from_unchecked(cache),
+                None => {
+                    let cache = PyDict::new(py);
+                    // if this fails, the objects are just cached for this lookup alone
+                    let _ = state.set_item(KEY, cache);
+                    cache
+                }
+            });
+        }
+    }
+    None
+}
+
 /// Prepares the use of Python in a free-threaded context.
 ///
 /// If the Python interpreter is not already initialized, this function will initialize it with
@@ -200,7 +286,8 @@ where
 #[allow(clippy::upper_case_acronyms)]
 #[must_use]
 pub struct GILGuard {
-    gstate: ffi::PyGILState_STATE,
+    // `None` if the GIL was already held through a sub-interpreter's thread state.
+    gstate: Option<ffi::PyGILState_STATE>,
     pool: ManuallyDrop<Option<GILPool>>,
 }
 
@@ -259,7 +346,13 @@ impl GILGuard {
     /// checking performed by `GILGuard::acquire` may fail. This includes calling
     /// as part of multi-phase interpreter initialization.
     pub(crate) fn acquire_unchecked() -> GILGuard {
-        let gstate = unsafe { ffi::PyGILState_Ensure() }; // acquire GIL
+        // `PyGILState_Ensure` only knows this thread's state in the main interpreter, and would
+        // wait forever for the GIL if it is held through a sub-interpreter's thread state.
+        let gstate = if entered_sub_interpreter() {
+            None
+        } else {
+            Some(unsafe { ffi::PyGILState_Ensure() }) // acquire GIL
+        };
 
         // If there's already a GILPool, we should not create another or this could lead to
         // incorrect dangling references in safe code (see #864).
@@ -283,7 +376,7 @@ impl Drop for GILGuard {
     fn drop(&mut self) {
         // First up, try to detect if the order of destruction is correct.
         let _ = GIL_COUNT.try_with(|c| {
-            if self.gstate == ffi::PyGILState_STATE::PyGILState_UNLOCKED && c.get() != 1 {
+            if self.gstate == Some(ffi::PyGILState_STATE::PyGILState_UNLOCKED) && c.get() != 1 {
                 // XXX: this panic commits to leaking all objects in the pool as well as
                 // potentially meaning the GIL never releases. Perhaps should be an abort?
                 // Unfortunately abort UX is much worse than panic.
@@ -304,8 +397,8 @@ impl Drop for GILGuard {
             decrement_gil_count();
         }
 
-        unsafe {
-            ffi::PyGILState_Release(self.gstate);
+        if let Some(gstate) = self.gstate {
+            unsafe { ffi::PyGILState_Release(gstate) };
         }
     }
 }
@@ -0,0 +1,208 @@
+//! Importing Python modules embedded in the Rust binary.
+//!
+//! [`PyModule::from_code`](crate::types::PyModule::from_code) creates a single module which
+//! other Python code can't import. A [`MemoryImporter`] instead installs a
+//! [finder](https://docs.python.org/3/glossary.html#term-meta-path-finder) into `sys.meta_path`,
+//! which serves packages and modules from source code or marshalled bytecode held by Rust, so
+//! that they are imported like any other module:
+//!
+//! ```rust
+//! use pyo3::importer::MemoryImporter;
+//! use pyo3::prelude::*;
+//!
+//! Python::with_gil(|py| -> PyResult<()> {
+//!     MemoryImporter::new()
+//!         .package("glue", "from glue.util import greet")
+//!         .module("glue.util", "def greet(name):\n    return f'hello {name}'\n")
+//!         .install(py)?;
+//!
+//!     py.run("import glue; assert glue.greet('world') == 'hello world'", None, None)
+//! })
+//! .unwrap();
+//! ```
+//!
+//! Source code is usually embedded with [`include_str!`]. Modules are given a `__file__` such as
+//! `<memory>/glue/util.py`, which doesn't exist on disk; tracebacks still show the source of
+//! modules embedded as source code.
+
+use crate::exceptions::PyImportError;
+use crate::types::{PyDict, PyModule};
+use crate::{pyclass, pymethods, IntoPy, PyAny, PyObject, PyRef, PyResult, Python};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// The directory the `__file__` of embedded modules is located in.
+const ROOT: &str = "<memory>";
+
+/// The code of an embedded module.
+#[derive(Clone, Debug)]
+pub enum ModuleCode {
+    /// Python source code.
+    Source(Cow<'static, str>),
+    /// A code object serialized with `marshal.dumps`, e.g. of the result of `compile()`.
+    ///
+    /// Note that `.pyc` files start with a 16 byte header, which isn't part of the marshalled
+    /// code, and that marshalled code can only be loaded by the Python version which created it.
+    Bytecode(Cow<'static, [u8]>),
+}
+
+impl From<&'static str> for ModuleCode {
+    fn from(source: &'static str) -> Self {
+        ModuleCode::Source(Cow::Borrowed(source))
+    }
+}
+
+impl From<String> for ModuleCode {
+    fn from(source: String) -> Self {
+        ModuleCode::Source(Cow::Owned(source))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    code: ModuleCode,
+    is_package: bool,
+}
+
+impl Entry {
+    /// The path used as `__file__` of the module.
+    fn origin(&self, name: &str) -> String {
+        let path = name.replace('.', "/");
+        if self.is_package {
+            format!("{}/{}/__init__.py", ROOT, path)
+        } else {
+            format!("{}/{}.py", ROOT, path)
+        }
+    }
+}
+
+/// Builds a finder which imports modules from a set of embedded sources and bytecode.
+///
+/// Submodules are named with their full dotted name, and can only be imported if their parent
+/// package was added too.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryImporter {
+    modules: HashMap<String, Entry>,
+}
+
+impl MemoryImporter {
+    /// Creates an importer without any modules.
+    pub fn new() -> Self {
+        MemoryImporter::default()
+    }
+
+    /// Adds the module `name`, e.g. `"glue.util"`.
+    pub fn module(mut self, name: impl Into<String>, code: impl Into<ModuleCode>) -> Self {
+        self.add(name.into(), code.into(), false);
+        self
+    }
+
+    /// Adds the package `name`, whose `__init__` module has the given code.
+    pub fn package(mut self, name: impl Into<String>, code: impl Into<ModuleCode>) -> Self {
+        self.add(name.into(), code.into(), true);
+        self
+    }
+
+    fn add(&mut self, name: String, code: ModuleCode, is_package: bool) {
+        self.modules.insert(name, Entry { code, is_package });
+    }
+
+    /// Inserts the finder at the start of `sys.meta_path`, so that the embedded modules take
+    /// precedence over all others, and returns it.
+    ///
+    /// The finder can be uninstalled by removing it from `sys.meta_path` again. Modules which
+    /// were already imported stay in `sys.modules`.
+    pub fn install(self, py: Python) -> PyResult<PyObject> {
+        let finder: PyObject = MemoryFinder {
+            modules: self.modules,
+        }
+        .into_py(py);
+        py.import("sys")?
+            .getattr("meta_path")?
+            .call_method1("insert", (0, &finder))?;
+        Ok(finder)
+    }
+}
+
+/// The meta path finder and loader installed by [`MemoryImporter::install`].
+#[pyclass]
+struct MemoryFinder {
+    modules: HashMap<String, Entry>,
+}
+
+impl MemoryFinder {
+    fn entry(&self, fullname: &str) -> PyResult<&Entry> {
+        self.modules.get(fullname).ok_or_else(|| {
+            PyImportError::new_err(format!("no embedded module named '{}'", fullname))
+        })
+    }
+}
+
+#[pymethods]
+impl MemoryFinder {
+    #[args(_target = "None")]
+    fn find_spec(
+        slf: PyRef<Self>,
+        py: Python,
+        fullname: &str,
+        _path: &PyAny,
+        _target: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let (origin, is_package) = match slf.modules.get(fullname) {
+            Some(entry) => (entry.origin(fullname), entry.is_package),
+            None => return Ok(py.None()),
+        };
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("origin", &origin)?;
+        kwargs.set_item("is_package", is_package)?;
+        let loader: PyObject = slf.into_py(py);
+        let spec = py
+            .import("importlib.machinery")?
+            .getattr("ModuleSpec")?
+            .call((fullname, loader), Some(kwargs))?;
+        // sets `__file__` from the origin
+        spec.setattr("has_location", true)?;
+        if is_package {
+            let directory = &origin[..origin.len() - "/__init__.py".len()];
+            spec.setattr("submodule_search_locations", vec![directory])?;
+        }
+        Ok(spec.into())
+    }
+
+    fn create_module(&self, _spec: &PyAny) -> Option<PyObject> {
+        // use the default module creation
+        None
+    }
+
+    fn exec_module(&self, py: Python, module: &PyModule) -> PyResult<()> {
+        let code = self.get_code(py, module.name()?)?;
+        let exec = py.import("builtins")?.getattr("exec")?;
+        exec.call1((code, module.dict()))?;
+        Ok(())
+    }
+
+    fn get_code(&self, py: Python, fullname: &str) -> PyResult<PyObject> {
+        let entry = self.entry(fullname)?;
+        let code = match &entry.code {
+            ModuleCode::Source(source) => py.import("builtins")?.call_method1(
+                "compile",
+                (source.as_ref(), entry.origin(fullname), "exec", 0, true),
+            )?,
+            ModuleCode::Bytecode(bytecode) => py
+                .import("marshal")?
+                .call_method1("loads", (bytecode.as_ref(),))?,
+        };
+        Ok(code.into())
+    }
+
+    fn get_source(&self, fullname: &str) -> PyResult<Option<&str>> {
+        match &self.entry(fullname)?.code {
+            ModuleCode::Source(source) => Ok(Some(source)),
+            ModuleCode::Bytecode(_) => Ok(None),
+        }
+    }
+
+    fn is_package(&self, fullname: &str) -> PyResult<bool> {
+        Ok(self.entry(fullname)?.is_package)
+    }
+}
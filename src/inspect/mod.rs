@@ -0,0 +1,352 @@
+//! Introspection of the functions and classes generated by the PyO3 macros.
+//!
+//! `#[pyfunction]`, `#[pyclass]` and `#[pymethods]` record the Python signature of each item they
+//! generate, with the argument and return types given by
+//! [`FromPyObject::type_input`](crate::FromPyObject::type_input) and
+//! [`IntoPy::type_output`](crate::IntoPy::type_output).
+//! The [`stubs`] module uses this information to write type stubs (`.pyi` files) for a module.
+//!
+//! The signatures are only generated with the `inspect` feature, which also records every
+//! function and class created by the macros in a global registry for [`function_info`],
+//! [`class_info`] and [`stubs`].
+//!
+//! ```rust
+//! # #[cfg(feature = "inspect")]
+//! # {
+//! use pyo3::prelude::*;
+//! use pyo3::inspect::function_info;
+//! use pyo3::types::PyCFunction;
+//! use pyo3::wrap_pyfunction;
+//!
+//! #[pyfunction]
+//! fn double(x: usize) -> usize {
+//!     x * 2
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let function: &PyCFunction = wrap_pyfunction!(double)(py).unwrap();
+//!     let info = function_info(function).unwrap();
+//!     assert_eq!(info.name, "double");
+//!     assert_eq!(info.parameters[0].annotation.to_annotation(py, "builtins").unwrap(), "int");
+//! });
+//! # }
+//! ```
+
+#[cfg(feature = "inspect")]
+#[cfg_attr(docsrs, doc(cfg(feature = "inspect")))]
+pub mod stubs;
+pub mod types;
+
+#[cfg(feature = "inspect")]
+use crate::class::methods::{PyMethodDef, PyMethodDefType};
+#[cfg(feature = "inspect")]
+use crate::pyclass::PyClass;
+#[cfg(feature = "inspect")]
+use crate::types::{PyCFunction, PyType};
+#[cfg(feature = "inspect")]
+use crate::{ffi, AsPyPointer};
+use crate::{FromPyObject, IntoPy, PyObject};
+#[cfg(feature = "inspect")]
+use parking_lot::{const_mutex, Mutex};
+#[cfg(feature = "inspect")]
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use types::TypeInfo;
+
+/// The signature of a Python function or method.
+#[derive(Clone, Debug)]
+pub struct FunctionInfo {
+    /// The name of the function in Python.
+    pub name: &'static str,
+    pub kind: FunctionKind,
+    /// The parameters, without the `self` or `cls` receiver.
+    pub parameters: Vec<ParameterInfo>,
+    pub return_type: TypeInfo,
+}
+
+/// The kind of function described by a [`FunctionInfo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    /// A module-level function
+    Function,
+    /// An instance method
+    Method,
+    /// A `#[classmethod]`
+    ClassMethod,
+    /// A `#[staticmethod]`
+    StaticMethod,
+    /// The `#[new]` constructor
+    New,
+}
+
+/// A parameter of a [`FunctionInfo`].
+#[derive(Clone, Debug)]
+pub struct ParameterInfo {
+    pub name: &'static str,
+    pub kind: ParameterKind,
+    pub annotation: TypeInfo,
+    /// The default value rendered as a Python expression, or `...` if it can't be expressed in
+    /// Python.
+    pub default: Option<&'static str>,
+}
+
+/// How a [`ParameterInfo`] is passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterKind {
+    PositionalOnly,
+    PositionalOrKeyword,
+    /// `*args`
+    VarPositional,
+    KeywordOnly,
+    /// `**kwargs`
+    VarKeyword,
+}
+
+/// The members of a `#[pyclass]`.
+#[derive(Clone, Debug)]
+pub struct ClassInfo {
+    /// The name of the class in Python.
+    pub name: &'static str,
+    /// The base class, for classes declared with `extends`.
+    pub base: Option<TypeInfo>,
+    /// The `#[new]` constructor.
+    pub new: Option<FunctionInfo>,
+    pub methods: Vec<FunctionInfo>,
+    pub attributes: Vec<AttributeInfo>,
+}
+
+/// A class attribute or property of a [`ClassInfo`].
+#[derive(Clone, Debug)]
+pub struct AttributeInfo {
+    pub name: &'static str,
+    pub annotation: TypeInfo,
+    pub kind: AttributeKind,
+}
+
+/// The kind of an [`AttributeInfo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// A `#[classattr]`
+    ClassAttribute,
+    /// A property defined by `#[getter]`, `#[setter]` or `#[pyo3(get, set)]`.
+    Property { readable: bool, writable: bool },
+}
+
+#[cfg(feature = "inspect")]
+impl ClassInfo {
+    /// Collects the members of `T` from the items generated by `#[pyclass]` and `#[pymethods]`.
+    pub fn of<T: PyClass>() -> Self {
+        let mut methods = Vec::new();
+        let mut attributes: Vec<AttributeInfo> = Vec::new();
+        T::for_each_method_def(&mut |defs| {
+            for def in defs {
+                match def {
+                    PyMethodDefType::Method(def) => {
+                        methods.push(method_info(def, FunctionKind::Method))
+                    }
+                    PyMethodDefType::Class(def) => {
+                        methods.push(method_info(def, FunctionKind::ClassMethod))
+                    }
+                    PyMethodDefType::Static(def) => {
+                        methods.push(method_info(def, FunctionKind::StaticMethod))
+                    }
+                    PyMethodDefType::ClassAttribute(def) => attributes.push(AttributeInfo {
+                        name: strip_nul(def.name),
+                        annotation: TypeInfo::Any,
+                        kind: AttributeKind::ClassAttribute,
+                    }),
+                    PyMethodDefType::Getter(def) => {
+                        let annotation = def.type_info.map(|info| info.0());
+                        add_property(&mut attributes, strip_nul(def.name), annotation, true);
+                    }
+                    PyMethodDefType::Setter(def) => {
+                        let annotation = def.type_info.map(|info| info.0());
+                        add_property(&mut attributes, strip_nul(def.name), annotation, false);
+                    }
+                }
+            }
+        });
+        ClassInfo {
+            name: T::NAME,
+            base: if T::IS_SUBCLASS {
+                Some(TypeInfo::of::<T::BaseType>())
+            } else {
+                None
+            },
+            new: T::get_new_info(),
+            methods,
+            attributes,
+        }
+    }
+}
+
+impl FunctionInfo {
+    /// The signature of a function without recorded information: `(*args, **kwargs) -> Any`.
+    pub fn unknown(name: &'static str, kind: FunctionKind) -> Self {
+        let parameter = |name, kind| ParameterInfo {
+            name,
+            kind,
+            annotation: TypeInfo::Any,
+            default: None,
+        };
+        FunctionInfo {
+            name,
+            kind,
+            parameters: vec![
+                parameter("args", ParameterKind::VarPositional),
+                parameter("kwargs", ParameterKind::VarKeyword),
+            ],
+            return_type: TypeInfo::Any,
+        }
+    }
+}
+
+#[cfg(feature = "inspect")]
+fn method_info(def: &PyMethodDef, kind: FunctionKind) -> FunctionInfo {
+    match def.info {
+        Some(info) => info.0(),
+        None => FunctionInfo::unknown(strip_nul(def.ml_name), kind),
+    }
+}
+
+#[cfg(feature = "inspect")]
+fn add_property(
+    attributes: &mut Vec<AttributeInfo>,
+    name: &'static str,
+    annotation: Option<TypeInfo>,
+    getter: bool,
+) {
+    let index = match attributes.iter().position(|attr| attr.name == name) {
+        Some(index) => index,
+        None => {
+            attributes.push(AttributeInfo {
+                name,
+                annotation: TypeInfo::Any,
+                kind: AttributeKind::Property {
+                    readable: false,
+                    writable: false,
+                },
+            });
+            attributes.len() - 1
+        }
+    };
+    let attribute = &mut attributes[index];
+    if let AttributeKind::Property { readable, writable } = &mut attribute.kind {
+        // The type of the getter takes precedence over the type of the setter.
+        if let Some(annotation) = annotation {
+            if getter || !*readable {
+                attribute.annotation = annotation;
+            }
+        }
+        if getter {
+            *readable = true;
+        } else {
+            *writable = true;
+        }
+    }
+}
+
+#[cfg(feature = "inspect")]
+fn strip_nul(name: &'static str) -> &'static str {
+    name.trim_end_matches('\0')
+}
+
+#[cfg(feature = "inspect")]
+type Registry<T> = Mutex<Option<HashMap<usize, fn() -> T>>>;
+
+#[cfg(feature = "inspect")]
+static FUNCTIONS: Registry<FunctionInfo> = const_mutex(None);
+#[cfg(feature = "inspect")]
+static CLASSES: Registry<ClassInfo> = const_mutex(None);
+
+#[cfg(feature = "inspect")]
+fn register<T>(registry: &Registry<T>, key: usize, info: fn() -> T) {
+    registry
+        .lock()
+        .get_or_insert_with(HashMap::new)
+        .entry(key)
+        .or_insert(info);
+}
+
+#[cfg(feature = "inspect")]
+fn lookup<T>(registry: &Registry<T>, key: usize) -> Option<T> {
+    let info = registry
+        .lock()
+        .as_ref()
+        .and_then(|registry| registry.get(&key).copied());
+    info.map(|info| info())
+}
+
+/// Records the signature of a function created by `PyCFunction::internal_new`, keyed by the
+/// address of its C function.
+#[cfg(feature = "inspect")]
+pub(crate) fn register_function(meth: usize, info: fn() -> FunctionInfo) {
+    register(&FUNCTIONS, meth, info)
+}
+
+/// Records the members of a `#[pyclass]` when its type object is created.
+#[cfg(feature = "inspect")]
+pub(crate) fn register_class(type_object: *mut ffi::PyTypeObject, info: fn() -> ClassInfo) {
+    register(&CLASSES, type_object as usize, info)
+}
+
+/// Returns the signature of a function created by `#[pyfunction]`.
+#[cfg(feature = "inspect")]
+#[cfg_attr(docsrs, doc(cfg(feature = "inspect")))]
+pub fn function_info(function: &PyCFunction) -> Option<FunctionInfo> {
+    let meth = unsafe { ffi::PyCFunction_GetFunction(function.as_ptr()) }?;
+    lookup(&FUNCTIONS, meth as usize)
+}
+
+/// Returns the members of a class created by `#[pyclass]`.
+#[cfg(feature = "inspect")]
+#[cfg_attr(docsrs, doc(cfg(feature = "inspect")))]
+pub fn class_info(ty: &PyType) -> Option<ClassInfo> {
+    lookup(&CLASSES, ty.as_type_ptr() as usize)
+}
+
+/// Selects the type information of argument and return types in the code generated by the
+/// macros, falling back to `typing.Any` for types which don't implement the conversion traits.
+#[doc(hidden)]
+pub struct TypeHint<T>(PhantomData<T>);
+
+impl<T> TypeHint<T> {
+    #[doc(hidden)]
+    pub fn new() -> Self {
+        TypeHint(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait ExtractTypeHint {
+    fn type_input(&self) -> TypeInfo;
+}
+
+impl<'a, T: FromPyObject<'a>> ExtractTypeHint for TypeHint<T> {
+    fn type_input(&self) -> TypeInfo {
+        T::type_input()
+    }
+}
+
+impl<T> ExtractTypeHint for &'_ TypeHint<T> {
+    fn type_input(&self) -> TypeInfo {
+        TypeInfo::Any
+    }
+}
+
+#[doc(hidden)]
+pub trait IntoTypeHint {
+    fn type_output(&self) -> TypeInfo;
+}
+
+impl<T: IntoPy<PyObject>> IntoTypeHint for TypeHint<T> {
+    fn type_output(&self) -> TypeInfo {
+        T::type_output()
+    }
+}
+
+impl<T> IntoTypeHint for &'_ TypeHint<T> {
+    fn type_output(&self) -> TypeInfo {
+        TypeInfo::Any
+    }
+}
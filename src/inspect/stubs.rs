@@ -0,0 +1,351 @@
+//! Generation of type stubs (`.pyi` files) for modules built with PyO3.
+//!
+//! The stubs are generated at runtime from an initialized module, using the information recorded
+//! by the macros for `#[pyfunction]`s and `#[pyclass]`es. Functions and classes which were not
+//! created by the macros are described as precisely as Python's own introspection allows.
+
+use super::types::{is_object, render_type};
+use super::{
+    class_info, function_info, AttributeKind, ClassInfo, FunctionInfo, FunctionKind, ParameterKind,
+};
+use crate::types::{PyCFunction, PyModule, PyType};
+use crate::{ffi, AsPyPointer, PyAny, PyResult, PyTryFrom, Python};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Renders the type stub of `module`.
+///
+/// Submodules, i.e. modules whose `__name__` is prefixed by the name of `module`, are referred to
+/// with `from . import name`, their stubs are written by [`write_stub_package`]. Other modules
+/// are annotated as module attributes.
+pub fn module_stub(module: &PyModule) -> PyResult<String> {
+    let module_name = module.name()?;
+    let mut imports = BTreeSet::new();
+    let mut items = Vec::new();
+    let mut submodules = Vec::new();
+
+    if let Some(doc) = docstring(module)? {
+        items.push(render_docstring(&doc, ""));
+    }
+    for (key, value) in module.dict() {
+        let name: &str = key.extract()?;
+        if is_dunder(name) {
+            continue;
+        }
+        if let Ok(function) = <PyCFunction as PyTryFrom>::try_from(value) {
+            let info = function_info(function)
+                .unwrap_or_else(|| FunctionInfo::unknown("", FunctionKind::Function));
+            let mut item = String::new();
+            render_function(
+                module.py(),
+                &mut item,
+                name,
+                &info,
+                docstring(function)?,
+                module_name,
+                &mut imports,
+                "",
+            )?;
+            items.push(item);
+        } else if as_submodule(module_name, value).is_some() {
+            submodules.push(name);
+        } else if value.is_instance::<PyModule>()? {
+            imports.insert("types".to_owned());
+            items.push(format!("{}: types.ModuleType\n", name));
+        } else if let Ok(ty) = <PyType as PyTryFrom>::try_from(value) {
+            items.push(render_class(name, ty, module_name, &mut imports)?);
+        } else {
+            let annotation = render_type(value.get_type(), module_name, &mut imports)?;
+            items.push(format!("{}: {}\n", name, annotation));
+        }
+    }
+
+    let mut stub = String::new();
+    let imports: Vec<_> = imports
+        .iter()
+        .filter(|import| *import != module_name && *import != "builtins")
+        .collect();
+    for import in &imports {
+        writeln!(stub, "import {}", import).unwrap();
+    }
+    for submodule in &submodules {
+        writeln!(stub, "from . import {}", submodule).unwrap();
+    }
+    for item in items {
+        if !stub.is_empty() {
+            stub.push('\n');
+        }
+        stub.push_str(&item);
+    }
+    Ok(stub)
+}
+
+/// Writes the type stubs of `module` and its submodules into `directory`, as a package which can
+/// be installed next to the compiled module.
+///
+/// The stub of `module` itself is written to `__init__.pyi`, alongside the `py.typed` marker of
+/// [PEP 561](https://www.python.org/dev/peps/pep-0561/). A submodule is written to `name.pyi`,
+/// or to a `name/` subpackage if it has submodules of its own. A submodule reachable under
+/// several names is only written once.
+pub fn write_stub_package(module: &PyModule, directory: impl AsRef<Path>) -> PyResult<()> {
+    let directory = directory.as_ref();
+    let mut visited = HashSet::new();
+    visited.insert(module.as_ptr());
+    write_package(module, directory, &mut visited)?;
+    fs::write(directory.join("py.typed"), "")?;
+    Ok(())
+}
+
+fn write_package(
+    module: &PyModule,
+    directory: &Path,
+    visited: &mut HashSet<*mut ffi::PyObject>,
+) -> PyResult<()> {
+    fs::create_dir_all(directory)?;
+    fs::write(directory.join("__init__.pyi"), module_stub(module)?)?;
+    for (name, submodule) in submodules(module)? {
+        if !visited.insert(submodule.as_ptr()) {
+            continue;
+        }
+        if submodules(submodule)?.is_empty() {
+            fs::write(
+                directory.join(format!("{}.pyi", name)),
+                module_stub(submodule)?,
+            )?;
+        } else {
+            write_package(submodule, &directory.join(name), visited)?;
+        }
+    }
+    Ok(())
+}
+
+fn submodules(module: &PyModule) -> PyResult<Vec<(&str, &PyModule)>> {
+    let module_name = module.name()?;
+    let mut submodules = Vec::new();
+    for (key, value) in module.dict() {
+        let name: &str = key.extract()?;
+        if !is_dunder(name) {
+            if let Some(submodule) = as_submodule(module_name, value) {
+                submodules.push((name, submodule));
+            }
+        }
+    }
+    Ok(submodules)
+}
+
+/// Returns `value` if it is a module named `{module_name}.{...}`, as opposed to another module
+/// which was imported into the module.
+fn as_submodule<'py>(module_name: &str, value: &'py PyAny) -> Option<&'py PyModule> {
+    let submodule = value.downcast::<PyModule>().ok()?;
+    let name = submodule.name().ok()?;
+    if name.len() > module_name.len() + 1
+        && name.starts_with(module_name)
+        && name[module_name.len()..].starts_with('.')
+    {
+        Some(submodule)
+    } else {
+        None
+    }
+}
+
+fn render_class(
+    name: &str,
+    ty: &PyType,
+    module: &str,
+    imports: &mut BTreeSet<String>,
+) -> PyResult<String> {
+    let info = class_info(ty);
+    let base = match info.as_ref().map(|info| &info.base) {
+        Some(Some(base)) => Some(base.render(ty.py(), module, imports)?),
+        Some(None) => None,
+        None => {
+            let base: &PyType = ty.getattr("__base__")?.downcast()?;
+            if is_object(base) {
+                None
+            } else {
+                Some(render_type(base, module, imports)?)
+            }
+        }
+    };
+
+    let mut stub = String::new();
+    match base {
+        Some(base) => writeln!(stub, "class {}({}):", name, base).unwrap(),
+        None => writeln!(stub, "class {}:", name).unwrap(),
+    }
+    let mut body = String::new();
+    if let Some(doc) = docstring(ty)? {
+        body.push_str(&render_docstring(&doc, "    "));
+    }
+    if let Some(info) = info {
+        render_class_body(&mut body, ty, &info, module, imports)?;
+    }
+    if body.is_empty() {
+        body.push_str("    ...\n");
+    }
+    stub.push_str(&body);
+    Ok(stub)
+}
+
+fn render_class_body(
+    body: &mut String,
+    ty: &PyType,
+    info: &ClassInfo,
+    module: &str,
+    imports: &mut BTreeSet<String>,
+) -> PyResult<()> {
+    let py = ty.py();
+    for attribute in &info.attributes {
+        match attribute.kind {
+            AttributeKind::ClassAttribute => {
+                imports.insert("typing".to_string());
+                let value = ty.getattr(attribute.name)?;
+                let annotation = render_type(value.get_type(), module, imports)?;
+                writeln!(
+                    body,
+                    "    {}: typing.ClassVar[{}]",
+                    attribute.name, annotation
+                )
+                .unwrap();
+            }
+            AttributeKind::Property { writable: true, .. } => {
+                let annotation = attribute.annotation.render(py, module, imports)?;
+                writeln!(body, "    {}: {}", attribute.name, annotation).unwrap();
+            }
+            AttributeKind::Property {
+                readable: true,
+                writable: false,
+            } => {
+                let annotation = attribute.annotation.render(py, module, imports)?;
+                body.push_str("    @property\n");
+                writeln!(
+                    body,
+                    "    def {}(self) -> {}: ...",
+                    attribute.name, annotation
+                )
+                .unwrap();
+            }
+            AttributeKind::Property { .. } => {}
+        }
+    }
+    if let Some(new) = &info.new {
+        render_function(py, body, "__new__", new, None, module, imports, "    ")?;
+    }
+    for method in &info.methods {
+        let doc = docstring(ty.getattr(method.name)?)?;
+        render_function(py, body, method.name, method, doc, module, imports, "    ")?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_function(
+    py: Python,
+    stub: &mut String,
+    name: &str,
+    info: &FunctionInfo,
+    doc: Option<String>,
+    module: &str,
+    imports: &mut BTreeSet<String>,
+    indent: &str,
+) -> PyResult<()> {
+    let mut parameters = Vec::new();
+    match info.kind {
+        FunctionKind::Method => parameters.push("self".to_string()),
+        FunctionKind::ClassMethod | FunctionKind::New => parameters.push("cls".to_string()),
+        FunctionKind::Function | FunctionKind::StaticMethod => {}
+    }
+    let mut keyword_only_marker = true;
+    for (i, parameter) in info.parameters.iter().enumerate() {
+        let annotation = parameter.annotation.render(py, module, imports)?;
+        let rendered = match parameter.kind {
+            ParameterKind::VarPositional => {
+                keyword_only_marker = false;
+                format!("*{}: {}", parameter.name, annotation)
+            }
+            ParameterKind::VarKeyword => format!("**{}: {}", parameter.name, annotation),
+            ParameterKind::KeywordOnly if keyword_only_marker => {
+                keyword_only_marker = false;
+                parameters.push("*".to_string());
+                format!("{}: {}", parameter.name, annotation)
+            }
+            _ => format!("{}: {}", parameter.name, annotation),
+        };
+        match parameter.default {
+            Some(default) => parameters.push(format!("{} = {}", rendered, default)),
+            None => parameters.push(rendered),
+        }
+        let next = info.parameters.get(i + 1).map(|next| next.kind);
+        if parameter.kind == ParameterKind::PositionalOnly
+            && next != Some(ParameterKind::PositionalOnly)
+        {
+            parameters.push("/".to_string());
+        }
+    }
+    let return_type = info.return_type.render(py, module, imports)?;
+
+    match info.kind {
+        FunctionKind::ClassMethod => writeln!(stub, "{}@classmethod", indent).unwrap(),
+        FunctionKind::StaticMethod => writeln!(stub, "{}@staticmethod", indent).unwrap(),
+        FunctionKind::Function | FunctionKind::Method | FunctionKind::New => {}
+    }
+    write!(
+        stub,
+        "{}def {}({}) -> {}:",
+        indent,
+        name,
+        parameters.join(", "),
+        return_type
+    )
+    .unwrap();
+    match doc {
+        Some(doc) => {
+            stub.push('\n');
+            let body_indent = format!("{}    ", indent);
+            stub.push_str(&render_docstring(&doc, &body_indent));
+            writeln!(stub, "{}...", body_indent).unwrap();
+        }
+        None => stub.push_str(" ...\n"),
+    }
+    Ok(())
+}
+
+fn docstring(object: &PyAny) -> PyResult<Option<String>> {
+    let doc = object.getattr("__doc__")?;
+    if doc.is_none() {
+        return Ok(None);
+    }
+    let doc: String = doc.extract()?;
+    let doc = doc.trim();
+    Ok(if doc.is_empty() {
+        None
+    } else {
+        Some(doc.to_string())
+    })
+}
+
+fn render_docstring(doc: &str, indent: &str) -> String {
+    let doc = doc.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+    let mut rendered = format!("{}\"\"\"", indent);
+    for (i, line) in doc.lines().enumerate() {
+        if i > 0 {
+            rendered.push('\n');
+            if !line.is_empty() {
+                rendered.push_str(indent);
+            }
+        }
+        rendered.push_str(line);
+    }
+    if doc.contains('\n') {
+        rendered.push('\n');
+        rendered.push_str(indent);
+    }
+    rendered.push_str("\"\"\"\n");
+    rendered
+}
+
+fn is_dunder(name: &str) -> bool {
+    name.starts_with("__") && name.ends_with("__")
+}
@@ -0,0 +1,158 @@
+//! Python type annotations of Rust types.
+
+use crate::type_object::{PyTypeInfo, PyTypeObject};
+use crate::types::{PyAny, PyType};
+use crate::{PyNativeType, PyResult, Python};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The Python type annotation of a Rust type, as written in type stubs.
+///
+/// It is reported by [`FromPyObject::type_input`](crate::FromPyObject::type_input) for the types
+/// accepted as arguments and by [`IntoPy::type_output`](crate::IntoPy::type_output) for the types
+/// returned to Python.
+#[derive(Clone)]
+pub enum TypeInfo {
+    /// `typing.Any`, for types which can be anything or are unknown.
+    Any,
+    /// `None`
+    None,
+    /// A builtin type such as `int`, or a type qualified by its module such as `typing.List`, with
+    /// its type arguments.
+    Builtin(&'static str, Vec<TypeInfo>),
+    /// A Python class, such as a `#[pyclass]` or a native type, identified by its type object.
+    Class(for<'py> fn(Python<'py>) -> &'py PyType),
+}
+
+impl TypeInfo {
+    /// A builtin type without type arguments, e.g. `TypeInfo::builtin("int")`.
+    pub fn builtin(name: &'static str) -> Self {
+        TypeInfo::Builtin(name, Vec::new())
+    }
+
+    /// The class of the Python type `T`.
+    pub fn of<T: PyTypeObject>() -> Self {
+        TypeInfo::Class(T::type_object)
+    }
+
+    /// `typing.Optional[T]`
+    pub fn optional_of(t: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.Optional", vec![t])
+    }
+
+    /// `typing.List[T]`
+    pub fn list_of(t: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.List", vec![t])
+    }
+
+    /// `typing.Sequence[T]`
+    pub fn sequence_of(t: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.Sequence", vec![t])
+    }
+
+    /// `typing.Dict[K, V]`
+    pub fn dict_of(k: TypeInfo, v: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.Dict", vec![k, v])
+    }
+
+    /// `typing.Set[T]`
+    pub fn set_of(t: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.Set", vec![t])
+    }
+
+    /// `typing.Tuple[T, ...]` with one type per element.
+    pub fn tuple_of(elements: Vec<TypeInfo>) -> Self {
+        TypeInfo::Builtin("typing.Tuple", elements)
+    }
+
+    /// `typing.Coroutine[typing.Any, typing.Any, T]`, the type of an `async fn` returning `T`.
+    pub fn coroutine_of(t: TypeInfo) -> Self {
+        TypeInfo::Builtin("typing.Coroutine", vec![TypeInfo::Any, TypeInfo::Any, t])
+    }
+
+    /// `typing.Union[T, ...]`
+    pub fn union_of(types: Vec<TypeInfo>) -> Self {
+        TypeInfo::Builtin("typing.Union", types)
+    }
+
+    /// Renders the annotation as it would appear in the stub of the module named `module`.
+    ///
+    /// Classes defined in `module` or in `builtins` are unqualified, all others are prefixed
+    /// with the name of their module.
+    pub fn to_annotation(&self, py: Python, module: &str) -> PyResult<String> {
+        self.render(py, module, &mut BTreeSet::new())
+    }
+
+    /// Like `to_annotation`, and collects the modules which the stub needs to import.
+    pub(crate) fn render(
+        &self,
+        py: Python,
+        module: &str,
+        imports: &mut BTreeSet<String>,
+    ) -> PyResult<String> {
+        Ok(match self {
+            TypeInfo::Any => {
+                imports.insert("typing".to_string());
+                "typing.Any".to_string()
+            }
+            TypeInfo::None => "None".to_string(),
+            TypeInfo::Builtin(name, args) => {
+                if let Some(dot) = name.rfind('.') {
+                    imports.insert(name[..dot].to_string());
+                }
+                if args.is_empty() {
+                    if *name == "typing.Tuple" {
+                        "typing.Tuple[()]".to_string()
+                    } else {
+                        name.to_string()
+                    }
+                } else {
+                    let args = args
+                        .iter()
+                        .map(|arg| arg.render(py, module, imports))
+                        .collect::<PyResult<Vec<_>>>()?;
+                    format!("{}[{}]", name, args.join(", "))
+                }
+            }
+            TypeInfo::Class(type_object) => render_type(type_object(py), module, imports)?,
+        })
+    }
+}
+
+/// Renders a Python class as an annotation; `object` is rendered as `typing.Any`.
+pub(crate) fn render_type(
+    ty: &PyType,
+    module: &str,
+    imports: &mut BTreeSet<String>,
+) -> PyResult<String> {
+    if is_object(ty) {
+        return TypeInfo::Any.render(ty.py(), module, imports);
+    }
+    let name = ty.name()?;
+    let type_module: &str = ty.getattr("__module__")?.extract()?;
+    if type_module == "builtins" || type_module == module {
+        Ok(name.to_string())
+    } else {
+        imports.insert(type_module.to_string());
+        Ok(format!("{}.{}", type_module, name))
+    }
+}
+
+/// Whether `ty` is `object`.
+pub(crate) fn is_object(ty: &PyType) -> bool {
+    ty.as_type_ptr() == PyAny::type_object_raw(ty.py())
+}
+
+// Manual implementation because higher-ranked `fn` pointers don't implement `Debug`.
+impl fmt::Debug for TypeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeInfo::Any => f.write_str("Any"),
+            TypeInfo::None => f.write_str("None"),
+            TypeInfo::Builtin(name, args) => {
+                f.debug_tuple("Builtin").field(name).field(args).finish()
+            }
+            TypeInfo::Class(_) => f.write_str("Class"),
+        }
+    }
+}
@@ -2,7 +2,9 @@
 use crate::conversion::{PyTryFrom, ToBorrowedObject};
 use crate::err::{self, PyDowncastError, PyErr, PyResult};
 use crate::gil;
+use crate::inspect::types::TypeInfo;
 use crate::pycell::{PyBorrowError, PyBorrowMutError, PyCell};
+use crate::pyclass::boolean_struct::{False, True};
 use crate::types::{PyDict, PyTuple};
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, IntoPyPointer, PyAny, PyClass, PyClassInitializer,
@@ -393,7 +395,10 @@ where
     /// # Panics
     /// Panics if the value is currently mutably borrowed. For a non-panicking variant, use
     /// [`try_borrow_mut`](#method.try_borrow_mut).
-    pub fn borrow_mut<'py>(&'py self, py: Python<'py>) -> PyRefMut<'py, T> {
+    pub fn borrow_mut<'py>(&'py self, py: Python<'py>) -> PyRefMut<'py, T>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.as_ref(py).borrow_mut()
     }
 
@@ -420,9 +425,40 @@ where
     pub fn try_borrow_mut<'py>(
         &'py self,
         py: Python<'py>,
-    ) -> Result<PyRefMut<'py, T>, PyBorrowMutError> {
+    ) -> Result<PyRefMut<'py, T>, PyBorrowMutError>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.as_ref(py).try_borrow_mut()
     }
+
+    /// Gets a shared reference to the value of a `#[pyclass(frozen)]` class.
+    ///
+    /// Frozen classes can't be mutated after they are created, so unlike [`borrow`](#method.borrow)
+    /// this does not need the GIL and does not touch the borrow flag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pyo3::prelude::*;
+    ///
+    /// #[pyclass(frozen)]
+    /// struct Point {
+    ///     x: i32,
+    /// }
+    ///
+    /// let point: Py<Point> = Python::with_gil(|py| Py::new(py, Point { x: 1 })).unwrap();
+    /// // No need to acquire the GIL to read the value.
+    /// assert_eq!(point.get().x, 1);
+    /// ```
+    pub fn get(&self) -> &T
+    where
+        T: PyClass<Frozen = True> + Sync,
+    {
+        // Safety: the object is kept alive by this `Py<T>`, and its value can't be mutated
+        // because the class is frozen.
+        unsafe { (*(self.as_ptr() as *const PyCell<T>)).get() }
+    }
 }
 
 impl<T> Py<T> {
@@ -820,6 +856,10 @@ where
                 .map(|val| Py::from_borrowed_ptr(ob.py(), val.as_ptr()))
         }
     }
+
+    fn type_input() -> TypeInfo {
+        <&'a T::AsRefTarget>::type_input()
+    }
 }
 
 /// Py<T> can be used as an error when T is an Error.
@@ -0,0 +1,279 @@
+use super::{InitializationError, INITIALIZING};
+use crate::{ffi, gil::GILPool, ToPyObject};
+use libc::wchar_t;
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::mem::MaybeUninit;
+use std::os::raw::c_int;
+use std::path::PathBuf;
+
+/// Initializes the Python interpreter with custom settings.
+///
+/// Settings which aren't given keep the defaults of the `python` executable, except that
+/// command line arguments are never parsed as interpreter options, and that Python's signal
+/// handlers aren't installed, like with
+/// [`prepare_freethreaded_python`](crate::prepare_freethreaded_python).
+///
+/// After [`initialize`](InterpreterBuilder::initialize) succeeds, the GIL is released and Python
+/// can be used with [`Python::with_gil`](crate::Python::with_gil).
+#[derive(Clone, Debug, Default)]
+pub struct InterpreterBuilder {
+    program_name: Option<OsString>,
+    home: Option<PathBuf>,
+    paths: Vec<PathBuf>,
+    isolated: bool,
+    site_import: Option<bool>,
+    utf8_mode: Option<bool>,
+    argv: Vec<OsString>,
+}
+
+impl InterpreterBuilder {
+    /// Creates a builder with the default settings.
+    pub fn new() -> Self {
+        InterpreterBuilder::default()
+    }
+
+    /// Sets the program name, which is used to find the Python installation and to set
+    /// `sys.executable`.
+    pub fn program_name(mut self, name: impl Into<OsString>) -> Self {
+        self.program_name = Some(name.into());
+        self
+    }
+
+    /// Sets the location of the standard library, like the `PYTHONHOME` environment variable.
+    pub fn home(mut self, home: impl Into<PathBuf>) -> Self {
+        self.home = Some(home.into());
+        self
+    }
+
+    /// Appends `path` to `sys.path` once the interpreter is initialized.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Enables isolated mode, like `python -I`: environment variables such as `PYTHONPATH` are
+    /// ignored and neither the user's site-packages directory nor the script's directory are
+    /// added to `sys.path`.
+    pub fn isolated(mut self, isolated: bool) -> Self {
+        self.isolated = isolated;
+        self
+    }
+
+    /// Sets whether the `site` module is imported on startup, adding site-packages directories
+    /// to `sys.path`. Disabling it has the effect of `python -S`.
+    pub fn site_import(mut self, site_import: bool) -> Self {
+        self.site_import = Some(site_import);
+        self
+    }
+
+    /// Enables or disables the [UTF-8 mode](https://www.python.org/dev/peps/pep-0540/), like
+    /// `python -X utf8`.
+    pub fn utf8_mode(mut self, utf8_mode: bool) -> Self {
+        self.utf8_mode = Some(utf8_mode);
+        self
+    }
+
+    /// Sets `sys.argv`. The arguments are passed to Python as they are, without being parsed as
+    /// interpreter options.
+    pub fn argv<I, S>(mut self, argv: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.argv = argv.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Initializes the interpreter with these settings.
+    ///
+    /// Fails if the interpreter is already initialized. Initialization which fails part way
+    /// leaves Python unusable for the rest of the process.
+    pub fn initialize(self) -> Result<(), InitializationError> {
+        let _guard = INITIALIZING.lock();
+        unsafe {
+            if ffi::Py_IsInitialized() != 0 {
+                return Err(InitializationError::AlreadyInitialized);
+            }
+            self.validate()?;
+            self.preinitialize()?;
+            let mut config = Config::new();
+            self.configure(&mut config)?;
+            check(ffi::Py_InitializeFromConfig(&config.0))?;
+            drop(config);
+
+            let result = self.extend_path();
+            // Release the GIL, like `prepare_freethreaded_python`.
+            ffi::PyEval_SaveThread();
+            result
+        }
+    }
+
+    /// Rejects settings which can't be passed to Python before anything is initialized, as
+    /// Python can't be pre-initialized a second time.
+    fn validate(&self) -> Result<(), InitializationError> {
+        let program_name = self
+            .program_name
+            .iter()
+            .map(|name| (name.as_os_str(), "program_name"));
+        let home = self.home.iter().map(|home| (home.as_os_str(), "home"));
+        let argv = self.argv.iter().map(|arg| (arg.as_os_str(), "argv"));
+        for (value, setting) in program_name.chain(home).chain(argv) {
+            encode(value, setting)?;
+        }
+        Ok(())
+    }
+
+    unsafe fn preinitialize(&self) -> Result<(), InitializationError> {
+        let mut preconfig = MaybeUninit::uninit();
+        ffi::PyPreConfig_InitPythonConfig(preconfig.as_mut_ptr());
+        let mut preconfig: ffi::PyPreConfig = preconfig.assume_init();
+        preconfig.parse_argv = 0;
+        if self.isolated {
+            preconfig.isolated = 1;
+            preconfig.use_environment = 0;
+        }
+        if let Some(utf8_mode) = self.utf8_mode {
+            preconfig.utf8_mode = utf8_mode as c_int;
+        }
+        check(ffi::Py_PreInitialize(&preconfig))
+    }
+
+    unsafe fn configure(&self, config: &mut Config) -> Result<(), InitializationError> {
+        let config: *mut ffi::PyConfig = &mut config.0;
+        (*config).parse_argv = 0;
+        (*config).install_signal_handlers = 0;
+        if self.isolated {
+            (*config).isolated = 1;
+            (*config).use_environment = 0;
+            (*config).user_site_directory = 0;
+        }
+        if let Some(site_import) = self.site_import {
+            (*config).site_import = site_import as c_int;
+        }
+        if let Some(program_name) = &self.program_name {
+            set_string(
+                config,
+                &mut (*config).program_name,
+                program_name,
+                "program_name",
+            )?;
+        }
+        if let Some(home) = &self.home {
+            set_string(config, &mut (*config).home, home.as_os_str(), "home")?;
+        }
+        if !self.argv.is_empty() {
+            set_argv(config, &self.argv)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the configured paths to `sys.path`; must be called with the GIL held.
+    unsafe fn extend_path(&self) -> Result<(), InitializationError> {
+        if self.paths.is_empty() {
+            return Ok(());
+        }
+        let pool = GILPool::new();
+        let py = pool.python();
+        py.import("sys")
+            .and_then(|sys| sys.getattr("path"))
+            .and_then(|path| {
+                for entry in &self.paths {
+                    path.call_method1("append", (entry.to_object(py),))?;
+                }
+                Ok(())
+            })
+            .map_err(|err| InitializationError::Failed {
+                function: None,
+                message: err.to_string(),
+            })
+    }
+}
+
+/// Owns a `PyConfig`, clearing it when dropped.
+struct Config(ffi::PyConfig);
+
+impl Config {
+    unsafe fn new() -> Self {
+        let mut config = MaybeUninit::uninit();
+        ffi::PyConfig_InitPythonConfig(config.as_mut_ptr());
+        Config(config.assume_init())
+    }
+}
+
+impl Drop for Config {
+    fn drop(&mut self) {
+        unsafe { ffi::PyConfig_Clear(&mut self.0) }
+    }
+}
+
+/// Converts a `PyStatus` into a `Result`.
+unsafe fn check(status: ffi::PyStatus) -> Result<(), InitializationError> {
+    if ffi::PyStatus_Exception(status) == 0 {
+        return Ok(());
+    }
+    if ffi::PyStatus_IsExit(status) != 0 {
+        return Err(InitializationError::Exit(status.exitcode));
+    }
+    let to_string = |ptr: *const std::os::raw::c_char| {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+        }
+    };
+    Err(InitializationError::Failed {
+        function: to_string(status.func),
+        message: to_string(status.err_msg).unwrap_or_else(|| "unknown error".to_owned()),
+    })
+}
+
+unsafe fn set_string(
+    config: *mut ffi::PyConfig,
+    field: *mut *mut wchar_t,
+    value: &OsStr,
+    setting: &'static str,
+) -> Result<(), InitializationError> {
+    let value = encode(value, setting)?;
+    #[cfg(not(windows))]
+    let status = ffi::PyConfig_SetBytesString(config, field, value.as_ptr());
+    #[cfg(windows)]
+    let status = ffi::PyConfig_SetString(config, field, value.as_ptr());
+    check(status)
+}
+
+unsafe fn set_argv(
+    config: *mut ffi::PyConfig,
+    argv: &[OsString],
+) -> Result<(), InitializationError> {
+    let argv = argv
+        .iter()
+        .map(|arg| encode(arg, "argv"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut pointers: Vec<_> = argv.iter().map(|arg| arg.as_ptr()).collect();
+    let argc = pointers.len() as ffi::Py_ssize_t;
+    #[cfg(not(windows))]
+    let status = ffi::PyConfig_SetBytesArgv(config, argc, pointers.as_mut_ptr());
+    #[cfg(windows)]
+    let status = ffi::PyConfig_SetArgv(config, argc, pointers.as_mut_ptr());
+    check(status)
+}
+
+/// Converts `value` to a nul-terminated string in the platform's encoding, which Python decodes
+/// with the locale encoding (or UTF-8 in UTF-8 mode).
+#[cfg(not(windows))]
+fn encode(value: &OsStr, setting: &'static str) -> Result<CString, InitializationError> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(value.as_bytes()).map_err(|_| InitializationError::NulByte { setting })
+}
+
+/// Converts `value` to a nul-terminated wide string.
+#[cfg(windows)]
+fn encode(value: &OsStr, setting: &'static str) -> Result<Vec<wchar_t>, InitializationError> {
+    use std::os::windows::ffi::OsStrExt;
+    let mut wide: Vec<wchar_t> = value.encode_wide().collect();
+    if wide.contains(&0) {
+        return Err(InitializationError::NulByte { setting });
+    }
+    wide.push(0);
+    Ok(wide)
+}
@@ -0,0 +1,42 @@
+use super::{InitializationError, INITIALIZING};
+use crate::ffi;
+use std::ffi::CString;
+
+/// The type of the module initialization functions generated by [`#[pymodule]`](crate::pymodule),
+/// which are named `PyInit_<module name>`.
+pub type ModuleInitializer = unsafe extern "C" fn() -> *mut ffi::PyObject;
+
+/// Registers `init` as the initialization function of the built-in module `name`, which Python
+/// calls when the module is first imported.
+///
+/// This must be called before the interpreter is initialized. It is usually easier to use
+/// [`append_to_inittab!`](crate::append_to_inittab), which finds the initialization function of
+/// a `#[pymodule]`.
+///
+/// # Safety
+///
+/// `init` must be a module initialization function following the conventions of the Python
+/// C API, such as the functions generated by `#[pymodule]`.
+pub unsafe fn append_to_inittab(
+    name: &str,
+    init: ModuleInitializer,
+) -> Result<(), InitializationError> {
+    let _guard = INITIALIZING.lock();
+    if ffi::Py_IsInitialized() != 0 {
+        return Err(InitializationError::AlreadyInitialized);
+    }
+    let name = CString::new(name).map_err(|_| InitializationError::NulByte { setting: "name" })?;
+    // Python doesn't copy the name, so it has to live for the rest of the process.
+    let name = name.into_raw();
+    // The function is only ever called by Python, for which it makes no difference whether it is
+    // `unsafe` on the Rust side.
+    let init: extern "C" fn() -> *mut ffi::PyObject = std::mem::transmute(init);
+    if ffi::PyImport_AppendInittab(name, Some(init)) != 0 {
+        drop(CString::from_raw(name));
+        return Err(InitializationError::Failed {
+            function: Some("PyImport_AppendInittab".to_owned()),
+            message: "out of memory".to_owned(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Configuring the embedded Python interpreter and running sub-interpreters.
+//!
+//! [`prepare_freethreaded_python`](crate::prepare_freethreaded_python) initializes Python with
+//! its default configuration, which is read from the environment and the command line of the
+//! process. Applications embedding Python which need control over that configuration can
+//! initialize it with an [`InterpreterBuilder`] instead, which uses the
+//! [PEP 587](https://www.python.org/dev/peps/pep-0587/) configuration API (Python 3.8 to 3.11):
+//!
+//! ```rust,no_run
+//! use pyo3::interpreter::InterpreterBuilder;
+//! use pyo3::prelude::*;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! InterpreterBuilder::new()
+//!     .program_name("scripting-host")
+//!     .isolated(true)
+//!     .site_import(false)
+//!     .path("/opt/scripting-host/scripts")
+//!     .argv(vec!["scripting-host", "--verbose"])
+//!     .initialize()?;
+//!
+//! Python::with_gil(|py| py.run("import sys; print(sys.argv)", None, None))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A [`#[pymodule]`](crate::pymodule) can be registered as a built-in module with
+//! [`append_to_inittab!`](crate::append_to_inittab) before the interpreter is initialized, by
+//! whichever means, which makes it importable by name from any Python code:
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//!
+//! #[pymodule]
+//! fn host(_py: Python, m: &PyModule) -> PyResult<()> {
+//!     m.add("version", "1.0")
+//! }
+//!
+//! # #[allow(clippy::needless_doctest_main)]
+//! fn main() {
+//!     pyo3::append_to_inittab!(host).unwrap();
+//!     pyo3::prepare_freethreaded_python();
+//!     Python::with_gil(|py| py.run("import host; assert host.version == '1.0'", None, None))
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! Once initialized, Python code can be isolated from the rest of the application by running it
+//! in a [`SubInterpreter`].
+
+use parking_lot::{const_mutex, Mutex};
+
+#[cfg(all(Py_3_8, not(Py_3_12), not(Py_LIMITED_API)))]
+mod config;
+#[cfg(not(PyPy))]
+mod inittab;
+#[cfg(not(PyPy))]
+mod subinterpreter;
+
+#[cfg(all(Py_3_8, not(Py_3_12), not(Py_LIMITED_API)))]
+pub use self::config::InterpreterBuilder;
+#[cfg(not(PyPy))]
+pub use self::inittab::{append_to_inittab, ModuleInitializer};
+#[cfg(not(PyPy))]
+pub use self::subinterpreter::SubInterpreter;
+
+/// Serializes changes to the interpreter's state before initialization.
+static INITIALIZING: Mutex<()> = const_mutex(());
+
+/// The reason why initializing the interpreter, or preparing to do so, failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitializationError {
+    /// The interpreter was already initialized, by a builder or any other means.
+    AlreadyInitialized,
+    /// A setting contained a nul character, which can't be passed to Python.
+    NulByte {
+        /// The builder method or argument the setting was passed to.
+        setting: &'static str,
+    },
+    /// Python failed to initialize.
+    Failed {
+        /// The C function which reported the error, if known.
+        function: Option<String>,
+        /// The error message.
+        message: String,
+    },
+    /// Python asked for the process to exit with the given code instead of initializing.
+    Exit(i32),
+}
+
+impl std::fmt::Display for InitializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InitializationError::AlreadyInitialized => {
+                write!(f, "the Python interpreter is already initialized")
+            }
+            InitializationError::NulByte { setting } => {
+                write!(f, "the `{}` setting contains a nul character", setting)
+            }
+            InitializationError::Failed {
+                function: Some(function),
+                message,
+            } => write!(f, "failed to initialize Python: {}: {}", function, message),
+            InitializationError::Failed {
+                function: None,
+                message,
+            } => write!(f, "failed to initialize Python: {}", message),
+            InitializationError::Exit(code) => {
+                write!(f, "Python initialization exited with code {}", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InitializationError {}
@@ -0,0 +1,158 @@
+use crate::exceptions::PyRuntimeError;
+use crate::gil::{self, GILPool};
+use crate::internal_tricks::Unsendable;
+use crate::{ffi, PyResult, Python};
+use std::ptr::NonNull;
+
+/// An isolated Python interpreter running alongside the main interpreter.
+///
+/// A sub-interpreter has its own `sys.modules`, `builtins` and `__main__`, so code running in it
+/// doesn't see modules imported or globals set by the main interpreter, and vice versa. It is
+/// created with [`Py_NewInterpreter`](https://docs.python.org/3/c-api/init.html#c.Py_NewInterpreter)
+/// and finalized when dropped.
+///
+/// ```rust
+/// use pyo3::interpreter::SubInterpreter;
+/// use pyo3::prelude::*;
+///
+/// Python::with_gil(|py| -> PyResult<()> {
+///     py.run("secret = 42", None, None)?;
+///
+///     let plugin = SubInterpreter::new(py)?;
+///     // Safety: the closure doesn't use objects of the main interpreter
+///     let found = unsafe {
+///         plugin.run(py, |sub| -> PyResult<bool> {
+///             sub.eval("'secret' in globals()", None, None)?.extract()
+///         })
+///     }?;
+///     assert!(!found);
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Limitations
+///
+/// Classes defined with [`#[pyclass]`](crate::pyclass) and modules defined with
+/// [`#[pymodule]`](crate::pymodule) keep their state in Rust statics shared by all
+/// interpreters, so they refuse to load in a sub-interpreter: creating an instance of a class or
+/// adding it to a module raises `RuntimeError`, and importing a module raises `ImportError`.
+/// Only infallible uses of a class, like [`Python::get_type`], panic. Modules using
+/// `#[pyo3(multi_phase)]` are created anew for each interpreter instead, and can be imported as
+/// long as they don't add classes; see the [`module_state`](crate::module_state) module.
+///
+/// Exception types, like those of [`create_exception!`](crate::create_exception), are created
+/// anew for each interpreter. Values in other [`GILOnceCell`](crate::once_cell::GILOnceCell)s
+/// are shared, and must not be Python objects when used from a sub-interpreter, unless they are
+/// cached with [`get_or_init_in_interpreter`](crate::once_cell::GILOnceCell::get_or_init_in_interpreter).
+///
+/// All interpreters share one GIL, so a sub-interpreter isolates the state of Python code but
+/// doesn't let it run in parallel. It also doesn't protect the process: untrusted code can still
+/// use `ctypes`, `os` and anything else the standard library offers.
+pub struct SubInterpreter {
+    tstate: NonNull<ffi::PyThreadState>,
+    /// A dict of the sub-interpreter, in which pyo3 caches the Python objects it would otherwise
+    /// keep in statics, see `GILOnceCell::get_or_init_in_interpreter`.
+    cache: NonNull<ffi::PyObject>,
+    _not_send: Unsendable,
+}
+
+impl SubInterpreter {
+    /// Creates a sub-interpreter, leaving the current interpreter active.
+    pub fn new(_py: Python) -> PyResult<Self> {
+        unsafe {
+            let previous = ffi::PyThreadState_Get();
+            // makes the thread state of the new interpreter current
+            let tstate = ffi::Py_NewInterpreter();
+            let created = NonNull::new(tstate).map(|tstate| {
+                let cache = ffi::PyDict_New();
+                (tstate, NonNull::new(cache))
+            });
+            ffi::PyThreadState_Swap(previous);
+            match created {
+                Some((tstate, Some(cache))) => Ok(SubInterpreter {
+                    tstate,
+                    cache,
+                    _not_send: Unsendable::default(),
+                }),
+                Some((tstate, None)) => {
+                    end_interpreter(tstate, None);
+                    Err(PyRuntimeError::new_err(
+                        "failed to create the cache of a sub-interpreter",
+                    ))
+                }
+                None => Err(PyRuntimeError::new_err(
+                    "failed to create a sub-interpreter",
+                )),
+            }
+        }
+    }
+
+    /// Runs `f` in the sub-interpreter, switching back to the current interpreter afterwards,
+    /// also if `f` panics.
+    ///
+    /// `f` receives a GIL token for the sub-interpreter. Like with
+    /// [`Python::allow_threads`], `f` must be `Send`, so that references to objects of the
+    /// current interpreter can't be used inside it.
+    ///
+    /// Threads spawned by `f` which acquire the GIL use the main interpreter, not this one.
+    ///
+    /// # Safety
+    ///
+    /// Objects never move between interpreters, which `Send` alone can't ensure since
+    /// [`Py`](crate::Py) and [`PyObject`](crate::PyObject) are `Send`:
+    ///
+    /// - `f` must not use `Py`s of the current interpreter, whether captured or reached
+    ///   through other values such as statics.
+    /// - `Py`s created inside `f` must not outlive it, whether returned or stored elsewhere.
+    pub unsafe fn run<F, R>(&self, _py: Python, f: F) -> R
+    where
+        F: for<'sub> FnOnce(Python<'sub>) -> R + Send,
+    {
+        struct Restore {
+            tstate: *mut ffi::PyThreadState,
+            cache: *mut ffi::PyObject,
+        }
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                gil::leave_sub_interpreter(self.cache);
+                unsafe { ffi::PyThreadState_Swap(self.tstate) };
+            }
+        }
+
+        let tstate = ffi::PyThreadState_Swap(self.tstate.as_ptr());
+        let _restore = Restore {
+            tstate,
+            cache: gil::enter_sub_interpreter(self.cache),
+        };
+        // Declared after `_restore`, so that objects owned by the sub-interpreter are released
+        // before switching back.
+        let pool = GILPool::new();
+        f(pool.python())
+    }
+}
+
+/// Finalizes the sub-interpreter.
+///
+/// This aborts the process if Python threads started in the sub-interpreter are still running.
+impl Drop for SubInterpreter {
+    fn drop(&mut self) {
+        Python::with_gil(|_py| unsafe { end_interpreter(self.tstate, Some(self.cache)) });
+    }
+}
+
+/// Releases the cache of the sub-interpreter and finalizes it, leaving the current thread state
+/// active.
+unsafe fn end_interpreter(
+    tstate: NonNull<ffi::PyThreadState>,
+    cache: Option<NonNull<ffi::PyObject>>,
+) {
+    let previous = ffi::PyThreadState_Swap(tstate.as_ptr());
+    if let Some(cache) = cache {
+        ffi::Py_DECREF(cache.as_ptr());
+    }
+    // leaves no thread state current
+    ffi::Py_EndInterpreter(tstate.as_ptr());
+    ffi::PyThreadState_Swap(previous);
+}
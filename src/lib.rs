@@ -80,6 +80,7 @@
 //! - [`hashbrown`]: Enables conversions between Python objects and [hashbrown]'s [`HashMap`] and
 //! [`HashSet`] types.
 //! - [`indexmap`]: Enables conversions between Python dictionary and [indexmap]'s [`IndexMap`].
+//! - [`log`]: Provides a [log] logger which forwards records to Python's `logging` module.
 //! - [`num-bigint`]: Enables conversions between Python objects and [num-bigint]'s [`BigInt`] and
 //! [`BigUint`] types.
 //! - [`num-complex`]: Enables conversions between Python objects and [num-complex]'s [`Complex`]
@@ -256,6 +257,7 @@
 //! [`Serialize`]: https://docs.rs/serde/latest/serde/trait.Serialize.html
 //! [`hashbrown`]: ./hashbrown/index.html
 //! [`indexmap`]: <./indexmap/index.html>
+//! [`log`]: ./logging/index.html
 //! [`maturin`]: https://github.com/PyO3/maturin "Build and publish crates with pyo3, rust-cpython and cffi bindings as well as rust binaries as python packages"
 //! [`num-bigint`]: ./num_bigint/index.html
 //! [`num-complex`]: ./num_complex/index.html
@@ -267,6 +269,7 @@
 //! [global interpreter lock]: https://docs.python.org/3/glossary.html#term-global-interpreter-lock
 //! [hashbrown]: https://docs.rs/hashbrown
 //! [indexmap]: https://docs.rs/indexmap
+//! [log]: https://docs.rs/log
 //! [manual_builds]: https://pyo3.rs/latest/building_and_distribution.html#manual-builds "Manual builds - Building and Distribution - PyO3 user guide"
 //! [num-bigint]: https://docs.rs/num-bigint
 //! [num-complex]: https://docs.rs/num-complex
@@ -283,7 +286,7 @@ pub use crate::conversion::{
     AsPyPointer, FromPyObject, FromPyPointer, IntoPy, IntoPyPointer, PyTryFrom, PyTryInto,
     ToBorrowedObject, ToPyObject,
 };
-pub use crate::err::{PyDowncastError, PyErr, PyErrArguments, PyResult};
+pub use crate::err::{PyDowncastError, PyErr, PyErrArguments, PyResult, TracebackFrame};
 #[cfg(not(PyPy))]
 #[cfg_attr(docsrs, doc(cfg(not(PyPy))))]
 pub use crate::gil::{prepare_freethreaded_python, with_embedded_python_interpreter};
@@ -308,15 +311,23 @@ pub use {
 #[doc(hidden)]
 pub use inventory; // Re-exported for `#[pyclass]` and `#[pymethods]` with `multiple-pymethods`.
 
+// Allows the macros to be used inside this crate, e.g. for `coroutine::Coroutine`.
+#[cfg(feature = "macros")]
+extern crate self as pyo3;
+
 #[macro_use]
 mod internal_tricks;
 
+#[cfg(feature = "macros")]
+pub mod asyncio;
 pub mod buffer;
 #[doc(hidden)]
 pub mod callback;
 pub mod class;
 pub mod conversion;
 mod conversions;
+#[cfg(feature = "macros")]
+pub mod coroutine;
 #[macro_use]
 #[doc(hidden)]
 pub mod derive_utils;
@@ -326,8 +337,16 @@ pub mod ffi;
 mod gil;
 #[doc(hidden)]
 pub mod impl_;
+#[cfg(feature = "macros")]
+pub mod importer;
+pub mod inspect;
 mod instance;
+#[cfg(not(PyPy))]
+pub mod interpreter;
+#[cfg(feature = "log")]
+pub mod logging;
 pub mod marshal;
+pub mod module_state;
 pub mod once_cell;
 pub mod panic;
 pub mod prelude;
@@ -336,6 +355,8 @@ pub mod pyclass;
 pub mod pyclass_init;
 pub mod pyclass_slots;
 mod python;
+#[cfg(feature = "macros")]
+pub mod stdio;
 pub mod type_object;
 pub mod types;
 
@@ -353,7 +374,9 @@ pub mod proc_macro {
 
 #[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
 #[cfg(feature = "macros")]
-pub use pyo3_macros::{pyclass, pyfunction, pymethods, pymodule, pyproto, FromPyObject};
+pub use pyo3_macros::{
+    pyclass, pyfunction, pymethods, pymodule, pyproto, FromPyObject, IntoPyObject, PyException,
+};
 
 #[macro_use]
 mod macros;
@@ -0,0 +1,391 @@
+#![cfg(feature = "log")]
+#![cfg_attr(docsrs, doc(cfg(feature = "log")))]
+
+//! A [log](https://docs.rs/log) implementation which forwards records to Python's
+//! [`logging`](https://docs.python.org/3/library/logging.html) module.
+//!
+//! # Setup
+//!
+//! To use this feature, add this to your **`Cargo.toml`**:
+//!
+//! ```toml
+//! [dependencies]
+//! log = "0.4"
+// workaround for `extended_key_value_attributes`: https://github.com/rust-lang/rust/issues/82768#issuecomment-803935643
+#![cfg_attr(docsrs, cfg_attr(docsrs, doc = concat!("pyo3 = { version = \"", env!("CARGO_PKG_VERSION"),  "\", features = [\"log\"] }")))]
+#![cfg_attr(not(docsrs), doc = "pyo3 = { version = \"*\", features = [\"log\"] }")]
+//! ```
+//!
+//! # Mapping
+//!
+//! The target of a record selects the Python logger, with `::` replaced by `.`, so a record logged
+//! from the Rust module `my_ext::io` goes to `logging.getLogger("my_ext.io")`. Rust levels map to
+//! Python levels as follows:
+//!
+//! | Rust           | Python             |
+//! |----------------|--------------------|
+//! | `Level::Error` | `ERROR` (40)       |
+//! | `Level::Warn`  | `WARNING` (30)     |
+//! | `Level::Info`  | `INFO` (20)        |
+//! | `Level::Debug` | `DEBUG` (10)       |
+//! | `Level::Trace` | `5`, below `DEBUG` |
+//!
+//! # Caching
+//!
+//! The effective level of each Python logger is looked up once and cached, so records which the
+//! Python logger would discard are dropped without acquiring the GIL. If Python's logging
+//! configuration changes after records were logged, call [`ResetHandle::reset`] to clear the
+//! cache.
+//!
+//! # Threads
+//!
+//! Records which pass the filters are emitted right away, acquiring the GIL if the logging thread
+//! doesn't hold it. Records logged before Python is initialized, or inside a
+//! [sub-interpreter](crate::interpreter::SubInterpreter), are queued instead, and emitted before
+//! the next record logged from the main interpreter, or by calling `log::logger().flush()`.
+//! At most 1024 records are queued; if older records had to be dropped, a warning on the
+//! `pyo3.logging` logger reports how many.
+//!
+//! # Example
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//!
+//! #[pyfunction]
+//! fn compute() -> u64 {
+//!     log::info!("computing");
+//!     42
+//! }
+//!
+//! #[pymodule]
+//! fn my_extension(_py: Python, m: &PyModule) -> PyResult<()> {
+//!     // forward everything from info upwards; Python decides what is actually emitted
+//!     if pyo3::logging::Logger::new()
+//!         .filter(log::LevelFilter::Info)
+//!         .install()
+//!         .is_err()
+//!     {
+//!         // the application, or another extension, already set up logging
+//!     }
+//!     m.add_function(wrap_pyfunction!(compute, m)?)?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::gil::{self, GILPool};
+use crate::types::{PyModule, PyTuple};
+use crate::{ffi, IntoPy, PyAny, PyObject, PyResult, Python};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// The most records kept while Python is unavailable; older records are dropped beyond this.
+const MAX_PENDING: usize = 1024;
+
+/// Converts a Rust log level to the corresponding Python logging level.
+pub fn python_level(level: Level) -> u32 {
+    match level {
+        Level::Error => 40,
+        Level::Warn => 30,
+        Level::Info => 20,
+        Level::Debug => 10,
+        Level::Trace => 5,
+    }
+}
+
+/// Converts a Rust log target to the name of the Python logger it is sent to.
+pub fn logger_name(target: &str) -> String {
+    target.replace("::", ".")
+}
+
+/// A Python logger along with its cached effective level.
+struct CachedLogger {
+    logger: PyObject,
+    effective_level: u32,
+}
+
+/// A record which could not be emitted yet because Python was unavailable.
+struct PendingRecord {
+    target: String,
+    level: Level,
+    message: String,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+impl PendingRecord {
+    fn new(record: &Record) -> Self {
+        PendingRecord {
+            target: record.target().to_owned(),
+            level: record.level(),
+            message: record.args().to_string(),
+            file: record.file().map(ToOwned::to_owned),
+            line: record.line(),
+        }
+    }
+}
+
+/// The records queued while Python was unavailable.
+#[derive(Default)]
+struct Pending {
+    records: VecDeque<PendingRecord>,
+    /// How many records were dropped because the queue was full.
+    dropped: usize,
+}
+
+/// State shared between an installed [`Logger`] and its [`ResetHandle`].
+#[derive(Default)]
+struct Cache {
+    loggers: RwLock<HashMap<String, CachedLogger>>,
+}
+
+/// Forwards [`log`] records to Python's `logging` module.
+///
+/// Create it with [`Logger::new`], configure which records are forwarded with [`Logger::filter`]
+/// and [`Logger::filter_target`], then make it the global logger with [`Logger::install`].
+pub struct Logger {
+    top_filter: LevelFilter,
+    filters: Vec<(String, LevelFilter)>,
+    cache: Arc<Cache>,
+    pending: Mutex<Pending>,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::new()
+    }
+}
+
+impl Logger {
+    /// Creates a logger which forwards records of all levels, leaving filtering to Python.
+    pub fn new() -> Self {
+        Logger {
+            top_filter: LevelFilter::Trace,
+            filters: Vec::new(),
+            cache: Arc::default(),
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Sets the most verbose level forwarded for targets without a more specific filter.
+    pub fn filter(mut self, filter: LevelFilter) -> Self {
+        self.top_filter = filter;
+        self
+    }
+
+    /// Sets the most verbose level forwarded for `target` and the targets nested below it.
+    ///
+    /// The most specific filter applies, so `filter_target("my_ext::io", LevelFilter::Debug)`
+    /// takes precedence over `filter_target("my_ext", LevelFilter::Warn)`.
+    pub fn filter_target(mut self, target: impl Into<String>, filter: LevelFilter) -> Self {
+        let target = target.into();
+        self.filters.retain(|(existing, _)| *existing != target);
+        self.filters.push((target, filter));
+        // longest (most specific) targets first
+        self.filters
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        self
+    }
+
+    /// Returns a handle which can clear the cached Python logger levels.
+    pub fn reset_handle(&self) -> ResetHandle {
+        ResetHandle {
+            cache: self.cache.clone(),
+        }
+    }
+
+    /// Installs this logger as the global [`log`] logger.
+    ///
+    /// This fails if a logger was already installed, by this function or any other.
+    pub fn install(self) -> Result<ResetHandle, SetLoggerError> {
+        let handle = self.reset_handle();
+        let max_level = self
+            .filters
+            .iter()
+            .map(|(_, filter)| *filter)
+            .fold(self.top_filter, std::cmp::max);
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(handle)
+    }
+
+    fn filter_for(&self, target: &str) -> LevelFilter {
+        self.filters
+            .iter()
+            .find(|(prefix, _)| {
+                target.starts_with(prefix.as_str())
+                    && (target.len() == prefix.len() || target[prefix.len()..].starts_with("::"))
+            })
+            .map_or(self.top_filter, |(_, filter)| *filter)
+    }
+
+    /// Emits `record` to its Python logger, looking up and caching the logger on first use.
+    fn emit(&self, py: Python, record: &PendingRecord) -> PyResult<()> {
+        let level = python_level(record.level);
+        let cached = self
+            .cache
+            .loggers
+            .read()
+            .get(&record.target)
+            .map(|cached| (cached.logger.clone_ref(py), cached.effective_level));
+        let (logger, effective_level) = match cached {
+            Some(cached) => cached,
+            None => self.lookup(py, &record.target)?,
+        };
+        if level < effective_level {
+            return Ok(());
+        }
+        let logger = logger.as_ref(py);
+        let py_record = logger.call_method1(
+            "makeRecord",
+            (
+                logger.getattr("name")?,
+                level,
+                record.file.as_deref().unwrap_or("<unknown>"),
+                record.line.unwrap_or(0),
+                &record.message,
+                PyTuple::empty(py),
+                py.None(),
+            ),
+        )?;
+        logger.call_method1("handle", (py_record,))?;
+        Ok(())
+    }
+
+    /// Fetches the Python logger for `target` and caches it with its effective level.
+    fn lookup(&self, py: Python, target: &str) -> PyResult<(PyObject, u32)> {
+        let logger: &PyAny =
+            PyModule::import(py, "logging")?.call_method1("getLogger", (logger_name(target),))?;
+        let effective_level: u32 = logger.call_method0("getEffectiveLevel")?.extract()?;
+        self.cache.loggers.write().insert(
+            target.to_owned(),
+            CachedLogger {
+                logger: logger.into_py(py),
+                effective_level,
+            },
+        );
+        Ok((logger.into_py(py), effective_level))
+    }
+
+    /// Emits the records queued while Python was unavailable.
+    fn emit_pending(&self, py: Python) {
+        let pending = std::mem::take(&mut *self.pending.lock());
+        if pending.dropped > 0 {
+            let warning = PendingRecord {
+                target: "pyo3::logging".to_owned(),
+                level: Level::Warn,
+                message: format!(
+                    "{} log records were dropped while Python was unavailable",
+                    pending.dropped
+                ),
+                file: None,
+                line: None,
+            };
+            self.report(py, self.emit(py, &warning));
+        }
+        for record in &pending.records {
+            self.report(py, self.emit(py, record));
+        }
+    }
+
+    /// Emits the queued records followed by `record`.
+    fn emit_now(&self, py: Python, record: &PendingRecord) {
+        self.emit_pending(py);
+        self.report(py, self.emit(py, record));
+    }
+
+    fn report(&self, py: Python, result: PyResult<()>) {
+        if let Err(err) = result {
+            err.print(py);
+        }
+    }
+
+    fn enqueue(&self, record: PendingRecord) {
+        let mut pending = self.pending.lock();
+        if pending.records.len() == MAX_PENDING {
+            pending.records.pop_front();
+            pending.dropped += 1;
+        }
+        pending.records.push_back(record);
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.level() > self.filter_for(metadata.target()) {
+            return false;
+        }
+        match self.cache.loggers.read().get(metadata.target()) {
+            Some(cached) => python_level(metadata.level()) >= cached.effective_level,
+            // not known yet; Python has to be asked
+            None => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let record = PendingRecord::new(record);
+        // the cached loggers belong to the main interpreter
+        if unsafe { ffi::Py_IsInitialized() } == 0 || gil::entered_sub_interpreter() {
+            self.enqueue(record);
+        } else if gil::gil_is_acquired() {
+            if gil::in_sub_interpreter() {
+                self.enqueue(record);
+            } else {
+                // releases the objects created while emitting
+                let pool = unsafe { GILPool::new() };
+                self.emit_now(pool.python(), &record);
+            }
+        } else {
+            Python::with_gil(|py| self.emit_now(py, &record));
+        }
+    }
+
+    fn flush(&self) {
+        let empty = {
+            let pending = self.pending.lock();
+            pending.records.is_empty() && pending.dropped == 0
+        };
+        if empty || unsafe { ffi::Py_IsInitialized() } == 0 || gil::entered_sub_interpreter() {
+            return;
+        }
+        if gil::gil_is_acquired() {
+            if !gil::in_sub_interpreter() {
+                let pool = unsafe { GILPool::new() };
+                self.emit_pending(pool.python());
+            }
+        } else {
+            Python::with_gil(|py| self.emit_pending(py));
+        }
+    }
+}
+
+/// Clears the cached Python loggers of an installed [`Logger`].
+///
+/// Returned by [`Logger::install`] and [`Logger::reset_handle`].
+#[derive(Clone)]
+pub struct ResetHandle {
+    cache: Arc<Cache>,
+}
+
+impl ResetHandle {
+    /// Forgets the cached Python loggers and levels, so that changes to Python's logging
+    /// configuration are picked up by the next record.
+    pub fn reset(&self) {
+        self.cache.loggers.write().clear();
+    }
+}
+
+/// Installs a [`Logger`] forwarding all records to Python as the global [`log`] logger.
+///
+/// # Panics
+///
+/// Panics if a logger was already installed.
+pub fn init() -> ResetHandle {
+    Logger::new()
+        .install()
+        .expect("a global logger was already installed")
+}
@@ -23,11 +23,38 @@ macro_rules! wrap_pyfunction {
 macro_rules! wrap_pymodule {
     ($module_name:ident) => {{
         $crate::paste::expr! {
-            &|py| unsafe { $crate::PyObject::from_owned_ptr(py, [<PyInit_ $module_name>]()) }
+            &|py| unsafe {
+                $crate::derive_utils::module_from_init(py, [<PyInit_ $module_name>]())
+                    .expect("failed to wrap pymodule")
+            }
         }
     }};
 }
 
+/// Registers a [`#[pymodule]`](crate::pymodule) as a built-in module of the embedded
+/// interpreter, so that Python code can import it by name.
+///
+/// Like [`wrap_pymodule!`], this takes the name of the module in Python, which differs from the
+/// name of the Rust function if it has a `#[pyo3(name = "...")]` attribute. It must be used
+/// before the interpreter is initialized, and returns a
+/// `Result<(), `[`InitializationError`](crate::interpreter::InitializationError)`>`.
+///
+/// See the [`interpreter`](crate::interpreter) module for an example.
+#[macro_export]
+#[cfg(not(PyPy))]
+macro_rules! append_to_inittab {
+    ($module_name:ident) => {
+        $crate::paste::expr! {
+            unsafe {
+                $crate::interpreter::append_to_inittab(
+                    ::std::stringify!($module_name),
+                    [<PyInit_ $module_name>],
+                )
+            }
+        }
+    };
+}
+
 /// A convenient macro to execute a Python code snippet, with some local variables set.
 ///
 /// # Panics
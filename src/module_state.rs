@@ -0,0 +1,213 @@
+//! Per-module state for modules using multi-phase initialization.
+//!
+//! A [`#[pymodule]`](crate::pymodule) with the `#[pyo3(multi_phase)]` option is initialized as
+//! described by [PEP 489](https://www.python.org/dev/peps/pep-0489/): each import creates a new
+//! module object, for example in each sub-interpreter or after the module was removed from
+//! `sys.modules`. Instead of Rust statics, which all these modules would share, such a module
+//! can keep its data in a state object, which is stored with
+//! [`PyModule::set_state`](crate::types::PyModule::set_state) and retrieved with
+//! [`PyModule::state`](crate::types::PyModule::state). Functions reach the module, and so its
+//! state, with `#[pyo3(pass_module)]`:
+//!
+//! ```rust
+//! use pyo3::class::gc::{PyTraverseError, PyVisit};
+//! use pyo3::module_state::ModuleState;
+//! use pyo3::prelude::*;
+//! use pyo3::types::PyDict;
+//! use pyo3::wrap_pyfunction;
+//!
+//! struct Registry {
+//!     plugins: Py<PyDict>,
+//! }
+//!
+//! impl ModuleState for Registry {
+//!     fn traverse(&self, visit: PyVisit) -> Result<(), PyTraverseError> {
+//!         visit.call(&self.plugins)
+//!     }
+//! }
+//!
+//! #[pyfunction]
+//! #[pyo3(pass_module)]
+//! fn register(module: &PyModule, name: &str, plugin: PyObject) -> PyResult<()> {
+//!     module.state::<Registry>()?.plugins.as_ref(module.py()).set_item(name, plugin)
+//! }
+//!
+//! #[pymodule]
+//! #[pyo3(multi_phase)]
+//! fn plugins(py: Python, m: &PyModule) -> PyResult<()> {
+//!     m.set_state(Registry {
+//!         plugins: PyDict::new(py).into(),
+//!     })?;
+//!     m.add_function(wrap_pyfunction!(register, m)?)
+//! }
+//! #
+//! # Python::with_gil(|py| {
+//! #     let m = pyo3::wrap_pymodule!(plugins)(py);
+//! #     pyo3::py_run!(py, m, "m.register('a', 1)");
+//! #     let m: &PyModule = m.extract(py).unwrap();
+//! #     assert_eq!(m.state::<Registry>().unwrap().plugins.as_ref(py).len(), 1);
+//! # });
+//! ```
+//!
+//! The state is dropped when the module object is freed.
+
+use crate::class::gc::{PyTraverseError, PyVisit};
+use crate::exceptions::{PyRuntimeError, PyTypeError};
+use crate::panic::PanicException;
+use crate::{ffi, AsPyPointer, GILPool, PyResult, Python};
+use std::any::Any;
+use std::os::raw::{c_int, c_void};
+use std::panic::{AssertUnwindSafe, UnwindSafe};
+
+/// The state of a module using multi-phase initialization.
+///
+/// Python objects held by the state should be reported to the garbage collector by
+/// [`traverse`](ModuleState::traverse), like in
+/// [`PyGCProtocol::__traverse__`](crate::class::gc::PyGCProtocol::__traverse__). The state is
+/// only ever accessed by shared reference, so it needs interior mutability to change, and to
+/// release objects in [`clear`](ModuleState::clear).
+///
+/// A panic in `traverse` aborts the process, as the garbage collector can't handle errors.
+/// Panics in `clear` and in the `Drop` implementation of the state are reported to
+/// `sys.unraisablehook`.
+pub trait ModuleState: Send + 'static {
+    /// Visits the Python objects held by the state.
+    fn traverse(&self, visit: PyVisit) -> Result<(), PyTraverseError> {
+        let _ = visit;
+        Ok(())
+    }
+
+    /// Releases Python objects held by the state, to break reference cycles through the module.
+    fn clear(&self) {}
+}
+
+/// A [`ModuleState`] whose type can be checked at runtime.
+trait StoredState: ModuleState {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: ModuleState> StoredState for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type Stored = Box<dyn StoredState>;
+
+/// The size of the state allocated by Python: a thin pointer to the boxed state, which is null
+/// until the state is set.
+pub(crate) const STATE_SIZE: ffi::Py_ssize_t = std::mem::size_of::<*mut Stored>() as _;
+
+/// Returns the slot holding the state of `module`, if it is a multi-phase PyO3 module.
+unsafe fn state_slot(module: *mut ffi::PyObject) -> Option<*mut *mut Stored> {
+    let def = ffi::PyModule_GetDef(module);
+    // Modules of other extensions can have state of their own, which must not be touched.
+    if def.is_null() || (*def).m_free.map(|f| f as usize) != Some(free as ffi::freefunc as usize) {
+        return None;
+    }
+    let state = ffi::PyModule_GetState(module);
+    if state.is_null() {
+        None
+    } else {
+        Some(state as *mut *mut Stored)
+    }
+}
+
+fn no_state_error(py: Python, module: *mut ffi::PyObject) -> crate::PyErr {
+    let module: &crate::types::PyModule = unsafe { py.from_borrowed_ptr(module) };
+    PyTypeError::new_err(format!(
+        "module '{}' can't have state, it must be a #[pymodule] with #[pyo3(multi_phase)]",
+        module.name().unwrap_or("<unknown>")
+    ))
+}
+
+pub(crate) fn set_state<T: ModuleState>(
+    py: Python,
+    module: &impl AsPyPointer,
+    state: T,
+) -> PyResult<()> {
+    let module = module.as_ptr();
+    let slot = unsafe { state_slot(module) }.ok_or_else(|| no_state_error(py, module))?;
+    unsafe {
+        if !(*slot).is_null() {
+            return Err(PyRuntimeError::new_err("the module state was already set"));
+        }
+        let stored: Stored = Box::new(state);
+        *slot = Box::into_raw(Box::new(stored));
+    }
+    Ok(())
+}
+
+pub(crate) fn get_state<'a, T: ModuleState>(
+    py: Python,
+    module: &'a impl AsPyPointer,
+) -> PyResult<&'a T> {
+    let module = module.as_ptr();
+    let slot = unsafe { state_slot(module) }.ok_or_else(|| no_state_error(py, module))?;
+    // The state lives as long as the module, which outlives the reference to it.
+    let stored = unsafe { (*slot).as_ref() }
+        .ok_or_else(|| PyRuntimeError::new_err("the module state was not set yet"))?;
+    stored.as_any().downcast_ref().ok_or_else(|| {
+        PyTypeError::new_err(format!(
+            "the module state is not a `{}`",
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+/// Runs `f`, reporting a panic to `sys.unraisablehook` as there is no caller to raise it to.
+unsafe fn catch_unraisable(py: Python, context: *mut ffi::PyObject, f: impl FnOnce() + UnwindSafe) {
+    if let Err(payload) = crate::panic::catch_unwind(f) {
+        PanicException::from_panic_payload(py, payload).restore(py);
+        ffi::PyErr_WriteUnraisable(context);
+    }
+}
+
+/// `m_traverse` of multi-phase modules.
+pub(crate) unsafe extern "C" fn traverse(
+    module: *mut ffi::PyObject,
+    visit: ffi::visitproc,
+    arg: *mut c_void,
+) -> c_int {
+    let pool = GILPool::new();
+    let stored = match state_slot(module) {
+        Some(slot) if !(*slot).is_null() => &**slot,
+        _ => return 0,
+    };
+    let visit = PyVisit::new(visit, arg, pool.python());
+    match crate::panic::catch_unwind(AssertUnwindSafe(|| stored.traverse(visit))) {
+        Ok(Ok(())) => 0,
+        Ok(Err(PyTraverseError(code))) => code,
+        Err(_) => {
+            eprintln!("`ModuleState::traverse` panicked, aborting the process.");
+            std::process::abort()
+        }
+    }
+}
+
+/// `m_clear` of multi-phase modules.
+pub(crate) unsafe extern "C" fn clear(module: *mut ffi::PyObject) -> c_int {
+    let pool = GILPool::new();
+    if let Some(slot) = state_slot(module) {
+        if let Some(stored) = (*slot).as_ref() {
+            catch_unraisable(pool.python(), module, AssertUnwindSafe(|| stored.clear()));
+        }
+    }
+    0
+}
+
+/// `m_free` of multi-phase modules, which drops the state.
+pub(crate) unsafe extern "C" fn free(module: *mut c_void) {
+    let pool = GILPool::new();
+    if let Some(slot) = state_slot(module as *mut ffi::PyObject) {
+        let stored = std::mem::replace(&mut *slot, std::ptr::null_mut());
+        if !stored.is_null() {
+            // The module is being deallocated, so it can't be passed to the hook.
+            catch_unraisable(
+                pool.python(),
+                std::ptr::null_mut(),
+                AssertUnwindSafe(|| drop(Box::from_raw(stored))),
+            );
+        }
+    }
+}
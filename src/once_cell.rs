@@ -1,5 +1,5 @@
 //! A write-once cell mediated by the Python GIL.
-use crate::Python;
+use crate::{gil, Py, PyNativeType, PyTryFrom, PyTypeInfo, Python};
 use std::cell::UnsafeCell;
 
 /// A write-once cell similar to [`once_cell::OnceCell`](https://docs.rs/once_cell/1.4.0/once_cell/).
@@ -9,6 +9,11 @@ use std::cell::UnsafeCell;
 /// `lazy_static`'s synchronization strategy can lead to deadlocks when interacting with the Python
 /// GIL. For an example, see [the FAQ section](https://pyo3.rs/latest/faq.html) of the guide.
 ///
+/// A `GILOnceCell` in a static holds one value for the whole process, which is shared with
+/// [sub-interpreters](crate::interpreter::SubInterpreter) too. To cache a Python object, use
+/// [`get_or_init_in_interpreter`](GILOnceCell::get_or_init_in_interpreter), which keeps a separate
+/// object for each sub-interpreter.
+///
 /// # Examples
 ///
 /// The following example shows how to use `GILOnceCell` to share a reference to a Python list
@@ -102,3 +107,53 @@ impl<T> GILOnceCell<T> {
         Ok(())
     }
 }
+
+impl<T> GILOnceCell<Py<T>>
+where
+    T: PyTypeInfo<AsRefTarget = T> + PyNativeType,
+{
+    /// Like [`get_or_init`](GILOnceCell::get_or_init), but in a sub-interpreter the object is kept
+    /// in a cache of that interpreter instead of in the cell, so that interpreters never share
+    /// it.
+    ///
+    /// Objects cached for a sub-interpreter are released when the sub-interpreter is finalized.
+    pub fn get_or_init_in_interpreter<'py, F>(&'py self, py: Python<'py>, f: F) -> &'py T
+    where
+        F: FnOnce() -> Py<T>,
+    {
+        match self.get_or_try_init_in_interpreter(py, || Ok::<_, std::convert::Infallible>(f())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Like [`get_or_init_in_interpreter`](GILOnceCell::get_or_init_in_interpreter), for
+    /// initializations which can fail. Nothing is cached if `f` returns an error.
+    pub fn get_or_try_init_in_interpreter<'py, F, E>(
+        &'py self,
+        py: Python<'py>,
+        f: F,
+    ) -> Result<&'py T, E>
+    where
+        F: FnOnce() -> Result<Py<T>, E>,
+    {
+        let cache = match gil::sub_interpreter_cache(py) {
+            Some(cache) => cache,
+            None => {
+                if let Some(value) = self.get(py) {
+                    return Ok(value.as_ref(py));
+                }
+                let value = f()?;
+                return Ok(self.get_or_init(py, || value).as_ref(py));
+            }
+        };
+        let key = self as *const Self as usize;
+        if let Some(value) = cache.get_item(key) {
+            return Ok(unsafe { T::try_from_unchecked(value) });
+        }
+        let value = f()?;
+        // if this fails, the object is just cached for this call alone
+        let _ = cache.set_item(key, &value);
+        Ok(value.into_ref(py))
+    }
+}
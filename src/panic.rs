@@ -1,7 +1,41 @@
 //! Helper to convert Rust panics to Python exceptions.
-use crate::exceptions::PyBaseException;
-use crate::PyErr;
+//!
+//! When a panic is converted to a [`PanicException`], the place in Rust where it happened is added
+//! to the exception's traceback, so that Python tracebacks show the Rust functions between the
+//! Python caller and the panic. On Rust 1.65 and greater the whole Rust backtrace is used, which
+//! requires debug info to resolve file names and line numbers; otherwise only the location of the
+//! panic is shown. The backtrace is also available as the `rust_backtrace` attribute of the
+//! exception.
+//!
+//! Backtraces are captured by a panic hook, installed the first time a panic can be caught by
+//! PyO3, which forwards to the previously installed hook. Capturing and resolving a backtrace has
+//! a cost for each panic, so like for [`std::backtrace::Backtrace::capture`] it is only done when
+//! the `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment variable is set to something else
+//! than `0`. This can be overridden with [`set_backtrace_capture`], for example to always capture
+//! backtraces in debug builds:
+//!
+//! ```
+//! # use pyo3::prelude::*;
+//! #[pymodule]
+//! fn my_module(_py: Python, _m: &PyModule) -> PyResult<()> {
+//!     if cfg!(debug_assertions) {
+//!         pyo3::panic::set_backtrace_capture(true);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use crate::exceptions::{PyBaseException, PyExceptionType};
+use crate::type_object::PyTypeObject;
+use crate::types::{PyAny, PyType};
+use crate::{ffi, AsPyPointer, PyErr, PyTypeInfo, Python};
+use parking_lot::{const_mutex, Mutex};
 use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::panic::{self, UnwindSafe};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Once;
 
 pyo3_exception!(
     "
@@ -15,15 +49,480 @@ pyo3_exception!(
     PyBaseException
 );
 
+const CAPTURE_FROM_ENV: u8 = 0;
+const CAPTURE_DISABLED: u8 = 1;
+const CAPTURE_ENABLED: u8 = 2;
+
+static CAPTURE_BACKTRACES: AtomicU8 = AtomicU8::new(CAPTURE_FROM_ENV);
+
+/// Sets whether the Rust backtrace of panics is added to the traceback of `PanicException`s.
+///
+/// By default, capturing is enabled if the `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment
+/// variable is set to something else than `0`. When disabled, `PanicException`s only contain the
+/// panic message, and PyO3 doesn't install its panic hook.
+pub fn set_backtrace_capture(enabled: bool) {
+    let setting = if enabled {
+        CAPTURE_ENABLED
+    } else {
+        CAPTURE_DISABLED
+    };
+    CAPTURE_BACKTRACES.store(setting, Ordering::Relaxed);
+}
+
+fn backtrace_capture_enabled() -> bool {
+    match CAPTURE_BACKTRACES.load(Ordering::Relaxed) {
+        CAPTURE_ENABLED => true,
+        CAPTURE_DISABLED => false,
+        _ => {
+            // Read the environment once, unless `set_backtrace_capture` was called meanwhile
+            let setting = if backtrace_env_enabled() {
+                CAPTURE_ENABLED
+            } else {
+                CAPTURE_DISABLED
+            };
+            match CAPTURE_BACKTRACES.compare_exchange(
+                CAPTURE_FROM_ENV,
+                setting,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => setting == CAPTURE_ENABLED,
+                Err(current) => current == CAPTURE_ENABLED,
+            }
+        }
+    }
+}
+
+/// Whether the environment enables backtraces, with the same rules as
+/// `std::backtrace::Backtrace::capture`.
+fn backtrace_env_enabled() -> bool {
+    match std::env::var_os("RUST_LIB_BACKTRACE").or_else(|| std::env::var_os("RUST_BACKTRACE")) {
+        Some(value) => value != "0",
+        None => false,
+    }
+}
+
+thread_local! {
+    /// The number of calls to `catch_unwind` running on this thread.
+    static CATCHING: Cell<usize> = Cell::new(0);
+    /// Details of the last panic caught by `catch_unwind` on this thread.
+    static CAPTURED: RefCell<Option<CapturedPanic>> = RefCell::new(None);
+}
+
+/// What the panic hook records about a panic.
+struct CapturedPanic {
+    /// File and line of the panic, if known.
+    location: Option<(String, u32)>,
+    #[cfg(backtrace)]
+    backtrace: std::backtrace::Backtrace,
+}
+
+/// A frame of the Rust call stack, as shown in Python tracebacks.
+#[derive(Debug, PartialEq)]
+struct RustFrame {
+    function: String,
+    filename: String,
+    lineno: u32,
+}
+
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let catching = CATCHING.try_with(|catching| catching.get() > 0);
+            if catching.unwrap_or(false) && backtrace_capture_enabled() {
+                let captured = CapturedPanic {
+                    location: info
+                        .location()
+                        .map(|location| (location.file().to_string(), location.line())),
+                    #[cfg(backtrace)]
+                    backtrace: std::backtrace::Backtrace::force_capture(),
+                };
+                let _ = CAPTURED.try_with(|cell| *cell.borrow_mut() = Some(captured));
+            }
+            previous_hook(info)
+        }));
+    });
+}
+
+/// `std::panic::catch_unwind`, which also captures the backtrace of the panic for
+/// [`PanicPolicy::panic_into_err`].
+pub(crate) fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(f: F) -> std::thread::Result<R> {
+    if backtrace_capture_enabled() {
+        install_panic_hook();
+    }
+    CAPTURED.with(|cell| cell.borrow_mut().take());
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = panic::catch_unwind(f);
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+    result
+}
+
 impl PanicException {
+    pub(crate) fn from_panic_payload(py: Python, payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        panic_payload_into_err(py, payload, Self::type_object(py))
+    }
+}
+
+/// Creates an exception of type `ty` from a panic, with the panic message as argument.
+fn panic_payload_into_err(
+    py: Python,
+    payload: Box<dyn Any + Send + 'static>,
+    ty: &PyType,
+) -> PyErr {
     // Try to format the error in the same way panic does
-    pub(crate) fn from_panic_payload(payload: Box<dyn Any + Send + 'static>) -> PyErr {
-        if let Some(string) = payload.downcast_ref::<String>() {
-            Self::new_err((string.clone(),))
-        } else if let Some(s) = payload.downcast_ref::<&str>() {
-            Self::new_err((s.to_string(),))
+    let err = if let Some(string) = payload.downcast_ref::<String>() {
+        PyErr::from_type(ty, (string.clone(),))
+    } else if let Some(s) = payload.downcast_ref::<&str>() {
+        PyErr::from_type(ty, (s.to_string(),))
+    } else {
+        PyErr::from_type(ty, ("panic from Rust code",))
+    };
+    match CAPTURED.with(|cell| cell.borrow_mut().take()) {
+        Some(captured) => add_rust_frames(py, err, captured),
+        None => err,
+    }
+}
+
+/// How a panic in a function called from Python is handled, selected with the
+/// `#[pyo3(panic = "...")]` option of the macros.
+#[doc(hidden)]
+pub trait PanicPolicy {
+    /// Converts the payload of a caught panic to the exception to raise.
+    fn panic_into_err(py: Python, payload: Box<dyn Any + Send + 'static>) -> PyErr;
+}
+
+/// `panic = "raise"`, the default: raise a `PanicException`.
+#[doc(hidden)]
+pub struct RaisePanic;
+
+impl PanicPolicy for RaisePanic {
+    fn panic_into_err(py: Python, payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        PanicException::from_panic_payload(py, payload)
+    }
+}
+
+/// `panic = "raise_as = T"`: raise an exception of type `T`.
+///
+/// `T` must be an exception type, such as the ones in [`exceptions`](crate::exceptions), defined
+/// with [`create_exception!`](crate::create_exception) or a `#[pyclass]` extending one of them.
+#[doc(hidden)]
+pub struct RaisePanicAs<T>(PhantomData<T>);
+
+impl<T: PyExceptionType> PanicPolicy for RaisePanicAs<T> {
+    fn panic_into_err(py: Python, payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        panic_payload_into_err(py, payload, T::type_object(py))
+    }
+}
+
+/// `panic = "abort"`: abort the process, as continuing could expose state left inconsistent by
+/// the panic.
+#[doc(hidden)]
+pub struct AbortOnPanic;
+
+impl PanicPolicy for AbortOnPanic {
+    fn panic_into_err(_py: Python, _payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        eprintln!("Rust code called from Python panicked, aborting the process.");
+        std::process::abort()
+    }
+}
+
+/// `PanicPolicy::panic_into_err` of a policy chosen at runtime.
+type PanicIntoErr = fn(Python, Box<dyn Any + Send + 'static>) -> PyErr;
+
+thread_local! {
+    /// The policy of the `#[pymodule]` being initialized on this thread, if it sets one.
+    static MODULE_POLICY: Cell<Option<PanicIntoErr>> = Cell::new(None);
+}
+
+/// The policies of the modules which added functions and classes, keyed like in `PanicScope`.
+static ADDED_POLICIES: Mutex<Option<HashMap<usize, PanicIntoErr>>> = const_mutex(None);
+
+/// Runs the initialization of a `#[pymodule]` with a `panic` option, so that the functions and
+/// classes it adds handle panics according to `P` unless they set their own option.
+#[doc(hidden)]
+pub fn with_module_policy<P: PanicPolicy, R>(init: impl FnOnce() -> R) -> R {
+    struct Restore(Option<PanicIntoErr>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            MODULE_POLICY.with(|policy| policy.set(self.0));
+        }
+    }
+
+    let _restore = Restore(MODULE_POLICY.with(|policy| policy.replace(Some(P::panic_into_err))));
+    init()
+}
+
+/// Records the policy of the module being initialized, if any, for a function or class added to
+/// it.
+pub(crate) fn module_added(object: &PyAny) {
+    let policy = match MODULE_POLICY.try_with(Cell::get) {
+        Ok(Some(policy)) => policy,
+        _ => return,
+    };
+    let ptr = object.as_ptr();
+    let key = unsafe {
+        if ffi::PyCFunction_Check(ptr) != 0 {
+            match ffi::PyCFunction_GetFunction(ptr) {
+                Some(function) => function as usize,
+                None => return,
+            }
+        } else if ffi::PyType_Check(ptr) != 0 {
+            ptr as usize
+        } else {
+            return;
+        }
+    };
+    ADDED_POLICIES
+        .lock()
+        .get_or_insert_with(HashMap::new)
+        .insert(key, policy);
+}
+
+/// Where a function without its own `panic` option is defined, so that a panic in it is handled
+/// according to the `panic` option of the `#[pymodule]` which added the function or its class.
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub enum PanicScope {
+    /// A `#[pyfunction]`, identified by the address of its C wrapper.
+    Function(usize),
+    /// A method of a `#[pyclass]`, identified by its type object.
+    Class(fn(Python) -> *mut ffi::PyTypeObject),
+}
+
+impl PanicScope {
+    pub fn class<T: PyTypeInfo>() -> Self {
+        PanicScope::Class(T::type_object_raw)
+    }
+
+    /// Like [`handle_panic`](crate::callback::handle_panic), with the policy of the module.
+    ///
+    /// # Safety
+    ///
+    /// Like `handle_panic`, this must only be called from a function called by Python.
+    #[inline]
+    pub unsafe fn handle_panic<F, R>(self, body: F) -> R
+    where
+        F: FnOnce(Python) -> crate::PyResult<R> + UnwindSafe,
+        R: crate::callback::PyCallbackOutput,
+    {
+        crate::callback::handle_panic_in(body, move |py, payload| self.panic_into_err(py, payload))
+    }
+
+    /// Converts a caught panic to an exception with the policy of the module, which is also used
+    /// for the future of an `async fn`.
+    #[cold]
+    pub fn panic_into_err(self, py: Python, payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        let key = match self {
+            PanicScope::Function(key) => key,
+            PanicScope::Class(type_object) => type_object(py) as usize,
+        };
+        let policy = ADDED_POLICIES
+            .lock()
+            .as_ref()
+            .and_then(|policies| policies.get(&key).copied());
+        match policy {
+            Some(panic_into_err) => panic_into_err(py, payload),
+            None => RaisePanic::panic_into_err(py, payload),
+        }
+    }
+}
+
+/// Sets the `rust_backtrace` attribute of the exception, and adds the frames of the backtrace to
+/// its traceback.
+fn add_rust_frames(py: Python, err: PyErr, captured: CapturedPanic) -> PyErr {
+    #[cfg(backtrace)]
+    let (backtrace, mut frames) = {
+        let backtrace = captured.backtrace.to_string();
+        let frames = parse_backtrace(&backtrace);
+        (Some(backtrace), frames)
+    };
+    #[cfg(not(backtrace))]
+    let (backtrace, mut frames): (Option<String>, _) = (None, Vec::new());
+
+    // Without debug info the backtrace has no locations, so at least show where the panic happened
+    if frames.is_empty() {
+        if let Some((filename, lineno)) = captured.location {
+            frames.push(RustFrame {
+                function: "<panic>".to_string(),
+                filename,
+                lineno,
+            });
+        }
+    }
+
+    if err
+        .instance(py)
+        .setattr("rust_backtrace", backtrace)
+        .is_err()
+    {
+        return err;
+    }
+    add_traceback_frames(py, err, &frames)
+}
+
+/// Adds fake Python frames with the location of Rust functions to the traceback of `err`, like
+/// Cython does for its generated C code. The frames are ordered from the innermost.
+#[cfg(not(Py_LIMITED_API))]
+fn add_traceback_frames(py: Python, err: PyErr, frames: &[RustFrame]) -> PyErr {
+    use crate::types::PyDict;
+    use crate::{ffi, AsPyPointer, PyObject};
+    use std::ffi::CString;
+    use std::os::raw::c_int;
+
+    // The frames must be created while no error is set
+    let globals = PyDict::new(py);
+    let py_frames: Vec<PyObject> = frames
+        .iter()
+        .filter_map(|frame| unsafe {
+            let filename = CString::new(frame.filename.as_str()).ok()?;
+            let function = CString::new(frame.function.as_str()).ok()?;
+            let code = PyObject::from_owned_ptr_or_opt(
+                py,
+                ffi::PyCode_NewEmpty(filename.as_ptr(), function.as_ptr(), frame.lineno as c_int)
+                    as *mut ffi::PyObject,
+            )?;
+            PyObject::from_owned_ptr_or_opt(
+                py,
+                ffi::PyFrame_New(
+                    ffi::PyThreadState_Get(),
+                    code.as_ptr() as *mut ffi::PyCodeObject,
+                    globals.as_ptr(),
+                    std::ptr::null_mut(),
+                ) as *mut ffi::PyObject,
+            )
+        })
+        .collect();
+    // Creating a frame can only fail when out of memory
+    unsafe { ffi::PyErr_Clear() };
+
+    err.restore(py);
+    for frame in &py_frames {
+        unsafe { ffi::PyTraceBack_Here(frame.as_ptr() as *mut ffi::PyFrameObject) };
+    }
+    // `PyErr::fetch` would resume the panic
+    unsafe { PyErr::fetch_raw(py) }
+}
+
+#[cfg(Py_LIMITED_API)]
+fn add_traceback_frames(_py: Python, err: PyErr, _frames: &[RustFrame]) -> PyErr {
+    err
+}
+
+/// Extracts the frames between the panic and the enclosing `catch_unwind` from the output of
+/// `std::backtrace::Backtrace`, which looks like:
+///
+/// ```text
+///    0: rust_begin_unwind
+///              at /rustc/.../library/std/src/panicking.rs:665:5
+///    1: my_crate::my_function
+///              at ./src/lib.rs:10:5
+/// ```
+///
+/// Frames of the standard library, of PyO3 and its generated wrappers, and without debug info are
+/// skipped.
+#[cfg_attr(not(backtrace), allow(dead_code))]
+fn parse_backtrace(backtrace: &str) -> Vec<RustFrame> {
+    const SKIPPED_PREFIXES: &[&str] = &[
+        "std::",
+        "core::",
+        "alloc::",
+        "pyo3::",
+        "<std::",
+        "<core::",
+        "<alloc::",
+        "<pyo3::",
+        "rust_begin_unwind",
+        "__rust",
+    ];
+    let skipped = |function: &str| {
+        SKIPPED_PREFIXES
+            .iter()
+            .any(|prefix| function.starts_with(prefix))
+            || function.contains("::__pyo3_raw_")
+            || function.contains("::__wrap")
+    };
+
+    let mut frames = Vec::new();
+    let mut function: Option<&str> = None;
+    for line in backtrace.lines() {
+        let line = line.trim();
+        let mut words = line.splitn(2, ' ');
+        if let (Some("at"), Some(location)) = (words.next(), words.next()) {
+            let function = match function.take() {
+                Some(function) => function,
+                None => continue,
+            };
+            // `path:line:column`, where the path may itself contain colons
+            let mut parts = location.rsplitn(3, ':');
+            let _column = parts.next();
+            let lineno = parts.next().and_then(|lineno| lineno.parse().ok());
+            if let (Some(lineno), Some(filename)) = (lineno, parts.next()) {
+                if !skipped(function) {
+                    frames.push(RustFrame {
+                        function: function.to_string(),
+                        filename: filename.to_string(),
+                        lineno,
+                    });
+                }
+            }
         } else {
-            Self::new_err(("panic from Rust code",))
+            // Inlined functions are listed without an index under the frame they were inlined in
+            let symbol = match line.find(": ") {
+                Some(index) if line[..index].bytes().all(|b| b.is_ascii_digit()) => {
+                    &line[index + 2..]
+                }
+                _ => line,
+            };
+            if symbol.starts_with("std::panicking::try")
+                || symbol.starts_with("std::panicking::catch_unwind")
+                || symbol.starts_with("std::panic::catch_unwind")
+            {
+                break;
+            }
+            function = Some(symbol);
         }
     }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_backtrace, RustFrame};
+
+    #[test]
+    fn test_parse_backtrace() {
+        let backtrace = "   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:665:5
+   1: core::panicking::panic_fmt
+             at /rustc/abc/library/core/src/panicking.rs:76:14
+   2: my_crate::inner
+             at ./src/lib.rs:10:5
+      my_crate::outer::{{closure}}
+             at C:\\src\\lib.rs:20:9
+   3: <my_crate::Foo as my_crate::Bar>::bar
+   4: my_crate::__pyo3_raw_outer::{{closure}}
+             at ./src/lib.rs:15:1
+      pyo3::callback::handle_panic::{{closure}}
+             at ./src/callback.rs:247:9
+   5: std::panicking::try::do_call
+             at /rustc/abc/library/std/src/panicking.rs:552:40
+   6: python_function
+             at ./main.rs:1:1
+";
+        let frame = |function: &str, filename: &str, lineno| RustFrame {
+            function: function.to_string(),
+            filename: filename.to_string(),
+            lineno,
+        };
+        assert_eq!(
+            parse_backtrace(backtrace),
+            vec![
+                frame("my_crate::inner", "./src/lib.rs", 10),
+                frame("my_crate::outer::{{closure}}", "C:\\src\\lib.rs", 20),
+            ]
+        );
+    }
 }
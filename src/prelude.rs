@@ -24,4 +24,6 @@ pub use crate::wrap_pyfunction;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
 #[cfg(feature = "macros")]
-pub use pyo3_macros::{pyclass, pyfunction, pymethods, pymodule, pyproto, FromPyObject};
+pub use pyo3_macros::{
+    pyclass, pyfunction, pymethods, pymodule, pyproto, FromPyObject, IntoPyObject, PyException,
+};
@@ -141,7 +141,8 @@
 //! [Interior Mutability]: https://doc.rust-lang.org/book/ch15-05-interior-mutability.html "RefCell<T> and the Interior Mutability Pattern - The Rust Programming Language"
 
 use crate::exceptions::PyRuntimeError;
-use crate::pyclass::PyClass;
+use crate::inspect::types::TypeInfo;
+use crate::pyclass::{boolean_struct::False, is_frozen, PyClass};
 use crate::pyclass_init::PyClassInitializer;
 use crate::pyclass_slots::{PyClassDict, PyClassWeakRef};
 use crate::type_object::{PyLayout, PySizedLayout};
@@ -328,7 +329,10 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// Panics if the value is currently borrowed. For a non-panicking variant, use
     /// [`try_borrow_mut`](#method.try_borrow_mut).
-    pub fn borrow_mut(&self) -> PyRefMut<'_, T> {
+    pub fn borrow_mut(&self) -> PyRefMut<'_, T>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.try_borrow_mut().expect("Already borrowed")
     }
 
@@ -358,6 +362,10 @@ impl<T: PyClass> PyCell<T> {
     /// });
     /// ```
     pub fn try_borrow(&self) -> Result<PyRef<'_, T>, PyBorrowError> {
+        if is_frozen::<T>() {
+            // Frozen classes can never be mutably borrowed, so the flag is not needed.
+            return Ok(PyRef { inner: self });
+        }
         let flag = self.get_borrow_flag();
         if flag == BorrowFlag::HAS_MUTABLE_BORROW {
             Err(PyBorrowError { _private: () })
@@ -388,8 +396,19 @@ impl<T: PyClass> PyCell<T> {
     ///     assert!(c.try_borrow_mut().is_ok());
     /// });
     /// ```
-    pub fn try_borrow_mut(&self) -> Result<PyRefMut<'_, T>, PyBorrowMutError> {
-        if self.get_borrow_flag() != BorrowFlag::UNUSED {
+    pub fn try_borrow_mut(&self) -> Result<PyRefMut<'_, T>, PyBorrowMutError>
+    where
+        T: PyClass<Frozen = False>,
+    {
+        self.try_borrow_mut_dynamic()
+    }
+
+    /// Like [`try_borrow_mut`](#method.try_borrow_mut), but checks for `#[pyclass(frozen)]` at
+    /// runtime rather than at compile time.
+    ///
+    /// This is used by the `#[pyproto]` implementations, which are generic over all classes.
+    pub(crate) fn try_borrow_mut_dynamic(&self) -> Result<PyRefMut<'_, T>, PyBorrowMutError> {
+        if is_frozen::<T>() || self.get_borrow_flag() != BorrowFlag::UNUSED {
             Err(PyBorrowMutError { _private: () })
         } else {
             self.set_borrow_flag(BorrowFlag::HAS_MUTABLE_BORROW);
@@ -440,7 +459,10 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// Panics if the value is currently borrowed.
     #[inline]
-    pub fn replace(&self, t: T) -> T {
+    pub fn replace(&self, t: T) -> T
+    where
+        T: PyClass<Frozen = False>,
+    {
         std::mem::replace(&mut *self.borrow_mut(), t)
     }
 
@@ -449,7 +471,10 @@ impl<T: PyClass> PyCell<T> {
     /// # Panics
     ///
     /// Panics if the value is currently borrowed.
-    pub fn replace_with<F: FnOnce(&mut T) -> T>(&self, f: F) -> T {
+    pub fn replace_with<F: FnOnce(&mut T) -> T>(&self, f: F) -> T
+    where
+        T: PyClass<Frozen = False>,
+    {
         let mut_borrow = &mut *self.borrow_mut();
         let replacement = f(mut_borrow);
         std::mem::replace(mut_borrow, replacement)
@@ -461,13 +486,52 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// Panics if the value in either `PyCell` is currently borrowed.
     #[inline]
-    pub fn swap(&self, other: &Self) {
+    pub fn swap(&self, other: &Self)
+    where
+        T: PyClass<Frozen = False>,
+    {
         std::mem::swap(&mut *self.borrow_mut(), &mut *other.borrow_mut())
     }
 
-    fn get_ptr(&self) -> *mut T {
+    /// Gets a shared reference to the value of a `#[pyclass(frozen)]` class.
+    ///
+    /// Because frozen classes can never be mutably borrowed, this doesn't need to touch the
+    /// borrow flag, unlike [`borrow`](#method.borrow).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pyo3::prelude::*;
+    /// #[pyclass(frozen)]
+    /// struct FrozenCounter {
+    ///     value: usize,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let cell = PyCell::new(py, FrozenCounter { value: 0 }).unwrap();
+    ///     assert_eq!(cell.get().value, 0);
+    /// });
+    /// ```
+    pub fn get(&self) -> &T
+    where
+        T: PyClass<Frozen = crate::pyclass::boolean_struct::True>,
+    {
+        unsafe { &*self.get_ptr() }
+    }
+
+    pub(crate) fn get_ptr(&self) -> *mut T {
         self.contents.value.get()
     }
+
+    /// Releases a shared borrow which was kept by forgetting a `PyRef`.
+    pub(crate) unsafe fn release_ref(&self) {
+        drop(PyRef { inner: self })
+    }
+
+    /// Releases a mutable borrow which was kept by forgetting a `PyRefMut`.
+    pub(crate) unsafe fn release_mut(&self) {
+        drop(PyRefMut { inner: self })
+    }
 }
 
 unsafe impl<T: PyClass> PyLayout<T> for PyCell<T> {}
@@ -576,7 +640,7 @@ impl<'p, T: PyClass> PyRef<'p, T> {
 impl<'p, T, U> AsRef<U> for PyRef<'p, T>
 where
     T: PyClass<BaseType = U>,
-    U: PyClass,
+    U: PyClass<Frozen = T::Frozen>,
 {
     fn as_ref(&self) -> &T::BaseType {
         unsafe { &*self.inner.ob_base.get_ptr() }
@@ -586,7 +650,7 @@ where
 impl<'p, T, U> PyRef<'p, T>
 where
     T: PyClass<BaseType = U>,
-    U: PyClass,
+    U: PyClass<Frozen = T::Frozen>,
 {
     /// Gets a `PyRef<T::BaseType>`.
     ///
@@ -653,6 +717,9 @@ impl<'p, T: PyClass> Deref for PyRef<'p, T> {
 
 impl<'p, T: PyClass> Drop for PyRef<'p, T> {
     fn drop(&mut self) {
+        if is_frozen::<T>() {
+            return;
+        }
         let flag = self.inner.get_borrow_flag();
         self.inner.set_borrow_flag(flag.decrement())
     }
@@ -662,6 +729,10 @@ impl<T: PyClass> IntoPy<PyObject> for PyRef<'_, T> {
     fn into_py(self, py: Python<'_>) -> PyObject {
         unsafe { PyObject::from_borrowed_ptr(py, self.inner.as_ptr()) }
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T: PyClass> std::convert::TryFrom<&'a PyCell<T>> for crate::PyRef<'a, T> {
@@ -710,7 +781,7 @@ where
 impl<'p, T, U> AsMut<U> for PyRefMut<'p, T>
 where
     T: PyClass<BaseType = U>,
-    U: PyClass,
+    U: PyClass<Frozen = False>,
 {
     fn as_mut(&mut self) -> &mut T::BaseType {
         unsafe { &mut *self.inner.ob_base.get_ptr() }
@@ -720,7 +791,7 @@ where
 impl<'p, T, U> PyRefMut<'p, T>
 where
     T: PyClass<BaseType = U>,
-    U: PyClass,
+    U: PyClass<Frozen = False>,
 {
     /// Gets a `PyRef<T::BaseType>`.
     ///
@@ -760,6 +831,10 @@ impl<T: PyClass> IntoPy<PyObject> for PyRefMut<'_, T> {
     fn into_py(self, py: Python) -> PyObject {
         unsafe { PyObject::from_borrowed_ptr(py, self.inner.as_ptr()) }
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::of::<T>()
+    }
 }
 
 impl<'a, T: PyClass> AsPyPointer for PyRefMut<'a, T> {
@@ -768,7 +843,9 @@ impl<'a, T: PyClass> AsPyPointer for PyRefMut<'a, T> {
     }
 }
 
-impl<'a, T: PyClass> std::convert::TryFrom<&'a PyCell<T>> for crate::PyRefMut<'a, T> {
+impl<'a, T: PyClass<Frozen = False>> std::convert::TryFrom<&'a PyCell<T>>
+    for crate::PyRefMut<'a, T>
+{
     type Error = PyBorrowMutError;
     fn try_from(cell: &'a crate::PyCell<T>) -> Result<Self, Self::Error> {
         cell.try_borrow_mut()
@@ -12,6 +12,9 @@ use std::{
     ptr,
 };
 
+#[cfg(feature = "inspect")]
+use crate::inspect::{self, ClassInfo};
+
 /// If `PyClass` is implemented for a Rust type `T`, then we can use `T` in the Python
 /// world, via `PyCell`.
 ///
@@ -27,6 +30,46 @@ pub trait PyClass:
     /// The closest native ancestor. This is `PyAny` by default, and when you declare
     /// `#[pyclass(extends=PyDict)]`, it's `PyDict`.
     type BaseNativeType: PyTypeInfo + PyNativeType;
+    /// Whether the class is `#[pyclass(frozen)]`; either [`boolean_struct::True`] or
+    /// [`boolean_struct::False`].
+    ///
+    /// Frozen classes can't be mutably borrowed, so they don't need a runtime borrow flag.
+    type Frozen: boolean_struct::Boolean;
+}
+
+/// Type-level booleans, used by [`PyClass::Frozen`].
+pub mod boolean_struct {
+    pub(crate) mod private {
+        pub trait Sealed {}
+    }
+
+    /// Implemented by [`True`] and [`False`].
+    pub trait Boolean: private::Sealed {
+        /// The value of this type as a `bool`.
+        const VALUE: bool;
+    }
+
+    /// Type-level `true`.
+    pub struct True(());
+
+    /// Type-level `false`.
+    pub struct False(());
+
+    impl private::Sealed for True {}
+    impl private::Sealed for False {}
+
+    impl Boolean for True {
+        const VALUE: bool = true;
+    }
+
+    impl Boolean for False {
+        const VALUE: bool = false;
+    }
+}
+
+#[inline]
+pub(crate) fn is_frozen<T: PyClass>() -> bool {
+    <T::Frozen as boolean_struct::Boolean>::VALUE
 }
 
 /// For collecting slot items.
@@ -39,15 +82,38 @@ impl TypeSlots {
     }
 }
 
-fn tp_doc<T: PyClass>() -> PyResult<Option<*mut c_void>> {
-    Ok(match T::DOC {
-        "\0" => None,
-        s if s.as_bytes().ends_with(b"\0") => Some(s.as_ptr() as _),
+/// Returns the null-terminated class docstring.
+///
+/// Unless the docstring already starts with a `text_signature`, the `__text_signature__`
+/// generated from `#[new]` is prepended in the format CPython expects.
+fn tp_doc<T: PyClass>() -> PyResult<Option<&'static [u8]>> {
+    let has_text_signature = T::DOC.starts_with(T::NAME)
+        && T::DOC[T::NAME.len()..].starts_with('(')
+        && T::DOC.contains(")\n--\n\n");
+    let text_signature = if has_text_signature {
+        None
+    } else {
+        T::get_new_text_signature()
+    };
+    Ok(match (T::DOC, text_signature) {
+        ("\0", None) => None,
+        (s, None) if s.as_bytes().ends_with(b"\0") => Some(s.as_bytes()),
         // If the description is not null-terminated, create CString and leak it
-        s => Some(CString::new(s)?.into_raw() as _),
+        (s, None) => Some(leak_cstring(s.to_owned())?),
+        (s, Some(text_signature)) => Some(leak_cstring(format!(
+            "{}{}\n--\n\n{}",
+            T::NAME,
+            text_signature,
+            s.trim_end_matches('\0')
+        ))?),
     })
 }
 
+fn leak_cstring(s: String) -> PyResult<&'static [u8]> {
+    let bytes = CString::new(s)?.into_bytes_with_nul().into_boxed_slice();
+    Ok(Box::leak(bytes))
+}
+
 fn get_type_name<T: PyTypeInfo>(module_name: Option<&str>) -> PyResult<*mut c_char> {
     Ok(match module_name {
         Some(module_name) => CString::new(format!("{}.{}", module_name, T::NAME))?.into_raw(),
@@ -69,8 +135,9 @@ where
     let mut slots = TypeSlots::default();
 
     slots.push(ffi::Py_tp_base, T::BaseType::type_object_raw(py) as _);
-    if let Some(doc) = tp_doc::<T>()? {
-        slots.push(ffi::Py_tp_doc, doc);
+    let doc = tp_doc::<T>()?;
+    if let Some(doc) = doc {
+        slots.push(ffi::Py_tp_doc, doc.as_ptr() as _);
     }
 
     slots.push(ffi::Py_tp_new, T::get_new().unwrap_or(fallback_new) as _);
@@ -128,14 +195,16 @@ where
     if type_object.is_null() {
         Err(PyErr::api_call_failed(py))
     } else {
-        tp_init_additional::<T>(type_object as _);
+        tp_init_additional::<T>(type_object as _, doc);
+        #[cfg(feature = "inspect")]
+        inspect::register_class(type_object as _, ClassInfo::of::<T>);
         Ok(type_object as _)
     }
 }
 
 /// Additional type initializations necessary before Python 3.10
 #[cfg(all(not(Py_LIMITED_API), not(Py_3_10)))]
-fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
+fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject, doc: Option<&[u8]>) {
     // Just patch the type objects for the things there's no
     // PyType_FromSpec API for... there's no reason this should work,
     // except for that it does and we have tests.
@@ -143,15 +212,15 @@ fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
     // Running this causes PyPy to segfault.
     #[cfg(all(not(PyPy), not(Py_3_10)))]
     {
-        if T::DOC != "\0" {
+        if let Some(doc) = doc {
             unsafe {
                 // Until CPython 3.10, tp_doc was treated specially for
                 // heap-types, and it removed the text_signature value from it.
                 // We go in after the fact and replace tp_doc with something
                 // that _does_ include the text_signature value!
                 ffi::PyObject_Free((*type_object).tp_doc as _);
-                let data = ffi::PyObject_Malloc(T::DOC.len());
-                data.copy_from(T::DOC.as_ptr() as _, T::DOC.len());
+                let data = ffi::PyObject_Malloc(doc.len());
+                data.copy_from(doc.as_ptr() as _, doc.len());
                 (*type_object).tp_doc = data as _;
             }
         }
@@ -187,7 +256,7 @@ fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
 }
 
 #[cfg(any(Py_LIMITED_API, Py_3_10))]
-fn tp_init_additional<T: PyClass>(_type_object: *mut ffi::PyTypeObject) {}
+fn tp_init_additional<T: PyClass>(_type_object: *mut ffi::PyTypeObject, _doc: Option<&[u8]>) {}
 
 fn py_class_flags(has_gc_methods: bool, is_gc: bool, is_basetype: bool) -> c_uint {
     let mut flags = if has_gc_methods || is_gc {
@@ -1,8 +1,10 @@
 //! Contains initialization utilities for `#[pyclass]`.
 use crate::class::impl_::PyClassThreadChecker;
+use crate::exceptions::PyExceptionType;
 use crate::pyclass_slots::{PyClassDict, PyClassWeakRef};
+use crate::types::PyTuple;
 use crate::{callback::IntoPyCallbackOutput, class::impl_::PyClassBaseType};
-use crate::{ffi, PyCell, PyClass, PyErr, PyResult, Python};
+use crate::{ffi, IntoPy, Py, PyCell, PyClass, PyErr, PyResult, Python};
 use crate::{
     ffi::PyTypeObject,
     pycell::{BorrowFlag, PyCellContents},
@@ -30,7 +32,11 @@ pub trait PyObjectInit<T>: Sized {
 }
 
 /// Initializer for Python native types, like `PyDict`.
-pub struct PyNativeTypeInitializer<T: PyTypeInfo>(PhantomData<T>);
+pub struct PyNativeTypeInitializer<T: PyTypeInfo> {
+    /// Positional arguments for `__new__` of the native type.
+    args: Option<Py<PyTuple>>,
+    _marker: PhantomData<T>,
+}
 
 impl<T: PyTypeInfo> PyObjectInit<T> for PyNativeTypeInitializer<T> {
     unsafe fn into_new_object(
@@ -59,7 +65,13 @@ impl<T: PyTypeInfo> PyObjectInit<T> for PyNativeTypeInitializer<T> {
             match (*type_object).tp_new {
                 // FIXME: Call __new__ with actual arguments
                 Some(newfunc) => {
-                    let obj = newfunc(subtype, std::ptr::null_mut(), std::ptr::null_mut());
+                    use crate::AsPyPointer;
+                    // Some types, like `OSError`, don't accept NULL arguments
+                    let args = match &self.args {
+                        Some(args) => args.as_ptr(),
+                        None => PyTuple::empty(py).as_ptr(),
+                    };
+                    let obj = newfunc(subtype, args, std::ptr::null_mut());
                     if obj.is_null() {
                         Err(PyErr::api_call_failed(py))
                     } else {
@@ -180,6 +192,10 @@ impl<T: PyClass> PyClassInitializer<T> {
     where
         T: PyClass,
     {
+        // checked before getting the type object, which panics in a sub-interpreter
+        if crate::gil::entered_sub_interpreter() {
+            return Err(crate::type_object::sub_interpreter_error::<T>());
+        }
         unsafe { self.create_cell_from_subtype(py, T::type_object_raw(py)) }
     }
 
@@ -197,10 +213,60 @@ impl<T: PyClass> PyClassInitializer<T> {
     where
         T: PyClass,
     {
+        if crate::gil::entered_sub_interpreter() {
+            return Err(crate::type_object::sub_interpreter_error::<T>());
+        }
         self.into_new_object(py, subtype).map(|obj| obj as _)
     }
 }
 
+impl<T> PyClassInitializer<T>
+where
+    T: PyClass,
+    T::BaseType: PyClassBaseType<Initializer = PyNativeTypeInitializer<T::BaseType>>,
+{
+    /// Sets the positional arguments passed to `__new__` of the native base class.
+    ///
+    /// This is mostly useful for classes which extend an exception type, to set the `args` of
+    /// the exception and thereby its message. When the class is called from Python, `__init__` of
+    /// the base class is called afterwards with the call arguments, and may overwrite the result.
+    /// The arguments are ignored if the base class is `PyAny`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use pyo3::prelude::*;
+    /// use pyo3::exceptions::PyValueError;
+    /// use pyo3::PyClassInitializer;
+    ///
+    /// #[pyclass(extends=PyValueError)]
+    /// struct OutOfRange {
+    ///     #[pyo3(get)]
+    ///     value: i64,
+    /// }
+    ///
+    /// fn check(value: i64) -> PyResult<()> {
+    ///     if value > 100 {
+    ///         return Python::with_gil(|py| {
+    ///             let init = PyClassInitializer::from(OutOfRange { value })
+    ///                 .with_base_args(py, (format!("{} is out of range", value),));
+    ///             Err(init.into())
+    ///         });
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let err = check(101).unwrap_err();
+    ///     assert!(err.is_instance::<PyValueError>(py));
+    ///     assert_eq!(err.to_string(), "OutOfRange: 101 is out of range");
+    /// });
+    /// ```
+    pub fn with_base_args<A: IntoPy<Py<PyTuple>>>(mut self, py: Python, args: A) -> Self {
+        self.super_init.args = Some(args.into_py(py));
+        self
+    }
+}
+
 impl<T: PyClass> PyObjectInit<T> for PyClassInitializer<T> {
     unsafe fn into_new_object(
         self,
@@ -257,7 +323,13 @@ where
 {
     #[inline]
     fn from(value: T) -> PyClassInitializer<T> {
-        Self::new(value, PyNativeTypeInitializer(PhantomData))
+        Self::new(
+            value,
+            PyNativeTypeInitializer {
+                args: None,
+                _marker: PhantomData,
+            },
+        )
     }
 }
 
@@ -273,6 +345,17 @@ where
     }
 }
 
+/// Creates an instance of the class as the exception value, for `#[pyclass]` types which extend
+/// an exception type such as `PyException`.
+impl<T: PyClass + PyExceptionType> From<PyClassInitializer<T>> for PyErr {
+    fn from(init: PyClassInitializer<T>) -> PyErr {
+        Python::with_gil(|py| match Py::new(py, init) {
+            Ok(instance) => PyErr::from_instance(instance.as_ref(py)),
+            Err(err) => err,
+        })
+    }
+}
+
 // Implementation used by proc macros to allow anything convertible to PyClassInitializer<T> to be
 // the return value of pyclass #[new] method (optionally wrapped in `Result<U, E>`).
 impl<T, U> IntoPyCallbackOutput<PyClassInitializer<T>> for U
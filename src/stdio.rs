@@ -0,0 +1,372 @@
+//! Redirecting Python's `sys.stdout` and `sys.stderr` to Rust.
+//!
+//! When Python is embedded in a Rust application, output of `print` and of tracebacks goes to the
+//! file descriptors of the process. [`Redirect`] replaces `sys.stdout` and `sys.stderr` with
+//! objects writing to a Rust [`Write`] implementation or calling a closure, and restores the
+//! original streams afterwards. [`capture`] collects the output as strings:
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//!
+//! Python::with_gil(|py| {
+//!     let (result, output) = pyo3::stdio::capture(py, || py.run("print('hello')", None, None))
+//!         .unwrap();
+//!     result.unwrap();
+//!     assert_eq!(output.stdout, "hello\n");
+//!     assert_eq!(output.stderr, "");
+//! });
+//! ```
+//!
+//! Output of code running in Python threads is redirected as well while the streams are
+//! replaced. Code writing directly to the file descriptors, such as C extensions or
+//! `os.write(1, ...)`, is not affected.
+
+use crate::exceptions::{PyRuntimeError, PyValueError};
+use crate::{pyclass, pymethods, AsPyPointer, Py, PyCell, PyObject, PyResult, Python};
+use parking_lot::{Mutex, ReentrantMutex, ReentrantMutexGuard};
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::Arc;
+
+type Callback = Box<dyn FnMut(&str) -> std::io::Result<()> + Send>;
+
+/// Where a redirected stream sends its output.
+enum Sink {
+    Writer(Box<dyn Write + Send>),
+    Callback(Callback),
+}
+
+impl Sink {
+    fn write(&mut self, text: &str) -> std::io::Result<()> {
+        match self {
+            Sink::Writer(writer) => writer.write_all(text.as_bytes()),
+            Sink::Callback(callback) => callback(text),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Sink::Writer(writer) => writer.flush(),
+            Sink::Callback(_) => Ok(()),
+        }
+    }
+}
+
+/// A sink shared between a [`RustStream`] and the writes in progress.
+///
+/// Writes don't keep the stream borrowed while the sink runs, so a sink which calls back into
+/// Python or releases the GIL doesn't make other writes fail with a borrow error.
+type SharedSink = Arc<ReentrantMutex<RefCell<Sink>>>;
+
+/// Runs `f` with exclusive access to `sink`.
+///
+/// Writing to the stream from within the sink itself raises `RuntimeError`, like a reentrant
+/// write to a buffered Python file does.
+fn with_sink<R>(
+    py: Python,
+    sink: &SharedSink,
+    f: impl FnOnce(&mut Sink) -> std::io::Result<R>,
+) -> PyResult<R> {
+    let guard = match sink.try_lock() {
+        Some(guard) => guard,
+        // Another thread is running the sink and may be waiting for the GIL
+        None => py.allow_threads(|| LockedSink(sink.lock())).0,
+    };
+    let mut sink = guard
+        .try_borrow_mut()
+        .map_err(|_| PyRuntimeError::new_err("reentrant call to a redirected stream"))?;
+    Ok(f(&mut sink)?)
+}
+
+/// A lock on a sink, which can be returned from [`Python::allow_threads`] as it runs its closure
+/// on the current thread.
+struct LockedSink<'a>(ReentrantMutexGuard<'a, RefCell<Sink>>);
+
+unsafe impl Send for LockedSink<'_> {}
+
+/// The Python object installed as `sys.stdout` or `sys.stderr` by [`Redirect`].
+///
+/// Once the redirection ends the stream is closed, and writing to it raises `ValueError` like
+/// writing to a closed file does.
+#[pyclass]
+pub struct RustStream {
+    sink: Option<SharedSink>,
+    /// The stream this one replaced.
+    original: PyObject,
+}
+
+impl RustStream {
+    fn sink(slf: &PyCell<Self>) -> PyResult<SharedSink> {
+        slf.try_borrow()?
+            .sink
+            .clone()
+            .ok_or_else(|| PyValueError::new_err("I/O operation on closed file."))
+    }
+}
+
+#[pymethods]
+impl RustStream {
+    fn write(slf: &PyCell<Self>, text: &str) -> PyResult<usize> {
+        let sink = Self::sink(slf)?;
+        with_sink(slf.py(), &sink, |sink| sink.write(text))?;
+        Ok(text.chars().count())
+    }
+
+    fn writelines(slf: &PyCell<Self>, lines: Vec<&str>) -> PyResult<()> {
+        let sink = Self::sink(slf)?;
+        with_sink(slf.py(), &sink, |sink| {
+            lines.into_iter().try_for_each(|line| sink.write(line))
+        })
+    }
+
+    fn flush(slf: &PyCell<Self>) -> PyResult<()> {
+        let sink = slf.try_borrow()?.sink.clone();
+        match sink {
+            Some(sink) => with_sink(slf.py(), &sink, Sink::flush),
+            None => Ok(()),
+        }
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn seekable(&self) -> bool {
+        false
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.sink.is_none()
+    }
+
+    #[getter]
+    fn encoding(&self) -> &'static str {
+        "utf-8"
+    }
+
+    #[getter]
+    fn errors(&self) -> &'static str {
+        "strict"
+    }
+}
+
+/// Replaces `sys.stdout` and `sys.stderr` with Rust sinks for a scope.
+///
+/// Streams without a sink are left alone.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::stdio::Redirect;
+/// use std::sync::{Arc, Mutex};
+///
+/// let console = Arc::new(Mutex::new(Vec::new()));
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let lines = console.clone();
+///     Redirect::new()
+///         .stdout_fn(move |text| {
+///             lines.lock().unwrap().push(text.to_owned());
+///             Ok(())
+///         })
+///         .stderr(std::io::sink())
+///         .run(py, || py.run("import sys; print('shown'); print('hidden', file=sys.stderr)", None, None))?
+/// })
+/// .unwrap();
+/// assert_eq!(console.lock().unwrap().concat(), "shown\n");
+/// ```
+#[derive(Default)]
+pub struct Redirect {
+    stdout: Option<Sink>,
+    stderr: Option<Sink>,
+}
+
+impl Redirect {
+    /// Creates a redirection which doesn't replace any stream yet.
+    pub fn new() -> Self {
+        Redirect::default()
+    }
+
+    /// Writes the output of `sys.stdout` to `writer`, encoded as UTF-8.
+    pub fn stdout(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.stdout = Some(Sink::Writer(Box::new(writer)));
+        self
+    }
+
+    /// Writes the output of `sys.stderr` to `writer`, encoded as UTF-8.
+    pub fn stderr(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.stderr = Some(Sink::Writer(Box::new(writer)));
+        self
+    }
+
+    /// Calls `callback` with each string written to `sys.stdout`.
+    ///
+    /// An error returned by `callback` is raised as `OSError` from the Python `write` call.
+    pub fn stdout_fn(
+        mut self,
+        callback: impl FnMut(&str) -> std::io::Result<()> + Send + 'static,
+    ) -> Self {
+        self.stdout = Some(Sink::Callback(Box::new(callback)));
+        self
+    }
+
+    /// Calls `callback` with each string written to `sys.stderr`.
+    ///
+    /// An error returned by `callback` is raised as `OSError` from the Python `write` call.
+    pub fn stderr_fn(
+        mut self,
+        callback: impl FnMut(&str) -> std::io::Result<()> + Send + 'static,
+    ) -> Self {
+        self.stderr = Some(Sink::Callback(Box::new(callback)));
+        self
+    }
+
+    /// Replaces the streams until the returned guard is dropped.
+    pub fn install<'py>(self, py: Python<'py>) -> PyResult<RedirectGuard<'py>> {
+        let mut guard = RedirectGuard {
+            py,
+            stdout: None,
+            stderr: None,
+        };
+        if let Some(sink) = self.stdout {
+            guard.stdout = Some(replace_stream(py, "stdout", sink)?);
+        }
+        if let Some(sink) = self.stderr {
+            guard.stderr = Some(replace_stream(py, "stderr", sink)?);
+        }
+        Ok(guard)
+    }
+
+    /// Runs `f` with the streams replaced, restoring the original streams afterwards, also if
+    /// `f` panics.
+    pub fn run<R>(self, py: Python, f: impl FnOnce() -> R) -> PyResult<R> {
+        let guard = self.install(py)?;
+        let result = f();
+        guard.restore()?;
+        Ok(result)
+    }
+}
+
+/// A stream replaced by [`Redirect`].
+struct Replaced {
+    name: &'static str,
+    stream: Py<RustStream>,
+}
+
+fn replace_stream(py: Python, name: &'static str, sink: Sink) -> PyResult<Replaced> {
+    let sys = py.import("sys")?;
+    let stream = Py::new(
+        py,
+        RustStream {
+            sink: Some(Arc::new(ReentrantMutex::new(RefCell::new(sink)))),
+            original: sys.getattr(name)?.into(),
+        },
+    )?;
+    sys.setattr(name, &stream)?;
+    Ok(Replaced { name, stream })
+}
+
+impl Replaced {
+    fn restore(self, py: Python) -> PyResult<()> {
+        let sys = py.import("sys")?;
+        // A stream installed after ours, e.g. by a nested redirection, is left in place
+        if sys.getattr(self.name)?.as_ptr() == self.stream.as_ptr() {
+            sys.setattr(self.name, original_stream(py, &self.stream)?)?;
+        }
+        let sink = self.stream.as_ref(py).try_borrow_mut()?.sink.take();
+        match sink {
+            Some(sink) => with_sink(py, &sink, Sink::flush),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the stream replaced by `stream`, skipping replacements which were already restored.
+///
+/// Those were left in place because `stream` was installed after them, but are closed now.
+fn original_stream(py: Python, stream: &Py<RustStream>) -> PyResult<PyObject> {
+    let mut original = stream.as_ref(py).try_borrow()?.original.clone_ref(py);
+    while let Ok(replaced) = original.as_ref(py).downcast::<PyCell<RustStream>>() {
+        let replaced = replaced.try_borrow()?;
+        if replaced.sink.is_some() {
+            break;
+        }
+        let next = replaced.original.clone_ref(py);
+        drop(replaced);
+        original = next;
+    }
+    Ok(original)
+}
+
+/// Restores the original `sys.stdout` and `sys.stderr` when dropped.
+///
+/// Returned by [`Redirect::install`]. The replacement streams are closed once restored, so
+/// Python code holding on to them can't write to the Rust sinks afterwards.
+///
+/// Guards can be dropped in any order: a stream replaced by a later redirection stays installed
+/// until that redirection ends, which then restores the stream from before both.
+pub struct RedirectGuard<'py> {
+    py: Python<'py>,
+    stdout: Option<Replaced>,
+    stderr: Option<Replaced>,
+}
+
+impl<'py> RedirectGuard<'py> {
+    /// Restores the original streams, returning the first error encountered.
+    ///
+    /// Dropping the guard does the same, but prints errors instead.
+    pub fn restore(mut self) -> PyResult<()> {
+        self.restore_streams()
+    }
+
+    fn restore_streams(&mut self) -> PyResult<()> {
+        let py = self.py;
+        let stdout = self.stdout.take().map_or(Ok(()), |s| s.restore(py));
+        let stderr = self.stderr.take().map_or(Ok(()), |s| s.restore(py));
+        stdout.and(stderr)
+    }
+}
+
+impl<'py> Drop for RedirectGuard<'py> {
+    fn drop(&mut self) {
+        if let Err(err) = self.restore_streams() {
+            err.print(self.py);
+        }
+    }
+}
+
+/// Output written to `sys.stdout` and `sys.stderr`, as collected by [`capture`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Captured {
+    /// Text written to `sys.stdout`.
+    pub stdout: String,
+    /// Text written to `sys.stderr`.
+    pub stderr: String,
+}
+
+/// Runs `f` with `sys.stdout` and `sys.stderr` redirected, returning its result along with the
+/// text written to them.
+pub fn capture<R>(py: Python, f: impl FnOnce() -> R) -> PyResult<(R, Captured)> {
+    let captured = Arc::new(Mutex::new(Captured::default()));
+    let stdout = captured.clone();
+    let stderr = captured.clone();
+    let result = Redirect::new()
+        .stdout_fn(move |text| {
+            stdout.lock().stdout.push_str(text);
+            Ok(())
+        })
+        .stderr_fn(move |text| {
+            stderr.lock().stderr.push_str(text);
+            Ok(())
+        })
+        .run(py, f)?;
+    let captured = std::mem::take(&mut *captured.lock());
+    Ok((result, captured))
+}
@@ -7,7 +7,7 @@ use crate::pyclass::create_type_object;
 use crate::pyclass::PyClass;
 use crate::types::{PyAny, PyType};
 use crate::{conversion::IntoPyPointer, PyMethodDefType};
-use crate::{ffi, AsPyPointer, PyNativeType, PyObject, PyResult, Python};
+use crate::{ffi, AsPyPointer, PyErr, PyNativeType, PyObject, PyResult, Python};
 use parking_lot::{const_mutex, Mutex};
 use std::thread::{self, ThreadId};
 
@@ -96,7 +96,21 @@ impl LazyStaticType {
     }
 
     pub fn get_or_init<T: PyClass>(&self, py: Python) -> *mut ffi::PyTypeObject {
+        // The type object is created once and shared by all interpreters, but its methods and
+        // class attributes would hand out objects of the interpreter which created it.
+        // Only `SubInterpreter::run` is checked each time, as that needs no call into Python;
+        // other sub-interpreters can only reach the class through its module, which refuses to
+        // be imported there.
+        // Creating instances and `PyModule::add_class` raise `sub_interpreter_error` before
+        // getting here, so this only panics for infallible uses like `Python::get_type`.
+        if crate::gil::entered_sub_interpreter() {
+            refuse_sub_interpreter::<T>()
+        }
+
         let type_object = *self.value.get_or_init(py, || {
+            if crate::gil::in_sub_interpreter() {
+                refuse_sub_interpreter::<T>()
+            }
             create_type_object::<T>(py, T::MODULE).unwrap_or_else(|e| {
                 e.print(py);
                 panic!("An error occurred while initializing class {}", T::NAME)
@@ -181,6 +195,25 @@ impl LazyStaticType {
     }
 }
 
+#[cold]
+fn refuse_sub_interpreter<T: PyClass>() -> ! {
+    panic!("{}", sub_interpreter_message::<T>())
+}
+
+/// The error raised when class `T` is used in a sub-interpreter.
+#[cold]
+pub(crate) fn sub_interpreter_error<T: PyClass>() -> PyErr {
+    crate::exceptions::PyRuntimeError::new_err(sub_interpreter_message::<T>())
+}
+
+fn sub_interpreter_message<T: PyClass>() -> String {
+    format!(
+        "class {} can't be used in a sub-interpreter, because PyO3 classes are shared between \
+         all interpreters",
+        T::NAME
+    )
+}
+
 fn initialize_tp_dict(
     py: Python,
     type_object: *mut ffi::PyObject,
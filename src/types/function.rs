@@ -6,6 +6,9 @@ use crate::{
     ffi, AsPyPointer,
 };
 
+#[cfg(feature = "inspect")]
+use crate::inspect;
+
 /// Represents a builtin Python function object.
 #[repr(transparent)]
 pub struct PyCFunction(PyAny);
@@ -48,6 +51,10 @@ impl PyCFunction {
         let def = method_def
             .as_method_def()
             .map_err(|err| PyValueError::new_err(err.0))?;
+        #[cfg(feature = "inspect")]
+        if let (Some(info), Some(meth)) = (method_def.info, def.ml_meth) {
+            inspect::register_function(meth as usize, info.0);
+        }
         let (mod_ptr, module_name) = if let Some(m) = module {
             let mod_ptr = m.as_ptr();
             let name = m.name()?.into_py(py);
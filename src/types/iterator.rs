@@ -29,7 +29,7 @@ use crate::{PyDowncastError, PyTryFrom};
 pub struct PyIterator(PyAny);
 pyobject_native_type_named!(PyIterator);
 #[cfg(any(not(Py_LIMITED_API), Py_3_8))]
-pyobject_native_type_extract!(PyIterator);
+pyobject_native_type_extract!(PyIterator, #type_input=crate::inspect::types::TypeInfo::builtin("typing.Iterator"));
 
 impl PyIterator {
     /// Constructs a `PyIterator` from a Python iterable object.
@@ -4,6 +4,7 @@
 
 use crate::err::{self, PyResult};
 use crate::ffi::{self, Py_ssize_t};
+use crate::inspect::types::TypeInfo;
 use crate::internal_tricks::get_ssize_index;
 use crate::types::PySequence;
 use crate::{
@@ -309,6 +310,18 @@ where
     fn into_py(self, py: Python) -> PyObject {
         unsafe { PyObject::from_owned_ptr(py, new_from_iter(self.into_iter(), |e| e.into_py(py))) }
     }
+
+    fn try_into_py(self, py: Python) -> PyResult<PyObject> {
+        let elements = self
+            .into_iter()
+            .map(|e| e.try_into_py(py))
+            .collect::<PyResult<Vec<PyObject>>>()?;
+        Ok(elements.into_py(py))
+    }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::list_of(T::type_output())
+    }
 }
 
 #[cfg(test)]
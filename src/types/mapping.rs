@@ -10,7 +10,7 @@ use crate::{PyTryFrom, ToBorrowedObject};
 #[repr(transparent)]
 pub struct PyMapping(PyAny);
 pyobject_native_type_named!(PyMapping);
-pyobject_native_type_extract!(PyMapping);
+pyobject_native_type_extract!(PyMapping, #type_input=crate::inspect::types::TypeInfo::builtin("typing.Mapping"));
 
 impl PyMapping {
     /// Returns the number of objects in the mapping.
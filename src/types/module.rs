@@ -6,6 +6,7 @@ use crate::callback::IntoPyCallbackOutput;
 use crate::err::{PyErr, PyResult};
 use crate::exceptions;
 use crate::ffi;
+use crate::module_state::{self, ModuleState};
 use crate::pyclass::PyClass;
 use crate::type_object::PyTypeObject;
 use crate::types::{PyAny, PyDict, PyList};
@@ -229,7 +230,9 @@ impl PyModule {
         self.index()?
             .append(name)
             .expect("could not append __name__ to __all__");
-        self.setattr(name, value.into_py(self.py()))
+        let value = value.into_py(self.py());
+        crate::panic::module_added(value.as_ref(self.py()));
+        self.setattr(name, value)
     }
 
     /// Adds a new class to the module.
@@ -276,6 +279,9 @@ impl PyModule {
     where
         T: PyClass,
     {
+        if crate::gil::in_sub_interpreter() {
+            return Err(crate::type_object::sub_interpreter_error::<T>());
+        }
         self.add(T::NAME, <T as PyTypeObject>::type_object(self.py()))
     }
 
@@ -377,6 +383,22 @@ impl PyModule {
         self.add(name, fun)
     }
 
+    /// Stores `state` as the state of this module.
+    ///
+    /// Only a [`#[pymodule]`](crate::pymodule) with `#[pyo3(multi_phase)]` can have state, which
+    /// is usually set once by the module's initializer. See the
+    /// [`module_state`](crate::module_state) module for an example.
+    pub fn set_state<T: ModuleState>(&self, state: T) -> PyResult<()> {
+        module_state::set_state(self.py(), self, state)
+    }
+
+    /// Returns the state of this module, as set by [`set_state`](PyModule::set_state).
+    ///
+    /// Fails if the module has no state, or if its state is not a `T`.
+    pub fn state<T: ModuleState>(&self) -> PyResult<&T> {
+        module_state::get_state(self.py(), self)
+    }
+
     /// Calls a function in the module.
     ///
     /// This is equivalent to the Python expression `module.name(*args, **kwargs)`.
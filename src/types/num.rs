@@ -2,6 +2,7 @@
 //
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
 
+use crate::inspect::types::TypeInfo;
 use crate::{
     exceptions, ffi, AsPyPointer, FromPyObject, IntoPy, PyAny, PyErr, PyObject, PyResult, Python,
     ToPyObject,
@@ -34,6 +35,10 @@ macro_rules! int_fits_larger_int {
             fn into_py(self, py: Python) -> PyObject {
                 (self as $larger_type).into_py(py)
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
 
         impl<'source> FromPyObject<'source> for $rust_type {
@@ -42,6 +47,10 @@ macro_rules! int_fits_larger_int {
                 <$rust_type>::try_from(val)
                     .map_err(|e| exceptions::PyOverflowError::new_err(e.to_string()))
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
     };
 }
@@ -68,6 +77,10 @@ macro_rules! int_fits_c_long {
             fn into_py(self, py: Python) -> PyObject {
                 unsafe { PyObject::from_owned_ptr(py, ffi::PyLong_FromLong(self as c_long)) }
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
 
         impl<'source> FromPyObject<'source> for $rust_type {
@@ -86,6 +99,10 @@ macro_rules! int_fits_c_long {
                 <$rust_type>::try_from(val)
                     .map_err(|e| exceptions::PyOverflowError::new_err(e.to_string()))
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
     };
 }
@@ -103,6 +120,10 @@ macro_rules! int_convert_u64_or_i64 {
             fn into_py(self, py: Python) -> PyObject {
                 unsafe { PyObject::from_owned_ptr(py, $pylong_from_ll_or_ull(self)) }
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
         impl<'source> FromPyObject<'source> for $rust_type {
             fn extract(ob: &'source PyAny) -> PyResult<$rust_type> {
@@ -118,6 +139,10 @@ macro_rules! int_convert_u64_or_i64 {
                     }
                 }
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::builtin("int")
+            }
         }
     };
 }
@@ -182,6 +207,10 @@ mod fast_128bit_int_conversion {
                         PyObject::from_owned_ptr(py, obj)
                     }
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::builtin("int")
+                }
             }
 
             impl<'source> FromPyObject<'source> for $rust_type {
@@ -204,6 +233,10 @@ mod fast_128bit_int_conversion {
                         Ok(<$rust_type>::from_le_bytes(buffer))
                     }
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::builtin("int")
+                }
             }
         };
     }
@@ -246,6 +279,10 @@ mod slow_128bit_int_conversion {
                         )
                     }
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::builtin("int")
+                }
             }
 
             impl<'source> FromPyObject<'source> for $rust_type {
@@ -265,6 +302,10 @@ mod slow_128bit_int_conversion {
                         Ok((<$rust_type>::from(upper) << SHIFT) | lower)
                     }
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::builtin("int")
+                }
             }
         };
     }
@@ -2,6 +2,7 @@
 
 use crate::err::{self, PyDowncastError, PyErr, PyResult};
 use crate::ffi;
+use crate::inspect::types::TypeInfo;
 use crate::internal_tricks::get_ssize_index;
 use crate::types::{PyAny, PyList, PyTuple};
 use crate::AsPyPointer;
@@ -11,7 +12,7 @@ use crate::{FromPyObject, PyTryFrom, ToBorrowedObject};
 #[repr(transparent)]
 pub struct PySequence(PyAny);
 pyobject_native_type_named!(PySequence);
-pyobject_native_type_extract!(PySequence);
+pyobject_native_type_extract!(PySequence, #type_input=crate::inspect::types::TypeInfo::builtin("typing.Sequence"));
 
 impl PySequence {
     /// Returns the number of objects in sequence.
@@ -278,6 +279,10 @@ where
     default fn extract(obj: &'a PyAny) -> PyResult<Self> {
         extract_sequence(obj)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::sequence_of(T::type_input())
+    }
 }
 
 #[cfg(feature = "nightly")]
@@ -2,6 +2,7 @@
 //
 
 use crate::err::{self, PyErr, PyResult};
+use crate::inspect::types::TypeInfo;
 #[cfg(Py_LIMITED_API)]
 use crate::types::PyIterator;
 use crate::{
@@ -235,6 +236,10 @@ where
         }
         set.into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::set_of(K::type_output())
+    }
 }
 
 impl<'source, K, S> FromPyObject<'source> for HashSet<K, S>
@@ -246,6 +251,10 @@ where
         let set: &PySet = ob.downcast()?;
         set.iter().map(K::extract).collect()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::set_of(K::type_input())
+    }
 }
 
 impl<K> IntoPy<PyObject> for BTreeSet<K>
@@ -261,6 +270,10 @@ where
         }
         set.into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::set_of(K::type_output())
+    }
 }
 
 impl<'source, K> FromPyObject<'source> for BTreeSet<K>
@@ -271,6 +284,10 @@ where
         let set: &PySet = ob.downcast()?;
         set.iter().map(K::extract).collect()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::set_of(K::type_input())
+    }
 }
 
 impl PyFrozenSet {
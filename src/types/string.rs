@@ -2,6 +2,7 @@
 
 #[cfg(not(any(Py_LIMITED_API, target_endian = "big")))]
 use crate::exceptions::PyUnicodeDecodeError;
+use crate::inspect::types::TypeInfo;
 use crate::types::PyBytes;
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, PyAny, PyObject, PyResult, PyTryFrom, Python,
@@ -277,6 +278,10 @@ impl<'a> IntoPy<PyObject> for &'a str {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 /// Converts a Rust `Cow<str>` to a Python object.
@@ -293,6 +298,10 @@ impl IntoPy<PyObject> for Cow<'_, str> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 /// Converts a Rust `String` to a Python object.
@@ -315,12 +324,20 @@ impl IntoPy<PyObject> for char {
         let mut bytes = [0u8; 4];
         PyString::new(py, self.encode_utf8(&mut bytes)).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl IntoPy<PyObject> for String {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, &self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a String {
@@ -328,6 +345,10 @@ impl<'a> IntoPy<PyObject> for &'a String {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 /// Allows extracting strings from Python objects.
@@ -336,6 +357,10 @@ impl<'source> FromPyObject<'source> for &'source str {
     fn extract(ob: &'source PyAny) -> PyResult<Self> {
         <PyString as PyTryFrom>::try_from(ob)?.to_str()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 /// Allows extracting strings from Python objects.
@@ -346,6 +371,10 @@ impl FromPyObject<'_> for String {
             .to_str()
             .map(ToOwned::to_owned)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 impl FromPyObject<'_> for char {
@@ -360,6 +389,10 @@ impl FromPyObject<'_> for char {
             ))
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::builtin("str")
+    }
 }
 
 #[cfg(test)]
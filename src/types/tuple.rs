@@ -1,6 +1,7 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::ffi::{self, Py_ssize_t};
+use crate::inspect::types::TypeInfo;
 use crate::internal_tricks::get_ssize_index;
 use crate::types::PySequence;
 use crate::{
@@ -259,6 +260,15 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
                 PyObject::from_owned_ptr(py, ptr)
             }
         }
+
+        fn try_into_py(self, py: Python) -> PyResult<PyObject> {
+            let elements = ($(IntoPy::<PyObject>::try_into_py(self.$n, py)?,)+);
+            Ok(elements.into_py(py))
+        }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::tuple_of(vec![$(<$T as IntoPy<PyObject>>::type_output()),+])
+        }
     }
 
     impl <$($T: IntoPy<PyObject>),+> IntoPy<Py<PyTuple>> for ($($T,)+) {
@@ -269,6 +279,10 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
                 Py::from_owned_ptr(py, ptr)
             }
         }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::tuple_of(vec![$(<$T as IntoPy<PyObject>>::type_output()),+])
+        }
     }
 
     impl<'s, $($T: FromPyObject<'s>),+> FromPyObject<'s> for ($($T,)+) {
@@ -285,6 +299,10 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
                 Err(wrong_tuple_length(t, $length))
             }
         }
+
+        fn type_input() -> TypeInfo {
+            TypeInfo::tuple_of(vec![$($T::type_input()),+])
+        }
     }
 });
 
@@ -0,0 +1,291 @@
+use pyo3::asyncio::{future_into_py, into_future};
+use pyo3::exceptions::asyncio::CancelledError;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::future::Future;
+use std::mem::ManuallyDrop;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+
+/// A minimal executor, which parks the current thread until the future is woken.
+fn block_on<F: Future>(future: F) -> F::Output {
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_waker);
+
+    fn raw_waker(thread: Arc<Thread>) -> RawWaker {
+        RawWaker::new(Arc::into_raw(thread) as *const (), &VTABLE)
+    }
+    unsafe fn clone(ptr: *const ()) -> RawWaker {
+        let thread = ManuallyDrop::new(Arc::from_raw(ptr as *const Thread));
+        raw_waker(Arc::clone(&thread))
+    }
+    unsafe fn wake(ptr: *const ()) {
+        Arc::from_raw(ptr as *const Thread).unpark();
+    }
+    unsafe fn wake_by_ref(ptr: *const ()) {
+        (*(ptr as *const Thread)).unpark();
+    }
+    unsafe fn drop_waker(ptr: *const ()) {
+        drop(Arc::from_raw(ptr as *const Thread));
+    }
+
+    let waker = unsafe { Waker::from_raw(raw_waker(Arc::new(thread::current()))) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Starts an event loop in a background thread, and returns the globals of the code which started
+/// it, including the loop as `event_loop`.
+fn background_event_loop<'py>(py: Python<'py>) -> &'py PyDict {
+    let globals = PyDict::new(py);
+    py.run(
+        r#"
+import asyncio
+import threading
+
+event_loop = asyncio.new_event_loop()
+threading.Thread(target=event_loop.run_forever, daemon=True).start()
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+    globals
+}
+
+fn stop(py: Python, globals: &PyDict) {
+    py.run(
+        "event_loop.call_soon_threadsafe(event_loop.stop)",
+        Some(globals),
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_into_future() {
+    Python::with_gil(|py| {
+        let globals = background_event_loop(py);
+        let event_loop = globals.get_item("event_loop").unwrap();
+        let future = into_future(
+            py.eval("asyncio.sleep(0.01, result=42)", Some(globals), None)
+                .unwrap(),
+            event_loop,
+        )
+        .unwrap();
+        let result = py.allow_threads(|| block_on(future)).unwrap();
+        assert_eq!(result.extract::<i32>(py).unwrap(), 42);
+
+        // an `asyncio.Future` of the event loop
+        let future = py
+            .eval("event_loop.create_future()", Some(globals), None)
+            .unwrap();
+        event_loop
+            .call_method1(
+                "call_soon_threadsafe",
+                (future.getattr("set_result").unwrap(), "done"),
+            )
+            .unwrap();
+        let future = into_future(future, event_loop).unwrap();
+        let result = py.allow_threads(|| block_on(future)).unwrap();
+        assert_eq!(result.extract::<&str>(py).unwrap(), "done");
+        stop(py, globals);
+
+        // the helpers don't take a module name which could be imported
+        py_assert!(
+            py,
+            *globals,
+            "not any(name.startswith(('pyo3_asyncio', 'pyo3.')) for name in __import__('sys').modules)"
+        );
+    });
+}
+
+#[test]
+fn test_into_future_error() {
+    Python::with_gil(|py| {
+        let globals = background_event_loop(py);
+        let event_loop = globals.get_item("event_loop").unwrap();
+        py.run(
+            r#"
+async def fail():
+    await asyncio.sleep(0.01)
+    raise ValueError("oops")
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+        let future =
+            into_future(py.eval("fail()", Some(globals), None).unwrap(), event_loop).unwrap();
+        let err = py.allow_threads(|| block_on(future)).unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+
+        let err = into_future(py.None().as_ref(py), event_loop).err().unwrap();
+        assert!(err.is_instance::<PyTypeError>(py));
+        stop(py, globals);
+    });
+}
+
+#[test]
+fn test_into_future_cancellation() {
+    Python::with_gil(|py| {
+        let globals = background_event_loop(py);
+        let event_loop = globals.get_item("event_loop").unwrap();
+        py.run(
+            r#"
+cancelled = threading.Event()
+
+async def wait_forever():
+    try:
+        await asyncio.sleep(1000)
+    except asyncio.CancelledError:
+        cancelled.set()
+        raise
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+
+        // dropping the Rust future cancels the coroutine
+        let future = into_future(
+            py.eval("wait_forever()", Some(globals), None).unwrap(),
+            event_loop,
+        )
+        .unwrap();
+        drop(future);
+        let cancelled = py.eval("cancelled.wait(5)", Some(globals), None).unwrap();
+        assert!(cancelled.is_true().unwrap());
+
+        // cancelling the Python future is reported to Rust
+        let future = py
+            .eval("event_loop.create_future()", Some(globals), None)
+            .unwrap();
+        event_loop
+            .call_method1("call_soon_threadsafe", (future.getattr("cancel").unwrap(),))
+            .unwrap();
+        let future = into_future(future, event_loop).unwrap();
+        let err = py.allow_threads(|| block_on(future)).unwrap_err();
+        assert!(err.is_instance::<CancelledError>(py));
+        stop(py, globals);
+    });
+}
+
+/// A future which completes after `duration`, waking its waker from another thread.
+struct Sleep {
+    duration: Duration,
+    done: Option<Arc<AtomicBool>>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match &self.done {
+            Some(done) if done.load(Ordering::SeqCst) => Poll::Ready(()),
+            Some(_) => Poll::Pending,
+            None => {
+                let done = Arc::new(AtomicBool::new(false));
+                let waker = cx.waker().clone();
+                let duration = self.duration;
+                let thread_done = done.clone();
+                thread::spawn(move || {
+                    thread::sleep(duration);
+                    thread_done.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+                self.done = Some(done);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[test]
+fn test_future_into_py() {
+    Python::with_gil(|py| {
+        let globals = background_event_loop(py);
+        let event_loop = globals.get_item("event_loop").unwrap();
+        let future = future_into_py(event_loop, async {
+            Sleep {
+                duration: Duration::from_millis(10),
+                done: None,
+            }
+            .await;
+            "slept"
+        })
+        .unwrap();
+        globals.set_item("future", future).unwrap();
+        py_assert!(py, *globals, "isinstance(future, asyncio.Future)");
+
+        // and back to Rust
+        let future = into_future(future, event_loop).unwrap();
+        let result = py.allow_threads(|| block_on(future)).unwrap();
+        assert_eq!(result.extract::<&str>(py).unwrap(), "slept");
+
+        let future = future_into_py(event_loop, async {
+            Err::<(), _>(PyValueError::new_err("oops"))
+        })
+        .unwrap();
+        let future = into_future(future, event_loop).unwrap();
+        let err = py.allow_threads(|| block_on(future)).unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+        stop(py, globals);
+    });
+}
+
+/// Records whether it was dropped.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_future_into_py_cancellation() {
+    Python::with_gil(|py| {
+        let globals = background_event_loop(py);
+        let event_loop = globals.get_item("event_loop").unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        let future = future_into_py(event_loop, async move {
+            let _flag = flag;
+            Sleep {
+                duration: Duration::from_secs(1000),
+                done: None,
+            }
+            .await;
+        })
+        .unwrap();
+        // let the task start before cancelling it
+        py.allow_threads(|| thread::sleep(Duration::from_millis(50)));
+        event_loop
+            .call_method1("call_soon_threadsafe", (future.getattr("cancel").unwrap(),))
+            .unwrap();
+        py.allow_threads(|| {
+            for _ in 0..100 {
+                if dropped.load(Ordering::SeqCst) {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+        assert!(dropped.load(Ordering::SeqCst));
+        stop(py, globals);
+    });
+}
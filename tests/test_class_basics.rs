@@ -341,3 +341,49 @@ fn test_tuple_struct_class() {
         assert_eq!(instance.borrow(py).0, 1234);
     });
 }
+
+#[pyclass(frozen)]
+struct FrozenCounter {
+    #[pyo3(get)]
+    value: usize,
+}
+
+#[pymethods]
+impl FrozenCounter {
+    fn next(&self) -> FrozenCounter {
+        FrozenCounter {
+            value: self.value + 1,
+        }
+    }
+}
+
+#[test]
+fn test_frozen_class() {
+    Python::with_gil(|py| {
+        let counter = Py::new(py, FrozenCounter { value: 0 }).unwrap();
+        py_run!(
+            py,
+            counter,
+            r#"
+        assert counter.value == 0
+        assert counter.next().next().value == 2
+        "#
+        );
+        py_expect_exception!(py, counter, "counter.value = 1", PyAttributeError);
+
+        // Frozen classes never touch the borrow flag, so any number of borrows can coexist
+        let cell = counter.as_ref(py);
+        let borrow = cell.borrow();
+        assert_eq!(cell.get().value, 0);
+        assert_eq!(borrow.value, 0);
+        assert!(cell.try_borrow().is_ok());
+    });
+}
+
+#[test]
+fn test_frozen_py_get_without_gil() {
+    let counter: Py<FrozenCounter> =
+        Python::with_gil(|py| Py::new(py, FrozenCounter { value: 42 }).unwrap());
+    assert_eq!(counter.get().value, 42);
+    Python::with_gil(|_py| drop(counter));
+}
@@ -0,0 +1,104 @@
+use pyo3::prelude::*;
+use pyo3::py_run;
+
+mod common;
+
+#[pyclass(eq)]
+#[derive(PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn test_eq() {
+    Python::with_gil(|py| {
+        let a = Py::new(py, Point { x: 1, y: 2 }).unwrap();
+        let b = Py::new(py, Point { x: 1, y: 2 }).unwrap();
+        let c = Py::new(py, Point { x: 3, y: 4 }).unwrap();
+        py_run!(
+            py,
+            a b c,
+            r#"
+        assert a == b
+        assert not (a != b)
+        assert a != c
+        assert a != 1
+        assert not (a == "a")
+        "#
+        );
+        // No ordering was requested
+        py_expect_exception!(py, a b, "a < b", PyTypeError);
+        // Defining `__eq__` without `__hash__` makes the class unhashable
+        py_expect_exception!(py, a, "hash(a)", PyTypeError);
+    });
+}
+
+#[pyclass(frozen, eq, ord, hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+#[test]
+fn test_ord_and_hash() {
+    Python::with_gil(|py| {
+        let v1_0 = Py::new(py, Version { major: 1, minor: 0 }).unwrap();
+        let v1_2 = Py::new(py, Version { major: 1, minor: 2 }).unwrap();
+        let other_v1_2 = Py::new(py, Version { major: 1, minor: 2 }).unwrap();
+        py_run!(
+            py,
+            v1_0 v1_2 other_v1_2,
+            r#"
+        assert v1_0 < v1_2
+        assert v1_0 <= v1_2
+        assert v1_2 > v1_0
+        assert v1_2 >= other_v1_2
+        assert sorted([v1_2, v1_0])[0] == v1_0
+        assert hash(v1_2) == hash(other_v1_2)
+        assert len({v1_0, v1_2, other_v1_2}) == 2
+        "#
+        );
+        py_expect_exception!(py, v1_0, "v1_0 < 1", PyTypeError);
+    });
+}
+
+#[pyclass(eq, ord)]
+#[derive(Clone, PartialEq, PartialOrd)]
+enum Priority {
+    Low,
+    High,
+}
+
+#[test]
+fn test_enum_with_derived_comparisons() {
+    Python::with_gil(|py| {
+        let priority = py.get_type::<Priority>();
+        py_assert!(py, priority, "priority.Low < priority.High");
+        py_assert!(py, priority, "priority.High == priority.High");
+    });
+}
+
+#[pyclass(eq)]
+#[derive(Clone, PartialEq)]
+enum Message {
+    Quit {},
+    Write(String),
+}
+
+#[test]
+fn test_complex_enum_eq() {
+    Python::with_gil(|py| {
+        let message = py.get_type::<Message>();
+        py_run!(
+            py,
+            message,
+            r#"
+        assert message.Write("hello") == message.Write("hello")
+        assert message.Write("hello") != message.Write("bye")
+        assert message.Write("hello") != message.Quit()
+        "#
+        );
+    });
+}
@@ -20,8 +20,10 @@ fn _test_compile_errors() {
     t.compile_fail("tests/ui/invalid_property_args.rs");
     t.compile_fail("tests/ui/invalid_pyclass_args.rs");
     t.compile_fail("tests/ui/invalid_pyfunctions.rs");
+    t.compile_fail("tests/ui/invalid_pyfunction_signatures.rs");
     t.compile_fail("tests/ui/invalid_pymethods.rs");
     t.compile_fail("tests/ui/invalid_pymethod_names.rs");
+    t.compile_fail("tests/ui/invalid_panic_policy.rs");
     t.compile_fail("tests/ui/invalid_argument_attributes.rs");
     t.compile_fail("tests/ui/reject_generics.rs");
 
@@ -47,7 +49,13 @@ fn _test_compile_errors() {
 
     #[rustversion::since(1.54)]
     fn tests_rust_1_54(t: &trybuild::TestCases) {
+        t.compile_fail("tests/ui/invalid_async_unsendable.rs");
         t.compile_fail("tests/ui/invalid_frompy_derive.rs");
+        t.compile_fail("tests/ui/invalid_frozen_pyclass_borrow.rs");
+        t.compile_fail("tests/ui/invalid_frozen_pyclass_proto.rs");
+        t.compile_fail("tests/ui/invalid_intopy_derive.rs");
+        t.compile_fail("tests/ui/invalid_panic_raise_as.rs");
+        t.compile_fail("tests/ui/invalid_pyclass_exception.rs");
         t.compile_fail("tests/ui/invalid_result_conversion.rs");
         t.compile_fail("tests/ui/pyclass_send.rs");
         t.compile_fail("tests/ui/static_ref.rs");
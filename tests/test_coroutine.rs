@@ -0,0 +1,353 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::wrap_pyfunction;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+/// A future which is pending the first time it is polled and wakes its waker immediately.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// A future which completes after `duration`, waking its waker from another thread.
+struct Sleep {
+    duration: Duration,
+    done: Option<Arc<AtomicBool>>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match &self.done {
+            Some(done) if done.load(Ordering::SeqCst) => Poll::Ready(()),
+            Some(_) => Poll::Pending,
+            None => {
+                let done = Arc::new(AtomicBool::new(false));
+                let waker = cx.waker().clone();
+                let duration = self.duration;
+                let thread_done = done.clone();
+                thread::spawn(move || {
+                    thread::sleep(duration);
+                    thread_done.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+                self.done = Some(done);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn sleep(millis: u64) -> Sleep {
+    Sleep {
+        duration: Duration::from_millis(millis),
+        done: None,
+    }
+}
+
+#[pyfunction]
+async fn add(a: i32, b: i32) -> i32 {
+    YieldNow(false).await;
+    a + b
+}
+
+#[pyfunction]
+async fn sleep_and_return(millis: u64, value: String) -> String {
+    sleep(millis).await;
+    value
+}
+
+#[pyfunction]
+async fn fail(message: String) -> PyResult<()> {
+    YieldNow(false).await;
+    Err(pyo3::exceptions::PyValueError::new_err(message))
+}
+
+#[pyfunction]
+async fn panic() {
+    panic!("panic in future")
+}
+
+/// Runs `code` with the test functions as globals, so that they are visible in coroutines.
+fn run_with(py: Python, code: &str) {
+    let globals = PyDict::new(py);
+    globals
+        .set_item("add", wrap_pyfunction!(add)(py).unwrap())
+        .unwrap();
+    globals
+        .set_item(
+            "sleep_and_return",
+            wrap_pyfunction!(sleep_and_return)(py).unwrap(),
+        )
+        .unwrap();
+    globals
+        .set_item("fail", wrap_pyfunction!(fail)(py).unwrap())
+        .unwrap();
+    globals
+        .set_item("panic", wrap_pyfunction!(panic)(py).unwrap())
+        .unwrap();
+    globals
+        .set_item("Counter", py.get_type::<Counter>())
+        .unwrap();
+    if let Err(err) = py.run(code, Some(globals), None) {
+        err.print(py);
+        panic!("{}", code);
+    }
+}
+
+#[test]
+fn test_async_function() {
+    Python::with_gil(|py| {
+        run_with(
+            py,
+            r#"
+import asyncio
+
+async def main():
+    assert await add(1, 2) == 3
+    results = await asyncio.gather(
+        sleep_and_return(50, "slow"),
+        sleep_and_return(10, "fast"),
+    )
+    assert results == ["slow", "fast"]
+
+asyncio.run(main())
+assert asyncio.run(add(40, 2)) == 42
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_async_function_error() {
+    Python::with_gil(|py| {
+        run_with(
+            py,
+            r#"
+import asyncio
+
+try:
+    asyncio.run(fail("oops"))
+except ValueError as e:
+    assert str(e) == "oops"
+else:
+    assert False
+
+coroutine = panic()
+try:
+    coroutine.send(None)
+except BaseException as e:
+    assert type(e).__name__ == "PanicException"
+else:
+    assert False
+
+# the future is dropped after panicking
+try:
+    coroutine.send(None)
+except RuntimeError:
+    pass
+else:
+    assert False
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_coroutine_protocol() {
+    Python::with_gil(|py| {
+        run_with(
+            py,
+            r#"
+import collections.abc
+
+coroutine = add(1, 2)
+assert isinstance(coroutine, collections.abc.Coroutine)
+assert coroutine.__name__ == "add"
+assert coroutine.__qualname__ == "add"
+
+# outside of an event loop, the coroutine yields `None` while the future is pending
+assert coroutine.send(None) is None
+try:
+    coroutine.send(None)
+except StopIteration as e:
+    assert e.value == 3
+else:
+    assert False
+
+try:
+    coroutine.send(None)
+except RuntimeError:
+    pass
+else:
+    assert False
+
+coroutine = add(1, 2)
+coroutine.send(None)
+try:
+    coroutine.throw(KeyError("key"))
+except KeyError:
+    pass
+else:
+    assert False
+
+# the generator signature `throw(type, value=None, traceback=None)` is supported too
+try:
+    raise ValueError("original")
+except ValueError as e:
+    tb = e.__traceback__
+
+coroutine = add(1, 2)
+try:
+    coroutine.throw(KeyError, "key", tb)
+except KeyError as e:
+    assert e.args == ("key",)
+    assert e.__traceback__.tb_next is tb
+else:
+    assert False
+
+coroutine = add(1, 2)
+try:
+    coroutine.throw(KeyError("key"), "value")
+except TypeError:
+    pass
+else:
+    assert False
+
+coroutine = add(1, 2)
+coroutine.close()
+try:
+    coroutine.send(None)
+except RuntimeError:
+    pass
+else:
+    assert False
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_async_function_cancellation() {
+    Python::with_gil(|py| {
+        run_with(
+            py,
+            r#"
+import asyncio
+
+async def main():
+    task = asyncio.ensure_future(sleep_and_return(1000, "never"))
+    await asyncio.sleep(0.01)
+    task.cancel()
+    try:
+        await task
+    except asyncio.CancelledError:
+        pass
+    else:
+        assert False
+
+asyncio.run(main())
+"#,
+        );
+    });
+}
+
+#[pyclass]
+struct Counter {
+    count: usize,
+}
+
+#[pymethods]
+impl Counter {
+    #[new]
+    fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    async fn get(&self) -> usize {
+        YieldNow(false).await;
+        self.count
+    }
+
+    async fn increment(&mut self, by: usize) -> usize {
+        YieldNow(false).await;
+        self.count += by;
+        self.count
+    }
+
+    #[staticmethod]
+    async fn double(value: usize) -> usize {
+        value * 2
+    }
+}
+
+#[test]
+fn test_async_methods() {
+    Python::with_gil(|py| {
+        run_with(
+            py,
+            r#"
+import asyncio
+
+c = Counter()
+assert c.increment(1).__qualname__ == "Counter.increment"
+
+async def main():
+    assert await c.increment(2) == 2
+    assert await c.increment(3) == 5
+    assert await c.get() == 5
+    assert await Counter.double(21) == 42
+
+    # the object stays borrowed while the coroutine is alive
+    coroutine = c.increment(1)
+    coroutine.send(None)
+    try:
+        await c.get()
+    except RuntimeError:
+        pass
+    else:
+        assert False
+    coroutine.close()
+    assert await c.get() == 5
+
+asyncio.run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+#[cfg(feature = "inspect")]
+fn test_async_function_info() {
+    use pyo3::inspect::function_info;
+    use pyo3::types::PyCFunction;
+
+    Python::with_gil(|py| {
+        let function: &PyCFunction = wrap_pyfunction!(add)(py).unwrap();
+        let info = function_info(function).unwrap();
+        assert_eq!(
+            info.return_type.to_annotation(py, "builtins").unwrap(),
+            "typing.Coroutine[typing.Any, typing.Any, int]"
+        );
+    });
+}
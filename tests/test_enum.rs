@@ -0,0 +1,322 @@
+use pyo3::class::PyObjectProtocol;
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use pyo3::{py_run, wrap_pyfunction};
+
+mod common;
+
+#[pyclass]
+#[derive(Debug, PartialEq, Clone)]
+pub enum MyEnum {
+    Variant,
+    OtherVariant,
+}
+
+#[test]
+fn test_enum_class_attr() {
+    Python::with_gil(|py| {
+        let my_enum = py.get_type::<MyEnum>();
+        let var = Py::new(py, MyEnum::Variant).unwrap();
+        py_assert!(py, my_enum var, "my_enum.Variant == var");
+    })
+}
+
+#[pyfunction]
+fn return_enum() -> MyEnum {
+    MyEnum::Variant
+}
+
+#[test]
+fn test_return_enum() {
+    Python::with_gil(|py| {
+        let f = wrap_pyfunction!(return_enum)(py).unwrap();
+        let mynum = py.get_type::<MyEnum>();
+
+        py_run!(py, f mynum, "assert f() == mynum.Variant")
+    });
+}
+
+#[pyfunction]
+fn enum_arg(e: MyEnum) {
+    assert_eq!(MyEnum::OtherVariant, e)
+}
+
+#[test]
+fn test_enum_arg() {
+    Python::with_gil(|py| {
+        let f = wrap_pyfunction!(enum_arg)(py).unwrap();
+        let mynum = py.get_type::<MyEnum>();
+
+        py_run!(py, f mynum, "f(mynum.OtherVariant)")
+    })
+}
+
+#[test]
+fn test_enum_eq_and_hash() {
+    Python::with_gil(|py| {
+        let my_enum = py.get_type::<MyEnum>();
+        py_assert!(py, my_enum, "my_enum.Variant == my_enum.Variant");
+        py_assert!(py, my_enum, "my_enum.Variant != my_enum.OtherVariant");
+        py_assert!(py, my_enum, "my_enum.Variant != 0");
+        py_assert!(
+            py,
+            my_enum,
+            "hash(my_enum.Variant) == hash(my_enum.Variant)"
+        );
+        py_assert!(
+            py,
+            my_enum,
+            "len({my_enum.Variant, my_enum.Variant, my_enum.OtherVariant}) == 2"
+        );
+    })
+}
+
+#[test]
+fn test_default_repr_and_int() {
+    Python::with_gil(|py| {
+        let var1 = Py::new(py, MyEnum::Variant).unwrap();
+        let var2 = Py::new(py, MyEnum::OtherVariant).unwrap();
+        py_assert!(py, var1, "repr(var1) == 'MyEnum.Variant'");
+        py_assert!(py, var2, "repr(var2) == 'MyEnum.OtherVariant'");
+        py_assert!(py, var1 var2, "(int(var1), int(var2)) == (0, 1)");
+    })
+}
+
+#[pyclass]
+enum CustomDiscriminant {
+    One = 1,
+    Two = 2,
+}
+
+#[test]
+fn test_custom_discriminant() {
+    Python::with_gil(|py| {
+        #[allow(non_snake_case)]
+        let CustomDiscriminant = py.get_type::<CustomDiscriminant>();
+        let one = Py::new(py, CustomDiscriminant::One).unwrap();
+        let two = Py::new(py, CustomDiscriminant::Two).unwrap();
+        py_run!(py, CustomDiscriminant one two, r#"
+        assert CustomDiscriminant.One == one
+        assert CustomDiscriminant.Two == two
+        assert one != two
+        assert int(one) == 1
+        assert int(two) == 2
+        "#);
+    })
+}
+
+#[pyclass(name = "Renamed")]
+enum RenamedEnum {
+    #[pyo3(name = "UPPERCASE")]
+    Uppercase,
+    Lowercase,
+}
+
+#[test]
+fn test_renamed_enum_and_variants() {
+    Python::with_gil(|py| {
+        let renamed = py.get_type::<RenamedEnum>();
+        let upper = Py::new(py, RenamedEnum::Uppercase).unwrap();
+        let lower = Py::new(py, RenamedEnum::Lowercase).unwrap();
+        py_run!(py, renamed upper lower, r#"
+        assert renamed.__name__ == "Renamed"
+        assert renamed.UPPERCASE == upper
+        assert renamed.Lowercase == lower
+        assert repr(upper) == "Renamed.UPPERCASE"
+        "#);
+    })
+}
+
+// Protocol methods in `#[pymethods]` aren't supported with `multiple-pymethods`
+#[cfg(not(feature = "multiple-pymethods"))]
+#[pyclass]
+enum EnumWithOverriddenRepr {
+    A,
+}
+
+#[cfg(not(feature = "multiple-pymethods"))]
+#[pymethods]
+impl EnumWithOverriddenRepr {
+    fn __repr__(&self) -> &'static str {
+        "custom repr"
+    }
+}
+
+#[cfg(not(feature = "multiple-pymethods"))]
+#[test]
+fn test_user_repr_overrides_default() {
+    Python::with_gil(|py| {
+        let a = Py::new(py, EnumWithOverriddenRepr::A).unwrap();
+        py_assert!(py, a, "repr(a) == 'custom repr'");
+    })
+}
+
+// `#[pyproto]` overrides the generated slots in all configurations
+#[pyclass]
+enum EnumWithProtoRepr {
+    A,
+}
+
+#[pyproto]
+impl PyObjectProtocol for EnumWithProtoRepr {
+    fn __repr__(&self) -> &'static str {
+        "proto repr"
+    }
+}
+
+#[test]
+fn test_user_proto_repr_overrides_default() {
+    Python::with_gil(|py| {
+        let a = Py::new(py, EnumWithProtoRepr::A).unwrap();
+        py_assert!(py, a, "repr(a) == 'proto repr'");
+    })
+}
+
+#[pyclass]
+#[derive(Debug, PartialEq, Clone)]
+pub enum Shape {
+    /// A circle.
+    Circle {
+        radius: f64,
+    },
+    Rectangle {
+        width: f64,
+        height: f64,
+    },
+    Point(i32, i32),
+    #[pyo3(name = "EMPTY")]
+    Nothing,
+}
+
+#[pyfunction]
+fn unit_circle() -> Shape {
+    Shape::Circle { radius: 1.0 }
+}
+
+#[pyfunction]
+fn shape_arg(shape: Shape) -> Shape {
+    shape
+}
+
+#[test]
+fn test_complex_enum_variant_classes() {
+    Python::with_gil(|py| {
+        let shape = py.get_type::<Shape>();
+        let unit_circle = wrap_pyfunction!(unit_circle)(py).unwrap();
+        py_run!(py, shape unit_circle, r#"
+        circle = unit_circle()
+        assert isinstance(circle, shape)
+        assert isinstance(circle, shape.Circle)
+        assert not isinstance(circle, shape.Rectangle)
+        assert issubclass(shape.Point, shape)
+        assert circle.radius == 1.0
+        assert shape.Circle.__doc__ == "A circle."
+        "#);
+    })
+}
+
+#[test]
+fn test_complex_enum_constructors() {
+    Python::with_gil(|py| {
+        let shape = py.get_type::<Shape>();
+        let shape_arg = wrap_pyfunction!(shape_arg)(py).unwrap();
+        py_run!(py, shape shape_arg, r#"
+        rect = shape.Rectangle(width=2.0, height=3.0)
+        assert (rect.width, rect.height) == (2.0, 3.0)
+        point = shape.Point(1, 2)
+        assert (point._0, point._1) == (1, 2)
+        empty = shape.EMPTY()
+        assert isinstance(shape_arg(empty), shape.EMPTY)
+        roundtrip = shape_arg(point)
+        assert type(roundtrip) is shape.Point
+        assert (roundtrip._0, roundtrip._1) == (1, 2)
+        "#);
+        py_expect_exception!(py, shape, "shape()", PyTypeError);
+    })
+}
+
+#[test]
+fn test_complex_enum_fields_are_read_only() {
+    Python::with_gil(|py| {
+        let circle = Shape::Circle { radius: 1.0 }.into_py(py);
+        py_expect_exception!(py, circle, "circle.radius = 2.0", PyAttributeError);
+    })
+}
+
+#[test]
+fn test_complex_enum_extract() {
+    Python::with_gil(|py| {
+        let shape = py.get_type::<Shape>();
+        let rect: Shape = py
+            .eval(
+                "shape.Rectangle(1.0, 2.0)",
+                None,
+                Some([("shape", shape)].into_py_dict(py)),
+            )
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(
+            rect,
+            Shape::Rectangle {
+                width: 1.0,
+                height: 2.0
+            }
+        );
+    })
+}
+
+#[test]
+fn test_complex_enum_match_args() {
+    Python::with_gil(|py| {
+        let shape = py.get_type::<Shape>();
+        py_run!(
+            py,
+            shape,
+            r#"
+        assert shape.Rectangle.__match_args__ == ("width", "height")
+        assert shape.Point.__match_args__ == ("_0", "_1")
+        assert shape.EMPTY.__match_args__ == ()
+        "#
+        );
+    })
+}
+
+#[cfg(Py_3_10)]
+#[test]
+fn test_complex_enum_pattern_matching() {
+    Python::with_gil(|py| {
+        let shape = py.get_type::<Shape>();
+        py_run!(
+            py,
+            shape,
+            r#"
+        match shape.Point(3, 4):
+            case shape.Circle(radius=r):
+                assert False
+            case shape.Point(x, y):
+                assert (x, y) == (3, 4)
+            case _:
+                assert False
+        "#
+        );
+    })
+}
+
+#[pyclass(frozen)]
+#[derive(Clone)]
+enum FrozenShape {
+    Square { side: u32 },
+}
+
+#[test]
+fn test_frozen_complex_enum() {
+    Python::with_gil(|py| {
+        let square = Py::new(py, FrozenShape::Square { side: 3 }).unwrap();
+        let FrozenShape::Square { side } = square.get();
+        assert_eq!(*side, 3);
+
+        let shape = py.get_type::<FrozenShape>();
+        py_assert!(py, shape, "shape.Square(side=4).side == 4");
+    })
+}
@@ -443,3 +443,309 @@ fn test_from_py_with() {
         assert_eq!(zap.some_object_length, 3usize);
     });
 }
+
+fn default_port() -> u16 {
+    8080
+}
+
+#[derive(Debug, FromPyObject)]
+pub struct WithDefaults {
+    #[pyo3(item)]
+    host: String,
+    #[pyo3(item, default = default_port())]
+    port: u16,
+    #[pyo3(item("tags"), default)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_default() {
+    Python::with_gil(|py| {
+        let dict = py.eval(r#"{"host": "localhost"}"#, None, None).unwrap();
+        let with_defaults = WithDefaults::extract(dict).unwrap();
+        assert_eq!(with_defaults.host, "localhost");
+        assert_eq!(with_defaults.port, 8080);
+        assert!(with_defaults.tags.is_empty());
+
+        let dict = py
+            .eval(
+                r#"{"host": "localhost", "port": 1, "tags": ["a"]}"#,
+                None,
+                None,
+            )
+            .unwrap();
+        let with_defaults = WithDefaults::extract(dict).unwrap();
+        assert_eq!(with_defaults.port, 1);
+        assert_eq!(with_defaults.tags, vec!["a"]);
+
+        // The default is only used for missing values, not for values of the wrong type
+        let dict = py
+            .eval(r#"{"host": "localhost", "port": "a"}"#, None, None)
+            .unwrap();
+        let err = WithDefaults::extract(dict).unwrap_err();
+        assert_eq!(
+            extract_traceback(py, err),
+            "TypeError: failed to extract field WithDefaults.port: TypeError: 'str' object \
+         cannot be interpreted as an integer"
+        );
+
+        // Missing attributes also use the default
+        #[derive(FromPyObject)]
+        struct AttrDefault {
+            #[pyo3(default)]
+            real: f64,
+            #[pyo3(default)]
+            missing: usize,
+        }
+        let attr_default = AttrDefault::extract(py.eval("1.5", None, None).unwrap()).unwrap();
+        assert_eq!(attr_default.real, 1.5);
+        assert_eq!(attr_default.missing, 0);
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(rename_all = "camelCase")]
+pub struct RenameAll {
+    #[pyo3(item)]
+    first_name: String,
+    #[pyo3(item)]
+    is_admin: bool,
+    #[pyo3(item("ID"))]
+    id: usize,
+}
+
+#[test]
+fn test_rename_all() {
+    Python::with_gil(|py| {
+        let dict = py
+            .eval(
+                r#"{"firstName": "Alice", "isAdmin": True, "ID": 1}"#,
+                None,
+                None,
+            )
+            .unwrap();
+        let renamed = RenameAll::extract(dict).unwrap();
+        assert_eq!(renamed.first_name, "Alice");
+        assert!(renamed.is_admin);
+        assert_eq!(renamed.id, 1);
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+pub enum RenameAllVariants {
+    #[pyo3(rename_all = "kebab-case")]
+    Kebab {
+        #[pyo3(item)]
+        kebab_value: usize,
+    },
+    #[pyo3(rename_all = "SCREAMING_SNAKE_CASE")]
+    Screaming {
+        #[pyo3(item)]
+        screaming_value: usize,
+    },
+}
+
+#[test]
+fn test_rename_all_variants() {
+    Python::with_gil(|py| {
+        let dict = py.eval(r#"{"kebab-value": 1}"#, None, None).unwrap();
+        match RenameAllVariants::extract(dict).unwrap() {
+            RenameAllVariants::Kebab { kebab_value } => assert_eq!(kebab_value, 1),
+            _ => panic!("expected `Kebab` variant"),
+        }
+        let dict = py.eval(r#"{"SCREAMING_VALUE": 2}"#, None, None).unwrap();
+        match RenameAllVariants::extract(dict).unwrap() {
+            RenameAllVariants::Screaming { screaming_value } => assert_eq!(screaming_value, 2),
+            _ => panic!("expected `Screaming` variant"),
+        }
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+pub struct Address {
+    #[pyo3(item)]
+    street: String,
+    #[pyo3(item)]
+    city: String,
+}
+
+#[derive(Debug, FromPyObject)]
+pub struct Person {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(flatten)]
+    address: Address,
+}
+
+#[test]
+fn test_flatten() {
+    Python::with_gil(|py| {
+        let dict = py
+            .eval(
+                r#"{"name": "Bob", "street": "Main Street", "city": "Springfield"}"#,
+                None,
+                None,
+            )
+            .unwrap();
+        let person = Person::extract(dict).unwrap();
+        assert_eq!(person.name, "Bob");
+        assert_eq!(person.address.street, "Main Street");
+        assert_eq!(person.address.city, "Springfield");
+
+        let dict = py
+            .eval(r#"{"name": "Bob", "street": "Main Street"}"#, None, None)
+            .unwrap();
+        let err = Person::extract(dict).unwrap_err();
+        assert_eq!(
+            extract_traceback(py, err),
+            "TypeError: failed to extract field Person.address: KeyError: 'city'"
+        );
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(tag = "kind")]
+pub enum Tagged {
+    Circle {
+        #[pyo3(item)]
+        radius: f64,
+    },
+    Rectangle {
+        #[pyo3(item)]
+        width: f64,
+        #[pyo3(item)]
+        height: f64,
+    },
+}
+
+#[pyclass]
+pub struct PyCircle {
+    #[pyo3(get)]
+    kind: String,
+    #[pyo3(get)]
+    radius: f64,
+}
+
+#[test]
+fn test_tagged_enum() {
+    Python::with_gil(|py| {
+        let dict = py
+            .eval(
+                r#"{"kind": "Rectangle", "width": 1.0, "height": 2.0}"#,
+                None,
+                None,
+            )
+            .unwrap();
+        match Tagged::extract(dict).unwrap() {
+            Tagged::Rectangle { width, height } => {
+                assert_eq!(width, 1.0);
+                assert_eq!(height, 2.0);
+            }
+            other => panic!("expected `Rectangle` variant, got {:?}", other),
+        }
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+pub struct Side {
+    #[pyo3(item)]
+    side: f64,
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(tag = "kind")]
+pub enum TaggedTupleAndNewtype {
+    Point(f64, f64),
+    Square(Side),
+}
+
+#[test]
+fn test_tagged_enum_tuple_and_newtype_variants() {
+    Python::with_gil(|py| {
+        let dict = py
+            .eval(r#"{"kind": "Point", 0: 1.0, 1: 2.0}"#, None, None)
+            .unwrap();
+        match TaggedTupleAndNewtype::extract(dict).unwrap() {
+            TaggedTupleAndNewtype::Point(x, y) => assert_eq!((x, y), (1.0, 2.0)),
+            other => panic!("expected `Point` variant, got {:?}", other),
+        }
+
+        let dict = py
+            .eval(r#"{"kind": "Square", "side": 3.0}"#, None, None)
+            .unwrap();
+        match TaggedTupleAndNewtype::extract(dict).unwrap() {
+            TaggedTupleAndNewtype::Square(Side { side }) => assert_eq!(side, 3.0),
+            other => panic!("expected `Square` variant, got {:?}", other),
+        }
+
+        let dict = py.eval(r#"{"kind": "Point", 0: 1.0}"#, None, None).unwrap();
+        let err = TaggedTupleAndNewtype::extract(dict).unwrap_err();
+        assert_eq!(
+            extract_traceback(py, err),
+            "TypeError: failed to extract field TaggedTupleAndNewtype :: Point.1: KeyError: 1"
+        );
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(tag = "kind", attribute)]
+pub enum AttributeTagged {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[test]
+fn test_attribute_tagged_enum() {
+    Python::with_gil(|py| {
+        let circle = Py::new(
+            py,
+            PyCircle {
+                kind: "Circle".into(),
+                radius: 3.0,
+            },
+        )
+        .unwrap();
+        match AttributeTagged::extract(circle.as_ref(py)).unwrap() {
+            AttributeTagged::Circle { radius } => assert_eq!(radius, 3.0),
+            other => panic!("expected `Circle` variant, got {:?}", other),
+        }
+
+        // a mapping has no `kind` attribute
+        let dict = py
+            .eval(r#"{"kind": "Circle", "radius": 1.0}"#, None, None)
+            .unwrap();
+        let err = AttributeTagged::extract(dict).unwrap_err();
+        assert_eq!(
+            extract_traceback(py, err),
+            "TypeError: failed to extract enum AttributeTagged: missing or invalid tag 'kind': \
+             AttributeError: 'dict' object has no attribute 'kind'"
+        );
+    });
+}
+
+#[test]
+fn test_tagged_enum_errors() {
+    Python::with_gil(|py| {
+        let dict = py
+            .eval(r#"{"kind": "Hexagon", "side": 1.0}"#, None, None)
+            .unwrap();
+        let err = Tagged::extract(dict).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TypeError: failed to extract enum Tagged: unknown value 'Hexagon' for tag 'kind', \
+         expected one of 'Circle', 'Rectangle'"
+        );
+
+        let dict = py.eval(r#"{"radius": 1.0}"#, None, None).unwrap();
+        let err = Tagged::extract(dict).unwrap_err();
+        assert_eq!(
+            extract_traceback(py, err),
+            "TypeError: failed to extract enum Tagged: missing or invalid tag 'kind': KeyError: 'kind'"
+        );
+
+        // Only the tagged variant is extracted, so its error is reported directly
+        let dict = py.eval(r#"{"kind": "Circle"}"#, None, None).unwrap();
+        let err = Tagged::extract(dict).unwrap_err();
+        assert_eq!(extract_traceback(py, err), "KeyError: 'radius'");
+    });
+}
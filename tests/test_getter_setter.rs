@@ -160,3 +160,70 @@ fn tuple_struct_getter_setter() {
     py_run!(py, inst, "inst.num = 20");
     py_assert!(py, inst, "inst.num == 20");
 }
+
+#[pyclass(get_all, set_all)]
+struct All {
+    /// The number of things.
+    num: i32,
+    #[pyo3(name = "label")]
+    name: String,
+    #[pyo3(skip)]
+    secret: i32,
+}
+
+#[test]
+fn get_all_and_set_all() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let inst = Py::new(
+        py,
+        All {
+            num: 1,
+            name: "one".to_owned(),
+            secret: 42,
+        },
+    )
+    .unwrap();
+
+    py_run!(
+        py,
+        inst,
+        r#"
+        assert inst.num == 1
+        assert inst.label == "one"
+        inst.num = 2
+        inst.label = "two"
+        assert (inst.num, inst.label) == (2, "two")
+        assert not hasattr(inst, "secret")
+        assert type(inst).num.__doc__ == "The number of things."
+        "#
+    );
+    assert_eq!(inst.borrow(py).num, 2);
+    assert_eq!(inst.borrow(py).secret, 42);
+}
+
+#[pyclass(get_all)]
+struct ReadOnly {
+    num: i32,
+    #[pyo3(set)]
+    writable: i32,
+}
+
+#[test]
+fn get_all_with_field_setter() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let inst = Py::new(
+        py,
+        ReadOnly {
+            num: 1,
+            writable: 2,
+        },
+    )
+    .unwrap();
+
+    py_run!(py, inst, "inst.writable = 3; assert inst.writable == 3");
+    py_expect_exception!(py, inst, "inst.num = 2", PyAttributeError);
+}
@@ -0,0 +1,106 @@
+use pyo3::importer::{MemoryImporter, ModuleCode};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+#[test]
+fn test_import_packages_and_modules() {
+    Python::with_gil(|py| {
+        MemoryImporter::new()
+            .package("mem_pkg", "from mem_pkg.sub.leaf import VALUE")
+            .package("mem_pkg.sub", "")
+            .module("mem_pkg.sub.leaf", "VALUE = 42\n")
+            .module(
+                "mem_pkg.other",
+                String::from("def fail():\n    raise ValueError('oops')\n"),
+            )
+            .install(py)
+            .unwrap();
+
+        py.run(
+            r#"
+import importlib
+import traceback
+import mem_pkg
+import mem_pkg.other
+from mem_pkg.sub import leaf
+
+assert mem_pkg.VALUE == 42
+assert mem_pkg.__file__ == "<memory>/mem_pkg/__init__.py", mem_pkg.__file__
+assert mem_pkg.__path__ == ["<memory>/mem_pkg"], mem_pkg.__path__
+assert mem_pkg.__spec__.name == "mem_pkg"
+assert mem_pkg.__spec__.submodule_search_locations == ["<memory>/mem_pkg"]
+assert mem_pkg.sub.__package__ == "mem_pkg.sub"
+assert leaf.__file__ == "<memory>/mem_pkg/sub/leaf.py"
+assert leaf.__spec__.parent == "mem_pkg.sub"
+assert leaf.__loader__.get_source("mem_pkg.sub.leaf") == "VALUE = 42\n"
+assert leaf.__loader__.is_package("mem_pkg.sub")
+assert importlib.reload(leaf).VALUE == 42
+
+try:
+    mem_pkg.other.fail()
+except ValueError as e:
+    frame = traceback.extract_tb(e.__traceback__)[-1]
+    assert frame.filename == "<memory>/mem_pkg/other.py"
+    assert frame.line == "raise ValueError('oops')"
+else:
+    assert False
+
+try:
+    import mem_pkg.missing
+except ModuleNotFoundError:
+    pass
+else:
+    assert False
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn test_import_bytecode() {
+    Python::with_gil(|py| {
+        let bytecode: &PyBytes = py
+            .eval(
+                "__import__('marshal').dumps(compile('ANSWER = 6 * 7', 'ignored', 'exec'))",
+                None,
+                None,
+            )
+            .unwrap()
+            .downcast()
+            .unwrap();
+        let finder = MemoryImporter::new()
+            .module(
+                "mem_bytecode",
+                ModuleCode::Bytecode(bytecode.as_bytes().to_vec().into()),
+            )
+            .install(py)
+            .unwrap();
+
+        let module = py.import("mem_bytecode").unwrap();
+        assert_eq!(
+            module.getattr("ANSWER").unwrap().extract::<i32>().unwrap(),
+            42
+        );
+        assert!(finder
+            .call_method1(py, "get_source", ("mem_bytecode",))
+            .unwrap()
+            .is_none(py));
+
+        // uninstalling
+        py.import("sys")
+            .unwrap()
+            .getattr("meta_path")
+            .unwrap()
+            .call_method1("remove", (finder,))
+            .unwrap();
+        py.run(
+            "import sys; del sys.modules['mem_bytecode']; __import__('mem_bytecode')",
+            None,
+            None,
+        )
+        .unwrap_err();
+    });
+}
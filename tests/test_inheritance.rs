@@ -153,8 +153,9 @@ except Exception as e:
 #[cfg(not(Py_LIMITED_API))]
 mod inheriting_native_type {
     use super::*;
-    use pyo3::exceptions::PyException;
+    use pyo3::exceptions::{PyException, PyOSError, PyValueError};
     use pyo3::types::{IntoPyDict, PyDict, PySet};
+    use pyo3::{wrap_pyfunction, PyCell, PyClassInitializer};
 
     #[pyclass(extends=PySet)]
     #[derive(Debug)]
@@ -269,6 +270,132 @@ mod inheriting_native_type {
             )
         })
     }
+
+    #[pyclass(extends=PyValueError)]
+    struct ParseError {
+        #[pyo3(get)]
+        line: usize,
+        column: usize,
+    }
+
+    #[pymethods]
+    impl ParseError {
+        #[new]
+        fn new(line: usize, column: usize) -> Self {
+            ParseError { line, column }
+        }
+
+        fn position(&self) -> (usize, usize) {
+            (self.line, self.column)
+        }
+    }
+
+    #[pyfunction]
+    fn parse(source: &str) -> PyResult<()> {
+        Python::with_gil(|py| {
+            let init = PyClassInitializer::from(ParseError { line: 3, column: 7 })
+                .with_base_args(py, (format!("unexpected token in {:?}", source),));
+            Err(init.into())
+        })
+    }
+
+    #[test]
+    fn raise_custom_exception_from_rust() {
+        Python::with_gil(|py| {
+            let err = parse("").unwrap_err();
+            assert!(err.is_instance::<ParseError>(py));
+            assert!(err.is_instance::<PyValueError>(py));
+            let value: &PyCell<ParseError> = err.instance(py).downcast().unwrap();
+            assert_eq!(value.borrow().column, 7);
+
+            // an existing instance can be raised with `PyErr::from_instance`
+            let instance = PyCell::new(py, ParseError::new(5, 8)).unwrap();
+            let err = PyErr::from_instance(instance);
+            assert!(err.is_instance::<ParseError>(py));
+            assert_eq!(err.to_string(), "ParseError: ");
+
+            let cls = py.get_type::<ParseError>();
+            let parse = wrap_pyfunction!(parse)(py).unwrap();
+            py_run!(
+                py,
+                cls parse,
+                r#"
+                try:
+                    parse("1 +")
+                except ValueError as e:
+                    assert type(e) is cls
+                    assert str(e) == 'unexpected token in "1 +"'
+                    assert e.line == 3
+                    assert e.position() == (3, 7)
+                else:
+                    assert False
+
+                e = cls(1, 2)
+                assert e.args == (1, 2)
+                assert e.position() == (1, 2)
+                "#
+            );
+        })
+    }
+
+    #[pyclass(extends=PyOSError)]
+    struct StorageError {
+        #[pyo3(get)]
+        device: String,
+    }
+
+    #[pymethods]
+    impl StorageError {
+        #[new]
+        fn new(device: String) -> Self {
+            StorageError { device }
+        }
+    }
+
+    #[test]
+    fn custom_exception_with_larger_base_layout() {
+        Python::with_gil(|py| {
+            let init = PyClassInitializer::from(StorageError {
+                device: "sda".to_string(),
+            })
+            .with_base_args(py, (28, "No space left on device", "data.bin"));
+            let err = PyErr::from(init);
+            assert!(err.is_instance::<PyOSError>(py));
+            let e = err.instance(py);
+            py_run!(
+                py,
+                e,
+                r#"
+                assert e.errno == 28
+                assert e.strerror == "No space left on device"
+                assert e.filename == "data.bin"
+                assert e.device == "sda"
+                "#
+            );
+        })
+    }
+
+    #[test]
+    fn construct_exception_without_base_args() {
+        Python::with_gil(|py| {
+            // `OSError.__new__` needs an argument tuple, even if empty
+            let e = Py::new(py, StorageError::new("sdb".to_string())).unwrap();
+            let cls = py.get_type::<StorageError>();
+            py_run!(
+                py,
+                e cls,
+                r#"
+                assert e.device == "sdb"
+                assert e.args == ()
+                assert e.errno is None
+
+                e = cls("sdc")
+                assert isinstance(e, OSError)
+                assert e.device == "sdc"
+                "#
+            );
+        })
+    }
 }
 
 #[pyclass(subclass)]
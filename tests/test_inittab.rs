@@ -0,0 +1,51 @@
+#![cfg(not(PyPy))]
+
+use pyo3::interpreter::InitializationError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+
+#[pyfunction]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pymodule]
+fn embedded_host(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(double, m)?)
+}
+
+#[pymodule]
+#[pyo3(name = "renamed_host")]
+fn rust_name(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("answer", 42)
+}
+
+// Modules must be registered before the interpreter is initialized, so everything is checked in
+// one test.
+#[test]
+fn test_append_to_inittab() {
+    pyo3::append_to_inittab!(embedded_host).unwrap();
+    pyo3::append_to_inittab!(renamed_host).unwrap();
+
+    Python::with_gil(|py| {
+        py.run(
+            r#"
+import sys
+import embedded_host
+import renamed_host
+
+assert "embedded_host" in sys.builtin_module_names
+assert embedded_host.double(21) == 42
+assert renamed_host.answer == 42
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+    });
+
+    assert_eq!(
+        pyo3::append_to_inittab!(embedded_host),
+        Err(InitializationError::AlreadyInitialized)
+    );
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "inspect")]
+
+use pyo3::inspect::stubs::{module_stub, write_stub_package};
+use pyo3::inspect::types::TypeInfo;
+use pyo3::inspect::{class_info, function_info, AttributeKind, FunctionKind, ParameterKind};
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use pyo3::{wrap_pyfunction, wrap_pymodule};
+use std::collections::HashMap;
+
+mod common;
+
+/// A point in the plane.
+#[pyclass(module = "geometry")]
+#[derive(Clone)]
+struct Point {
+    #[pyo3(get, set)]
+    x: f64,
+    #[pyo3(get)]
+    y: f64,
+}
+
+#[pymethods]
+impl Point {
+    #[new]
+    #[pyo3(signature = (x, y = 0.0))]
+    fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    /// Distance to another point.
+    fn distance(&self, other: PyRef<Self>) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+
+    fn scaled(&self, factor: Option<f64>) -> PyResult<Self> {
+        let factor = factor.unwrap_or(1.0);
+        Ok(Point {
+            x: self.x * factor,
+            y: self.y * factor,
+        })
+    }
+
+    #[classattr]
+    const DIMENSIONS: usize = 2;
+
+    #[classmethod]
+    fn origin(_cls: &pyo3::types::PyType) -> Self {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    #[staticmethod]
+    fn names() -> Vec<&'static str> {
+        vec!["x", "y"]
+    }
+
+    #[getter]
+    fn norm(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (points, /, *, closed = false))]
+fn perimeter(points: Vec<Point>, closed: bool) -> f64 {
+    let _ = closed;
+    points.len() as f64
+}
+
+#[pyfunction]
+fn describe(values: HashMap<String, (i32, Option<String>)>, extra: &PyAny) -> String {
+    let _ = extra;
+    format!("{}", values.len())
+}
+
+#[pyfunction(args = "*", kwargs = "**")]
+fn anything(args: &PyTuple, kwargs: Option<&PyDict>) {
+    let _ = (args, kwargs);
+}
+
+#[pymodule]
+fn shapes(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(anything, m)?)?;
+    Ok(())
+}
+
+#[pymodule]
+fn geometry(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Point>()?;
+    m.add_function(wrap_pyfunction!(perimeter, m)?)?;
+    m.add_function(wrap_pyfunction!(describe, m)?)?;
+    m.add("PRECISION", 1e-9)?;
+    let shapes = wrap_pymodule!(shapes)(py);
+    shapes.as_ref(py).setattr("__name__", "geometry.shapes")?;
+    m.add("shapes", shapes)?;
+    // an imported module rather than a submodule
+    m.add("math", py.import("math")?)?;
+    Ok(())
+}
+
+#[test]
+fn test_function_info() {
+    Python::with_gil(|py| {
+        let function: &PyCFunction = wrap_pyfunction!(perimeter)(py).unwrap();
+        let info = function_info(function).unwrap();
+        assert_eq!(info.name, "perimeter");
+        assert_eq!(info.kind, FunctionKind::Function);
+        assert_eq!(info.parameters.len(), 2);
+        assert_eq!(info.parameters[0].kind, ParameterKind::PositionalOnly);
+        assert_eq!(info.parameters[1].kind, ParameterKind::KeywordOnly);
+        assert_eq!(info.parameters[1].default, Some("False"));
+        assert_eq!(
+            info.parameters[0]
+                .annotation
+                .to_annotation(py, "geometry")
+                .unwrap(),
+            "typing.Sequence[Point]"
+        );
+        assert_eq!(
+            info.return_type.to_annotation(py, "geometry").unwrap(),
+            "float"
+        );
+    });
+}
+
+#[test]
+fn test_class_info() {
+    Python::with_gil(|py| {
+        let info = class_info(py.get_type::<Point>()).unwrap();
+        assert_eq!(info.name, "Point");
+        assert!(info.base.is_none());
+        assert_eq!(info.new.unwrap().parameters.len(), 2);
+        let x = info
+            .attributes
+            .iter()
+            .find(|attr| attr.name == "x")
+            .unwrap();
+        assert_eq!(
+            x.kind,
+            AttributeKind::Property {
+                readable: true,
+                writable: true
+            }
+        );
+        let methods: Vec<_> = info.methods.iter().map(|method| method.name).collect();
+        assert_eq!(methods, ["distance", "scaled", "origin", "names"]);
+    });
+}
+
+#[test]
+fn test_type_info() {
+    Python::with_gil(|py| {
+        let annotation = |info: TypeInfo| info.to_annotation(py, "builtins").unwrap();
+        assert_eq!(
+            annotation(<Vec<u8> as FromPyObject>::type_input()),
+            "typing.Sequence[int]"
+        );
+        assert_eq!(
+            annotation(<Vec<String> as IntoPy<PyObject>>::type_output()),
+            "typing.List[str]"
+        );
+        assert_eq!(
+            annotation(<(bool, Option<&str>) as FromPyObject>::type_input()),
+            "typing.Tuple[bool, typing.Optional[str]]"
+        );
+        assert_eq!(annotation(<&PyDict as FromPyObject>::type_input()), "dict");
+        assert_eq!(
+            annotation(<&PyAny as FromPyObject>::type_input()),
+            "typing.Any"
+        );
+        assert_eq!(
+            annotation(<PyObject as IntoPy<PyObject>>::type_output()),
+            "typing.Any"
+        );
+        assert_eq!(
+            annotation(<Point as IntoPy<PyObject>>::type_output()),
+            "geometry.Point"
+        );
+    });
+}
+
+const GEOMETRY_STUB: &str = r#"import types
+import typing
+from . import shapes
+
+class Point:
+    """A point in the plane."""
+    DIMENSIONS: typing.ClassVar[int]
+    @property
+    def norm(self) -> float: ...
+    x: float
+    @property
+    def y(self) -> float: ...
+    def __new__(cls, x: float, y: float = 0.0) -> Point: ...
+    def distance(self, other: Point) -> float:
+        """Distance to another point."""
+        ...
+    def scaled(self, factor: typing.Optional[float] = None) -> Point: ...
+    @classmethod
+    def origin(cls) -> Point: ...
+    @staticmethod
+    def names() -> typing.List[str]: ...
+
+def perimeter(points: typing.Sequence[Point], /, *, closed: bool = False) -> float: ...
+
+def describe(values: typing.Dict[str, typing.Tuple[int, typing.Optional[str]]], extra: typing.Any) -> str: ...
+
+PRECISION: float
+
+math: types.ModuleType
+"#;
+
+#[test]
+fn test_module_stub() {
+    Python::with_gil(|py| {
+        let module = wrap_pymodule!(geometry)(py);
+        let module: &PyModule = module.cast_as(py).unwrap();
+        assert_eq!(module_stub(module).unwrap(), GEOMETRY_STUB);
+    });
+}
+
+#[test]
+fn test_write_stub_package() {
+    Python::with_gil(|py| {
+        let module = wrap_pymodule!(geometry)(py);
+        let module: &PyModule = module.cast_as(py).unwrap();
+        let directory = std::env::temp_dir().join("pyo3_test_inspect_geometry");
+        write_stub_package(module, &directory).unwrap();
+
+        let read = |name| std::fs::read_to_string(directory.join(name)).unwrap();
+        assert_eq!(read("__init__.pyi"), GEOMETRY_STUB);
+        assert_eq!(read("py.typed"), "");
+        assert_eq!(
+            read("shapes.pyi"),
+            "import typing\n\ndef anything(*args: typing.Any, **kwargs: typing.Any) -> None: ...\n"
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    });
+}
@@ -0,0 +1,47 @@
+#![cfg(all(Py_3_8, not(Py_3_12), not(PyPy), not(Py_LIMITED_API)))]
+
+use pyo3::interpreter::{InitializationError, InterpreterBuilder};
+use pyo3::prelude::*;
+
+// The interpreter can only be initialized once per process, so everything is checked in one test.
+#[test]
+fn test_interpreter_builder() {
+    assert_eq!(
+        InterpreterBuilder::new().argv(vec!["nul\0"]).initialize(),
+        Err(InitializationError::NulByte { setting: "argv" })
+    );
+
+    let result = InterpreterBuilder::new()
+        .program_name("embedded")
+        .argv(vec!["embedded", "--flag", "-c"])
+        .isolated(true)
+        .site_import(false)
+        .utf8_mode(true)
+        .path("/nonexistent/pyo3/scripts")
+        .initialize();
+    assert_eq!(result, Ok(()));
+
+    Python::with_gil(|py| {
+        py.run(
+            r#"
+import sys
+assert sys.argv == ["embedded", "--flag", "-c"], sys.argv
+assert sys.flags.isolated == 1
+assert sys.flags.no_site == 1
+assert sys.flags.utf8_mode == 1
+assert "site" not in sys.modules
+assert sys.path[-1] == "/nonexistent/pyo3/scripts", sys.path
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+    });
+
+    assert_eq!(
+        InterpreterBuilder::new().initialize(),
+        Err(InitializationError::AlreadyInitialized)
+    );
+    // initializing again is harmless
+    pyo3::prepare_freethreaded_python();
+}
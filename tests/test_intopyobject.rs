@@ -0,0 +1,325 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::wrap_pyfunction;
+
+#[macro_use]
+mod common;
+
+#[derive(IntoPyObject)]
+pub struct A<'a> {
+    #[pyo3(item)]
+    s: String,
+    #[pyo3(item("renamed"))]
+    t: &'a str,
+    #[pyo3(item(0))]
+    p: Option<i32>,
+}
+
+#[test]
+fn test_named_fields_struct() {
+    Python::with_gil(|py| {
+        let a = A {
+            s: "foo".into(),
+            t: "bar",
+            p: None,
+        };
+        let obj = a.into_py(py);
+        let dict: &PyDict = obj.extract(py).unwrap();
+        assert_eq!(dict.len(), 3);
+        py_assert!(py, obj, "obj == {'s': 'foo', 'renamed': 'bar', 0: None}");
+    });
+}
+
+#[derive(Clone, Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub struct B {
+    s: String,
+    #[pyo3(attribute("foo"))]
+    p: Option<i32>,
+}
+
+#[test]
+fn test_attribute_fields_struct() {
+    Python::with_gil(|py| {
+        let b = B {
+            s: "foo".into(),
+            p: Some(1),
+        };
+        let obj = b.clone().into_py(py);
+        py_assert!(py, obj, "type(obj).__name__ == 'SimpleNamespace'");
+        py_assert!(py, obj, "vars(obj) == {'s': 'foo', 'foo': 1}");
+        let extracted: B = obj.extract(py).unwrap();
+        assert_eq!(extracted, b);
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Tuple(String, usize);
+
+#[test]
+fn test_tuple_struct() {
+    Python::with_gil(|py| {
+        let tup = Tuple("test".into(), 1).into_py(py);
+        py_assert!(py, tup, "tup == ('test', 1)");
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct TransparentTuple(String);
+
+#[derive(IntoPyObject)]
+#[pyo3(transparent)]
+pub struct TransparentStruct {
+    inner: Vec<u8>,
+}
+
+#[test]
+fn test_transparent() {
+    Python::with_gil(|py| {
+        let tup = TransparentTuple("test".into()).into_py(py);
+        py_assert!(py, tup, "tup == 'test'");
+        let st = TransparentStruct {
+            inner: vec![1, 2, 3],
+        }
+        .into_py(py);
+        py_assert!(py, st, "st == [1, 2, 3]");
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Generic<T> {
+    #[pyo3(item)]
+    value: T,
+}
+
+#[test]
+fn test_generic() {
+    Python::with_gil(|py| {
+        let obj = Generic { value: (1, "a") }.into_py(py);
+        py_assert!(py, obj, "obj == {'value': (1, 'a')}");
+    });
+}
+
+#[derive(IntoPyObject)]
+pub enum Foo<'a> {
+    TupleVar(usize, String),
+    StructVar {
+        test: &'a str,
+    },
+    #[pyo3(transparent)]
+    TransparentTuple(usize),
+    #[pyo3(transparent)]
+    TransparentStructVar {
+        a: Option<String>,
+    },
+}
+
+#[test]
+fn test_enum() {
+    Python::with_gil(|py| {
+        let tuple_var = Foo::TupleVar(1, "test".into()).into_py(py);
+        py_assert!(py, tuple_var, "tuple_var == (1, 'test')");
+        let struct_var = Foo::StructVar { test: "test" }.into_py(py);
+        py_assert!(py, struct_var, "vars(struct_var) == {'test': 'test'}");
+        let transparent_tuple = Foo::TransparentTuple(1).into_py(py);
+        py_assert!(py, transparent_tuple, "transparent_tuple == 1");
+        let transparent_struct = Foo::TransparentStructVar { a: None }.into_py(py);
+        py_assert!(py, transparent_struct, "transparent_struct is None");
+    });
+}
+
+#[derive(Clone, Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub struct RoundTrip {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(item("id"))]
+    identifier: u32,
+}
+
+#[test]
+fn test_round_trip() {
+    Python::with_gil(|py| {
+        let original = RoundTrip {
+            name: "test".into(),
+            identifier: 3,
+        };
+        let obj = original.clone().into_py(py);
+        let extracted: RoundTrip = obj.extract(py).unwrap();
+        assert_eq!(extracted, original);
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Address {
+    #[pyo3(item)]
+    street_name: String,
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(rename_all = "camelCase")]
+pub struct Person {
+    #[pyo3(item)]
+    first_name: String,
+    #[pyo3(flatten)]
+    address: Address,
+}
+
+#[test]
+fn test_rename_all_and_flatten() {
+    Python::with_gil(|py| {
+        let person = Person {
+            first_name: "Bob".into(),
+            address: Address {
+                street_name: "Main Street".into(),
+            },
+        }
+        .into_py(py);
+        py_assert!(
+            py,
+            person,
+            "person == {'firstName': 'Bob', 'street_name': 'Main Street'}"
+        );
+    });
+}
+
+#[derive(Clone, Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub struct Sprite {
+    name: String,
+    #[pyo3(flatten)]
+    position: Position,
+}
+
+#[test]
+fn test_flatten_attributes() {
+    Python::with_gil(|py| {
+        let sprite = Sprite {
+            name: "ship".into(),
+            position: Position { x: 1, y: 2 },
+        };
+        let obj = sprite.clone().into_py(py);
+        py_assert!(py, obj, "vars(obj) == {'name': 'ship', 'x': 1, 'y': 2}");
+        let extracted: Sprite = obj.extract(py).unwrap();
+        assert_eq!(extracted, sprite);
+    });
+}
+
+#[derive(Debug, PartialEq, FromPyObject, IntoPyObject)]
+#[pyo3(tag = "kind")]
+pub enum Tagged {
+    Circle {
+        #[pyo3(item)]
+        radius: f64,
+    },
+    Square {
+        #[pyo3(item)]
+        side: f64,
+    },
+}
+
+#[test]
+fn test_tagged_enum() {
+    Python::with_gil(|py| {
+        let square = Tagged::Square { side: 2.0 }.into_py(py);
+        py_assert!(py, square, "square == {'kind': 'Square', 'side': 2.0}");
+        let extracted: Tagged = square.extract(py).unwrap();
+        assert_eq!(extracted, Tagged::Square { side: 2.0 });
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Drawing {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(flatten)]
+    shape: Tagged,
+}
+
+#[test]
+fn test_flatten_tagged_enum() {
+    Python::with_gil(|py| {
+        let drawing = Drawing {
+            name: "dot".into(),
+            shape: Tagged::Circle { radius: 1.0 },
+        }
+        .into_py(py);
+        py_assert!(
+            py,
+            drawing,
+            "drawing == {'name': 'dot', 'kind': 'Circle', 'radius': 1.0}"
+        );
+    });
+}
+
+#[derive(Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub struct Side {
+    #[pyo3(item)]
+    side: f64,
+}
+
+#[derive(Debug, PartialEq, FromPyObject, IntoPyObject)]
+#[pyo3(tag = "kind")]
+pub enum TaggedTupleAndNewtype {
+    Point(f64, f64),
+    Square(Side),
+}
+
+#[test]
+fn test_tagged_tuple_and_newtype_variants() {
+    Python::with_gil(|py| {
+        let point = TaggedTupleAndNewtype::Point(1.0, 2.0).into_py(py);
+        py_assert!(py, point, "point == {'kind': 'Point', 0: 1.0, 1: 2.0}");
+        let extracted: TaggedTupleAndNewtype = point.extract(py).unwrap();
+        assert_eq!(extracted, TaggedTupleAndNewtype::Point(1.0, 2.0));
+
+        let square = TaggedTupleAndNewtype::Square(Side { side: 3.0 }).into_py(py);
+        py_assert!(py, square, "square == {'kind': 'Square', 'side': 3.0}");
+        let extracted: TaggedTupleAndNewtype = square.extract(py).unwrap();
+        assert_eq!(extracted, TaggedTupleAndNewtype::Square(Side { side: 3.0 }));
+    });
+}
+
+pub struct Unconvertible;
+
+impl IntoPy<PyObject> for Unconvertible {
+    fn into_py(self, py: Python) -> PyObject {
+        self.try_into_py(py).unwrap()
+    }
+
+    fn try_into_py(self, _py: Python) -> PyResult<PyObject> {
+        Err(PyValueError::new_err("unconvertible"))
+    }
+}
+
+#[derive(IntoPyObject)]
+pub struct Wrapper {
+    #[pyo3(item)]
+    value: Unconvertible,
+}
+
+#[pyfunction]
+fn make_wrapper() -> Wrapper {
+    Wrapper {
+        value: Unconvertible,
+    }
+}
+
+#[test]
+fn test_conversion_error_is_raised() {
+    Python::with_gil(|py| {
+        let err = Wrapper {
+            value: Unconvertible,
+        }
+        .try_into_py(py)
+        .unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+
+        let make_wrapper = wrap_pyfunction!(make_wrapper)(py).unwrap();
+        py_expect_exception!(py, make_wrapper, "make_wrapper()", PyValueError);
+    });
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "log")]
+
+use log::LevelFilter;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+#[test]
+fn test_logging() {
+    let handle = pyo3::logging::Logger::new()
+        .filter(LevelFilter::Debug)
+        .filter_target("test_logging::quiet", LevelFilter::Error)
+        .install()
+        .unwrap();
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let globals = PyDict::new(py);
+    py.run(
+        r#"
+import logging
+
+records = []
+
+class Collect(logging.Handler):
+    def emit(self, record):
+        records.append(record)
+
+logger = logging.getLogger("test_logging")
+logger.addHandler(Collect())
+logger.setLevel(logging.INFO)
+logger.propagate = False
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+
+    let line = line!() + 1;
+    log::info!("hello {}", "python");
+    log::debug!("below the python level");
+    log::warn!(target: "test_logging::quiet", "below the rust filter");
+    log::error!(target: "test_logging::quiet", "error");
+    log::warn!(target: "test_logging::quieter", "not nested below quiet");
+
+    globals.set_item("line", line).unwrap();
+    py.run(
+        r#"
+assert [(r.name, r.levelname, r.getMessage()) for r in records] == [
+    ("test_logging", "INFO", "hello python"),
+    ("test_logging.quiet", "ERROR", "error"),
+    ("test_logging.quieter", "WARNING", "not nested below quiet"),
+], records
+assert records[0].pathname.endswith("test_logging.rs")
+assert records[0].lineno == line
+records.clear()
+logger.setLevel(logging.DEBUG)
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+
+    // the cached level hides the change until the cache is reset
+    log::debug!("still filtered");
+    handle.reset();
+    log::debug!("debug");
+    log::trace!("below the rust filter");
+    log::logger().flush();
+
+    py.run(
+        r#"
+assert [(r.levelname, r.getMessage()) for r in records] == [("DEBUG", "debug")], records
+records.clear()
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+
+    // records logged without the GIL acquire it and are emitted right away
+    py.allow_threads(|| {
+        log::info!("without the gil");
+        std::thread::spawn(|| log::info!("from another thread"))
+            .join()
+            .unwrap();
+    });
+    py.run(
+        r#"
+assert [r.getMessage() for r in records] == ["without the gil", "from another thread"], records
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+
+    // records logged inside a sub-interpreter are queued until the next record in the main one
+    #[cfg(not(PyPy))]
+    {
+        let sub = pyo3::interpreter::SubInterpreter::new(py).unwrap();
+        unsafe { sub.run(py, |_| log::info!("from a sub-interpreter")) };
+        py.run("assert len(records) == 2, records", Some(globals), None)
+            .unwrap();
+        log::logger().flush();
+        py.run(
+            r#"
+assert [r.getMessage() for r in records[2:]] == ["from a sub-interpreter"], records
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+    }
+}
@@ -410,6 +410,74 @@ fn meth_args() {
     py_run!(py, inst, "assert inst.args_as_vec(1,2,3) == 6");
 }
 
+#[pyclass]
+struct MethSignature {
+    base: i32,
+}
+
+#[pymethods]
+impl MethSignature {
+    #[new]
+    #[pyo3(signature = (base = 10))]
+    fn new(base: i32) -> Self {
+        MethSignature { base }
+    }
+
+    #[pyo3(signature = (a, /, b = 2, *, c = None))]
+    fn get_pos_only(&self, a: i32, b: i32, c: Option<i32>) -> i32 {
+        self.base + a + b + c.unwrap_or(0)
+    }
+
+    #[pyo3(signature = (*args, **kwargs))]
+    fn get_args_kwargs(&self, py: Python, args: &PyTuple, kwargs: Option<&PyDict>) -> PyObject {
+        [args.into(), kwargs.to_object(py)].to_object(py)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (x, y = "default".to_owned()))]
+    fn static_with_signature(x: i32, y: String) -> String {
+        format!("{} {}", x, y)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (value = Some(3)))]
+    fn class_with_signature(_cls: &PyType, value: Option<i32>) -> Option<i32> {
+        value
+    }
+}
+
+#[test]
+fn meth_signature() {
+    Python::with_gil(|py| {
+        let cls = py.get_type::<MethSignature>();
+        py_run!(
+            py,
+            cls,
+            r#"
+        inst = cls()
+        assert inst.get_pos_only(1) == 13
+        assert inst.get_pos_only(1, 3, c=4) == 18
+        assert inst.get_pos_only(1, b=3) == 14
+        assert cls(0).get_pos_only(1) == 3
+        assert inst.get_args_kwargs(1, a=2) == [(1,), {'a': 2}]
+        assert cls.static_with_signature(1) == '1 default'
+        assert cls.static_with_signature(1, 'y') == '1 y'
+        assert cls.class_with_signature() == 3
+        assert cls.class_with_signature(None) is None
+        "#
+        );
+        let inst = Py::new(py, MethSignature { base: 0 }).unwrap();
+        py_expect_exception!(
+            py,
+            inst,
+            "inst.get_pos_only(a=1)",
+            PyTypeError,
+            "MethSignature.get_pos_only() got some positional-only arguments passed as keyword arguments: 'a'"
+        );
+        py_expect_exception!(py, inst, "inst.get_pos_only(1, 2, 3)", PyTypeError);
+    });
+}
+
 #[pyclass]
 /// A class with "documentation".
 struct MethDocs {
@@ -0,0 +1,198 @@
+use pyo3::class::gc::{PyTraverseError, PyVisit};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+use pyo3::module_state::ModuleState;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+mod common;
+
+struct Counter {
+    count: AtomicUsize,
+}
+
+impl ModuleState for Counter {}
+
+#[pyfunction]
+#[pyo3(pass_module)]
+fn increment(module: &PyModule) -> PyResult<usize> {
+    Ok(module
+        .state::<Counter>()?
+        .count
+        .fetch_add(1, Ordering::SeqCst)
+        + 1)
+}
+
+#[pymodule]
+#[pyo3(multi_phase)]
+fn counter(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.set_state(Counter {
+        count: AtomicUsize::new(0),
+    })?;
+    m.add_function(wrap_pyfunction!(increment, m)?)
+}
+
+#[test]
+fn test_module_state_per_module() {
+    Python::with_gil(|py| {
+        let first = pyo3::wrap_pymodule!(counter)(py);
+        let second = pyo3::wrap_pymodule!(counter)(py);
+        py_assert!(py, first, "first.__name__ == 'counter'");
+        py_assert!(py, first, "first.increment() == 1");
+        py_assert!(py, first, "first.increment() == 2");
+        py_assert!(py, second, "second.increment() == 1");
+    });
+}
+
+#[test]
+fn test_module_state_errors() {
+    #[pymodule]
+    fn single_phase(_py: Python, _m: &PyModule) -> PyResult<()> {
+        Ok(())
+    }
+
+    struct Other;
+    impl ModuleState for Other {}
+
+    Python::with_gil(|py| {
+        let m = pyo3::wrap_pymodule!(single_phase)(py);
+        let m: &PyModule = m.extract(py).unwrap();
+        let err = m.set_state(Other).unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+
+        let m = pyo3::wrap_pymodule!(counter)(py);
+        let m: &PyModule = m.extract(py).unwrap();
+        let err = m.state::<Other>().map(drop).unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+        let err = m.set_state(Other).unwrap_err();
+        assert!(err.is_instance::<PyRuntimeError>(py));
+        assert_eq!(
+            m.state::<Counter>().unwrap().count.load(Ordering::SeqCst),
+            0
+        );
+    });
+}
+
+static DROPPED: AtomicBool = AtomicBool::new(false);
+
+struct Cyclic {
+    module: Mutex<Option<PyObject>>,
+}
+
+impl ModuleState for Cyclic {
+    fn traverse(&self, visit: PyVisit) -> Result<(), PyTraverseError> {
+        if let Some(module) = &*self.module.lock().unwrap() {
+            visit.call(module)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&self) {
+        self.module.lock().unwrap().take();
+    }
+}
+
+impl Drop for Cyclic {
+    fn drop(&mut self) {
+        DROPPED.store(true, Ordering::SeqCst);
+    }
+}
+
+#[pymodule]
+#[pyo3(multi_phase)]
+fn cyclic(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.set_state(Cyclic {
+        module: Mutex::new(Some(m.into())),
+    })
+}
+
+#[test]
+fn test_module_state_gc() {
+    Python::with_gil(|py| {
+        drop(pyo3::wrap_pymodule!(cyclic)(py));
+        assert!(!DROPPED.load(Ordering::SeqCst));
+        py.run("import gc; gc.collect()", None, None).unwrap();
+        assert!(DROPPED.load(Ordering::SeqCst));
+    });
+}
+
+#[test]
+#[cfg(not(PyPy))]
+fn test_multi_phase_module_in_sub_interpreter() {
+    use pyo3::interpreter::SubInterpreter;
+
+    Python::with_gil(|py| {
+        let sub = SubInterpreter::new(py).unwrap();
+        unsafe {
+            sub.run(py, |sub| {
+                let m = pyo3::wrap_pymodule!(counter)(sub);
+                py_assert!(sub, m, "m.increment() == 1");
+            })
+        };
+    });
+}
+
+#[pymodule]
+#[pyo3(multi_phase)]
+fn failing(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Err(PyRuntimeError::new_err("can't initialize"))
+}
+
+#[test]
+fn test_multi_phase_init_error() {
+    Python::with_gil(|py| {
+        let err = unsafe { pyo3::derive_utils::module_from_init(py, PyInit_failing()) }
+            .map(drop)
+            .unwrap_err();
+        assert!(err.is_instance::<PyRuntimeError>(py));
+        assert_eq!(err.to_string(), "RuntimeError: can't initialize");
+    });
+}
+
+#[cfg(Py_3_8)]
+struct PanicOnDrop;
+
+#[cfg(Py_3_8)]
+impl ModuleState for PanicOnDrop {}
+
+#[cfg(Py_3_8)]
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        panic!("dropping the state");
+    }
+}
+
+#[cfg(Py_3_8)]
+#[pymodule]
+#[pyo3(multi_phase)]
+fn panic_on_drop(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.set_state(PanicOnDrop)
+}
+
+#[test]
+#[cfg(Py_3_8)]
+fn test_module_state_drop_panic_is_unraisable() {
+    Python::with_gil(|py| {
+        let sys = py.import("sys").unwrap();
+        let hook = sys.getattr("unraisablehook").unwrap();
+        let seen = py
+            .eval(
+                "(lambda seen: (seen, lambda args: seen.append(args.exc_value)))([])",
+                None,
+                None,
+            )
+            .unwrap();
+        sys.setattr("unraisablehook", seen.get_item(1).unwrap())
+            .unwrap();
+
+        drop(pyo3::wrap_pymodule!(panic_on_drop)(py));
+
+        sys.setattr("unraisablehook", hook).unwrap();
+        let seen = seen.get_item(0).unwrap();
+        assert_eq!(seen.len().unwrap(), 1);
+        let err = seen.get_item(0).unwrap();
+        assert!(err.is_instance::<pyo3::panic::PanicException>().unwrap());
+        assert_eq!(err.str().unwrap().to_str().unwrap(), "dropping the state");
+    });
+}
@@ -0,0 +1,301 @@
+use pyo3::class::PyObjectProtocol;
+use pyo3::panic::PanicException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::wrap_pyfunction;
+
+const PANIC_LINE: u32 = line!() + 4;
+
+#[inline(never)]
+fn inner_fail(message: &str) {
+    panic!("{}", message);
+}
+
+#[pyfunction]
+fn fail(message: &str) {
+    inner_fail(message);
+}
+
+#[pyclass]
+struct Failing {}
+
+#[pymethods]
+impl Failing {
+    fn fail(&self) {
+        inner_fail("method");
+    }
+}
+
+fn globals<'py>(py: Python<'py>) -> &'py PyDict {
+    let globals = PyDict::new(py);
+    globals
+        .set_item("fail", wrap_pyfunction!(fail)(py).unwrap())
+        .unwrap();
+    globals
+        .set_item("failing", PyCell::new(py, Failing {}).unwrap())
+        .unwrap();
+    globals
+        .set_item("PanicException", py.get_type::<PanicException>())
+        .unwrap();
+    globals.set_item("PANIC_LINE", PANIC_LINE).unwrap();
+    py.run(
+        r#"
+import traceback
+
+def raised(f, *args):
+    try:
+        f(*args)
+    except PanicException as e:
+        return e
+    assert False
+"#,
+        Some(globals),
+        None,
+    )
+    .unwrap();
+    globals
+}
+
+#[test]
+fn test_panic_traceback() {
+    Python::with_gil(|py| {
+        let globals = globals(py);
+        // in the same test, as the setting is global
+        pyo3::panic::set_backtrace_capture(true);
+        py.run(
+            r#"
+for err, message in [(raised(fail, "oops"), "oops"), (raised(failing.fail), "method")]:
+    assert str(err) == message
+    frames = traceback.extract_tb(err.__traceback__)
+    # the innermost frame is where the panic happened
+    panic_frame = frames[-1]
+    assert panic_frame.filename.endswith("test_panic.rs"), panic_frame
+    assert panic_frame.lineno == PANIC_LINE, panic_frame
+    assert panic_frame.name in ("test_panic::inner_fail", "<panic>"), panic_frame
+    assert "panic!" in panic_frame.line
+    # PyO3's own frames are hidden
+    assert not any("pyo3" in frame.name for frame in frames), frames
+    assert err.rust_backtrace is None or "inner_fail" in err.rust_backtrace
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+
+        pyo3::panic::set_backtrace_capture(false);
+        let result = py.run(
+            r#"
+err = raised(fail, "oops")
+assert str(err) == "oops"
+assert not hasattr(err, "rust_backtrace")
+assert all(frame.filename == "<string>" for frame in traceback.extract_tb(err.__traceback__))
+"#,
+            Some(globals),
+            None,
+        );
+        pyo3::panic::set_backtrace_capture(true);
+        result.unwrap();
+    });
+}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as = pyo3::exceptions::PyRuntimeError")]
+fn fail_as_runtime_error() {
+    panic!("runtime error");
+}
+
+#[pyfunction]
+#[pyo3(panic = "abort")]
+fn abort_on_panic(x: i32) -> i32 {
+    x + 1
+}
+
+#[pyclass]
+struct Policies {}
+
+#[pymethods]
+impl Policies {
+    #[pyo3(panic = "raise_as = pyo3::exceptions::PyValueError")]
+    fn method(&self) {
+        panic!("method");
+    }
+
+    #[getter]
+    #[pyo3(panic = "raise_as = pyo3::exceptions::PyValueError")]
+    fn getter(&self) -> i32 {
+        panic!("getter");
+    }
+}
+
+#[pymodule]
+#[pyo3(panic = "raise_as = pyo3::exceptions::PyRuntimeError")]
+fn policies(_py: Python, m: &PyModule) -> PyResult<()> {
+    #[pyfn(m)]
+    fn module_default() {
+        panic!("module default");
+    }
+
+    #[pyfn(m)]
+    #[pyo3(panic = "raise")]
+    fn own_policy() {
+        panic!("own policy");
+    }
+
+    #[pyfn(m)]
+    async fn async_module_default() {
+        panic!("async module default");
+    }
+
+    m.add_function(wrap_pyfunction!(not_pyfn, m)?)?;
+    m.add_class::<ModuleClass>()?;
+    Ok(())
+}
+
+/// Also covered by the `panic` option of the module, which adds it.
+#[pyfunction]
+fn not_pyfn() {
+    panic!("not pyfn");
+}
+
+#[pyclass]
+struct ModuleClass {}
+
+#[pymethods]
+impl ModuleClass {
+    #[new]
+    fn new() -> Self {
+        ModuleClass {}
+    }
+
+    fn method(&self) {
+        panic!("class method");
+    }
+
+    async fn async_method(&self) {
+        panic!("async class method");
+    }
+
+    #[pyo3(panic = "raise")]
+    fn own_policy(&self) {
+        panic!("own policy");
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for ModuleClass {
+    fn __repr__(&self) -> String {
+        panic!("slot");
+    }
+}
+
+#[pyclass(extends = pyo3::exceptions::PyException)]
+struct CustomPanic {}
+
+#[pymethods]
+impl CustomPanic {
+    #[new]
+    fn new(_message: String) -> Self {
+        CustomPanic {}
+    }
+}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as = CustomPanic")]
+fn fail_as_custom_exception() {
+    panic!("custom");
+}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as = pyo3::exceptions::PyRuntimeError")]
+async fn async_fail_as_runtime_error() {
+    panic!("async runtime error");
+}
+
+#[test]
+fn test_panic_policies() {
+    Python::with_gil(|py| {
+        let globals = globals(py);
+        globals
+            .set_item(
+                "fail_as_runtime_error",
+                wrap_pyfunction!(fail_as_runtime_error)(py).unwrap(),
+            )
+            .unwrap();
+        globals
+            .set_item(
+                "abort_on_panic",
+                wrap_pyfunction!(abort_on_panic)(py).unwrap(),
+            )
+            .unwrap();
+        globals
+            .set_item(
+                "fail_as_custom_exception",
+                wrap_pyfunction!(fail_as_custom_exception)(py).unwrap(),
+            )
+            .unwrap();
+        globals
+            .set_item(
+                "async_fail_as_runtime_error",
+                wrap_pyfunction!(async_fail_as_runtime_error)(py).unwrap(),
+            )
+            .unwrap();
+        globals
+            .set_item("CustomPanic", py.get_type::<CustomPanic>())
+            .unwrap();
+        globals
+            .set_item("policies", PyCell::new(py, Policies {}).unwrap())
+            .unwrap();
+        globals
+            .set_item("module", pyo3::wrap_pymodule!(policies)(py))
+            .unwrap();
+        py.run(
+            r#"
+def raised(f):
+    try:
+        f()
+    except BaseException as e:
+        return e
+    assert False
+
+err = raised(fail_as_runtime_error)
+assert type(err) is RuntimeError and str(err) == "runtime error", err
+
+assert abort_on_panic(1) == 2
+
+err = raised(policies.method)
+assert type(err) is ValueError and str(err) == "method", err
+err = raised(lambda: policies.getter)
+assert type(err) is ValueError and str(err) == "getter", err
+
+err = raised(module.module_default)
+assert type(err) is RuntimeError and str(err) == "module default", err
+err = raised(module.own_policy)
+assert type(err) is PanicException and str(err) == "own policy", err
+err = raised(module.not_pyfn)
+assert type(err) is RuntimeError and str(err) == "not pyfn", err
+
+instance = module.ModuleClass()
+err = raised(instance.method)
+assert type(err) is RuntimeError and str(err) == "class method", err
+err = raised(lambda: repr(instance))
+assert type(err) is RuntimeError and str(err) == "slot", err
+err = raised(instance.own_policy)
+assert type(err) is PanicException and str(err) == "own policy", err
+
+err = raised(fail_as_custom_exception)
+assert isinstance(err, CustomPanic), err
+
+# panics in the future of an `async fn` follow the same policies
+err = raised(lambda: async_fail_as_runtime_error().send(None))
+assert type(err) is RuntimeError and str(err) == "async runtime error", err
+err = raised(lambda: module.async_module_default().send(None))
+assert type(err) is RuntimeError and str(err) == "async module default", err
+err = raised(lambda: instance.async_method().send(None))
+assert type(err) is RuntimeError and str(err) == "async class method", err
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+    });
+}
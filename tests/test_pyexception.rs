@@ -0,0 +1,151 @@
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::{py_run, wrap_pyfunction};
+use std::fmt;
+
+mod common;
+
+#[derive(Debug, PyException)]
+#[pyo3(module = "storage")]
+enum StorageError {
+    NotFound {
+        path: String,
+    },
+    #[pyo3(name = "Quota")]
+    QuotaExceeded(u64, u64),
+    Corrupted {
+        offset: usize,
+        #[pyo3(skip)]
+        #[allow(dead_code)]
+        source: std::io::Error,
+        #[pyo3(name = "detail")]
+        reason: String,
+    },
+    ReadOnly,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::NotFound { path } => write!(f, "{} not found", path),
+            StorageError::QuotaExceeded(used, limit) => {
+                write!(f, "used {} of {} bytes", used, limit)
+            }
+            StorageError::Corrupted { offset, reason, .. } => {
+                write!(f, "corrupted at {}: {}", offset, reason)
+            }
+            StorageError::ReadOnly => write!(f, "storage is read-only"),
+        }
+    }
+}
+
+#[pyfunction]
+fn fail(kind: &str) -> Result<(), StorageError> {
+    Err(match kind {
+        "not_found" => StorageError::NotFound {
+            path: "data.bin".to_string(),
+        },
+        "quota" => StorageError::QuotaExceeded(120, 100),
+        "corrupted" => StorageError::Corrupted {
+            offset: 42,
+            source: std::io::ErrorKind::InvalidData.into(),
+            reason: "checksum mismatch".to_string(),
+        },
+        _ => StorageError::ReadOnly,
+    })
+}
+
+#[test]
+fn test_exception_hierarchy() {
+    Python::with_gil(|py| {
+        let base = py.get_type::<StorageError>();
+        assert!(base.is_subclass::<PyException>().unwrap());
+        py_run!(
+            py,
+            base,
+            r#"
+        assert base.__name__ == "StorageError"
+        assert base.__module__ == "storage"
+        for name in ["NotFound", "Quota", "Corrupted", "ReadOnly"]:
+            variant = getattr(base, name)
+            assert variant.__name__ == name
+            assert variant.__module__ == "storage"
+            assert issubclass(variant, base)
+        "#
+        );
+    });
+}
+
+#[test]
+fn test_raise_variants() {
+    Python::with_gil(|py| {
+        let fail = wrap_pyfunction!(fail)(py).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("fail", fail).unwrap();
+        globals
+            .set_item("StorageError", py.get_type::<StorageError>())
+            .unwrap();
+        py.run(
+            r#"
+def raised(kind):
+    try:
+        fail(kind)
+    except StorageError as e:
+        return e
+    assert False
+
+e = raised("not_found")
+assert type(e) is StorageError.NotFound
+assert str(e) == "data.bin not found"
+assert e.path == "data.bin"
+
+e = raised("quota")
+assert type(e) is StorageError.Quota
+assert str(e) == "used 120 of 100 bytes"
+assert (e._0, e._1) == (120, 100)
+
+e = raised("corrupted")
+assert str(e) == "corrupted at 42: checksum mismatch"
+assert e.offset == 42
+assert e.detail == "checksum mismatch"
+assert not hasattr(e, "source")
+
+e = raised("read_only")
+assert isinstance(e, StorageError.ReadOnly)
+assert e.args == ("storage is read-only",)
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+    });
+}
+
+#[derive(Debug, PyException)]
+#[pyo3(name = "ParseError", extends = PyValueError)]
+enum ParseErr {
+    Empty,
+}
+
+impl fmt::Display for ParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "empty input")
+    }
+}
+
+#[test]
+fn test_into_pyerr() {
+    Python::with_gil(|py| {
+        let err: PyErr = ParseErr::Empty.into();
+        assert!(err.is_instance::<ParseErr>(py));
+        assert!(err.is_instance::<PyValueError>(py));
+        assert_eq!(err.to_string(), "Empty: empty input");
+        let ty = py.get_type::<ParseErr>();
+        py_run!(
+            py,
+            ty,
+            "assert ty.__name__ == 'ParseError' and ty.__module__ == 'builtins'"
+        );
+    });
+}
@@ -213,3 +213,76 @@ fn test_conversion_error() {
         "argument 'option_arg': 'str' object cannot be interpreted as an integer"
     );
 }
+
+#[pyfunction]
+#[pyo3(signature = (a, b = 1, /, c = None, *args, d, e = vec![2], **kwargs))]
+fn function_with_signature(
+    a: i32,
+    b: i32,
+    c: Option<i32>,
+    args: &pyo3::types::PyTuple,
+    d: i32,
+    e: Vec<i32>,
+    kwargs: Option<&pyo3::types::PyDict>,
+) -> String {
+    format!(
+        "{} {} {:?} {} {} {:?} {}",
+        a,
+        b,
+        c,
+        args,
+        d,
+        e,
+        kwargs.map_or_else(|| "None".to_owned(), |kwargs| kwargs.to_string())
+    )
+}
+
+#[test]
+fn test_function_with_signature() {
+    Python::with_gil(|py| {
+        let f = wrap_pyfunction!(function_with_signature)(py).unwrap();
+        py_assert!(py, f, "f(1, d=4) == '1 1 None () 4 [2] None'");
+        py_assert!(
+            py,
+            f,
+            "f(1, 2, 3, 5, d=4, e=[6], g=7) == \"1 2 Some(3) (5,) 4 [6] {'g': 7}\""
+        );
+        py_assert!(py, f, "f(1, c=3, d=4) == '1 1 Some(3) () 4 [2] None'");
+        py_expect_exception!(
+            py,
+            f,
+            "f(a=1, d=4)",
+            PyTypeError,
+            "function_with_signature() got some positional-only arguments passed as keyword arguments: 'a'"
+        );
+        py_expect_exception!(
+            py,
+            f,
+            "f(1, 2)",
+            PyTypeError,
+            "function_with_signature() missing 1 required keyword argument: 'd'"
+        );
+    });
+}
+
+#[pyfunction]
+#[pyo3(signature = (py_value, count = 2, *, factor = Some(3)))]
+fn signature_with_python_arg(
+    py: Python,
+    py_value: &PyAny,
+    count: usize,
+    factor: Option<i64>,
+) -> PyResult<PyObject> {
+    let value: i64 = py_value.extract()?;
+    Ok((value * count as i64 * factor.unwrap_or(1)).into_py(py))
+}
+
+#[test]
+fn test_signature_with_python_arg() {
+    Python::with_gil(|py| {
+        let f = wrap_pyfunction!(signature_with_python_arg)(py).unwrap();
+        py_assert!(py, f, "f(1) == 6");
+        py_assert!(py, f, "f(1, 1, factor=None) == 1");
+        py_expect_exception!(py, f, "f(1, 1, 1)", PyTypeError);
+    });
+}
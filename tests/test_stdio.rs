@@ -0,0 +1,181 @@
+use pyo3::prelude::*;
+use pyo3::stdio::{capture, Redirect};
+use pyo3::types::PyDict;
+use pyo3::AsPyPointer;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_capture() {
+    Python::with_gil(|py| {
+        let ((), output) = capture(py, || {
+            py.run(
+                "import sys; print('out'); print('err', file=sys.stderr)",
+                None,
+                None,
+            )
+            .unwrap();
+            py.run("1 / 0", None, None).unwrap_err().print(py);
+        })
+        .unwrap();
+        assert_eq!(output.stdout, "out\n");
+        assert!(
+            output.stderr.starts_with("err\nTraceback"),
+            "{}",
+            output.stderr
+        );
+        assert!(output
+            .stderr
+            .ends_with("ZeroDivisionError: division by zero\n"));
+    });
+}
+
+/// A `Write` implementation which can be inspected after being handed to `Redirect`.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_redirect_restores_streams() {
+    Python::with_gil(|py| {
+        let sys = py.import("sys").unwrap();
+        let stdout = sys.getattr("stdout").unwrap();
+        let stderr = sys.getattr("stderr").unwrap();
+        let buffer = SharedBuffer::default();
+        let globals = PyDict::new(py);
+
+        Redirect::new()
+            .stdout(buffer.clone())
+            .run(py, || {
+                assert!(sys.getattr("stderr").unwrap().as_ptr() == stderr.as_ptr());
+                py.run(
+                    r#"
+import sys
+stream = sys.stdout
+assert not stream.closed and stream.writable() and not stream.isatty()
+assert stream.write("héllo\n") == 6
+stream.writelines(["a", "b\n"])
+"#,
+                    Some(globals),
+                    None,
+                )
+                .unwrap();
+            })
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+            "héllo\nab\n"
+        );
+        assert!(sys.getattr("stdout").unwrap().as_ptr() == stdout.as_ptr());
+        // Python code holding on to the replacement can't write to the sink any more
+        py.run(
+            r#"
+assert stream.closed
+try:
+    stream.write("late")
+except ValueError:
+    pass
+else:
+    assert False
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn test_redirect_callback_errors_and_panics() {
+    Python::with_gil(|py| {
+        let sys = py.import("sys").unwrap();
+        let stdout = sys.getattr("stdout").unwrap();
+
+        let err = Redirect::new()
+            .stdout_fn(|_| Err(std::io::ErrorKind::Other.into()))
+            .run(py, || py.run("print('lost')", None, None))
+            .unwrap()
+            .unwrap_err();
+        assert!(err.is_instance::<pyo3::exceptions::PyOSError>(py));
+
+        let result = std::panic::catch_unwind(|| {
+            Redirect::new()
+                .stdout_fn(|_| Ok(()))
+                .run(py, || panic!("in scope"))
+                .unwrap();
+        });
+        assert!(result.is_err());
+        assert!(sys.getattr("stdout").unwrap().as_ptr() == stdout.as_ptr());
+    });
+}
+
+#[test]
+fn test_redirect_leaves_later_replacement() {
+    Python::with_gil(|py| {
+        let sys = py.import("sys").unwrap();
+        let stdout = sys.getattr("stdout").unwrap();
+        let replacement = py.eval("__import__('io').StringIO()", None, None).unwrap();
+
+        Redirect::new()
+            .stdout(std::io::sink())
+            .run(py, || sys.setattr("stdout", replacement).unwrap())
+            .unwrap();
+        assert!(sys.getattr("stdout").unwrap().as_ptr() == replacement.as_ptr());
+
+        sys.setattr("stdout", stdout).unwrap();
+    });
+}
+
+#[test]
+fn test_redirect_guards_dropped_out_of_order() {
+    Python::with_gil(|py| {
+        let sys = py.import("sys").unwrap();
+        let stdout = sys.getattr("stdout").unwrap();
+
+        let first = Redirect::new().stdout(std::io::sink()).install(py).unwrap();
+        let second = Redirect::new().stdout(std::io::sink()).install(py).unwrap();
+        // the second stream is still installed, so it is left in place
+        first.restore().unwrap();
+        assert!(sys.getattr("stdout").unwrap().as_ptr() != stdout.as_ptr());
+        // the first stream is closed, so it is skipped
+        drop(second);
+        assert!(sys.getattr("stdout").unwrap().as_ptr() == stdout.as_ptr());
+        py.run("print(end='')", None, None).unwrap();
+    });
+}
+
+#[test]
+fn test_redirect_sink_calling_python() {
+    Python::with_gil(|py| {
+        let output = Arc::new(Mutex::new(String::new()));
+        let sink_output = output.clone();
+
+        Redirect::new()
+            .stdout_fn(move |text| {
+                Python::with_gil(|py| {
+                    // The stream isn't borrowed while the sink runs
+                    py.run("import sys; assert not sys.stdout.closed", None, None)
+                        .unwrap();
+                    let err = py.run("print('nested')", None, None).unwrap_err();
+                    assert!(err.is_instance::<pyo3::exceptions::PyRuntimeError>(py));
+                });
+                sink_output.lock().unwrap().push_str(text);
+                Ok(())
+            })
+            .run(py, || py.run("print('outer')", None, None))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*output.lock().unwrap(), "outer\n");
+    });
+}
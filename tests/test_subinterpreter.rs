@@ -0,0 +1,148 @@
+#![cfg(not(PyPy))]
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyImportError, PyRuntimeError};
+use pyo3::interpreter::SubInterpreter;
+use pyo3::panic::PanicException;
+use pyo3::prelude::*;
+use pyo3::type_object::PyTypeObject;
+use pyo3::AsPyPointer;
+
+create_exception!(plugin_host, PluginError, PyException);
+
+#[pyclass]
+struct Plugin {}
+
+#[pyfunction]
+fn new_plugin() -> Plugin {
+    Plugin {}
+}
+
+#[pyfunction]
+fn new_plugins() -> (Vec<Plugin>, Option<Plugin>) {
+    (vec![Plugin {}], Some(Plugin {}))
+}
+
+#[pymodule]
+fn plugin_host(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Plugin>()
+}
+
+#[test]
+fn test_sub_interpreter_is_isolated() {
+    Python::with_gil(|py| {
+        py.run("import json; shared = 1", None, None).unwrap();
+
+        let sub = SubInterpreter::new(py).unwrap();
+        unsafe {
+            sub.run(py, |sub| {
+                let found: bool = sub
+                    .eval("'shared' in globals() or 'json' in globals()", None, None)
+                    .unwrap()
+                    .extract()
+                    .unwrap();
+                assert!(!found);
+                sub.run("import array; private = 2", None, None).unwrap();
+            })
+        };
+
+        // state is kept between runs
+        let private: i32 =
+            unsafe { sub.run(py, |sub| sub.eval("private", None, None)?.extract()) }.unwrap();
+        assert_eq!(private, 2);
+
+        let leaked: bool = py
+            .eval("'private' in globals()", None, None)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert!(!leaked);
+        py.run("assert shared == 1", None, None).unwrap();
+    });
+}
+
+#[test]
+fn test_with_gil_in_sub_interpreter() {
+    Python::with_gil(|py| {
+        let sub = SubInterpreter::new(py).unwrap();
+        unsafe {
+            sub.run(py, |_| {
+                // the GIL is already held, and must not be acquired again
+                Python::with_gil(|inner| inner.run("x = 1", None, None).unwrap());
+            })
+        };
+    });
+}
+
+#[test]
+fn test_pyclass_refuses_sub_interpreter() {
+    Python::with_gil(|py| {
+        let sub = SubInterpreter::new(py).unwrap();
+        unsafe {
+            sub.run(py, |sub| {
+                let err = Py::new(sub, Plugin {}).unwrap_err();
+                assert!(err.is_instance::<PyRuntimeError>(sub));
+
+                // returning the class, also inside containers, raises the error instead of panicking
+                for function in &[
+                    pyo3::wrap_pyfunction!(new_plugin)(sub).unwrap(),
+                    pyo3::wrap_pyfunction!(new_plugins)(sub).unwrap(),
+                ] {
+                    let err = function.call0().unwrap_err();
+                    assert!(err.is_instance::<PyRuntimeError>(sub));
+                }
+            })
+        };
+
+        // the main interpreter is still active and can use the class
+        Py::new(py, Plugin {}).unwrap();
+    });
+}
+
+#[test]
+fn test_pymodule_refuses_sub_interpreter() {
+    Python::with_gil(|py| {
+        let sub = SubInterpreter::new(py).unwrap();
+        unsafe {
+            sub.run(py, |sub| {
+                let err = sub
+                    .from_owned_ptr_or_err::<PyModule>(PyInit_plugin_host())
+                    .unwrap_err();
+                assert!(err.is_instance::<PyImportError>(sub));
+            })
+        };
+    });
+}
+
+#[test]
+fn test_exception_types_per_interpreter() {
+    Python::with_gil(|py| {
+        let main_types = [
+            PluginError::type_object(py).as_ptr() as usize,
+            PanicException::type_object(py).as_ptr() as usize,
+        ];
+
+        let sub = SubInterpreter::new(py).unwrap();
+        let sub_types = unsafe {
+            sub.run(py, |sub| {
+                let err = PluginError::new_err("failed");
+                assert!(err.is_instance::<PluginError>(sub));
+                let types = [
+                    PluginError::type_object(sub).as_ptr() as usize,
+                    PanicException::type_object(sub).as_ptr() as usize,
+                ];
+                // cached for the rest of the sub-interpreter's life
+                assert_eq!(types[0], PluginError::type_object(sub).as_ptr() as usize);
+                types
+            })
+        };
+        assert_ne!(main_types[0], sub_types[0]);
+        assert_ne!(main_types[1], sub_types[1]);
+        drop(sub);
+
+        assert_eq!(
+            main_types[0],
+            PluginError::type_object(py).as_ptr() as usize
+        );
+    });
+}
@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
-use pyo3::{types::PyType, wrap_pymodule, PyCell};
+use pyo3::types::{PyDict, PyTuple, PyType};
+use pyo3::{wrap_pymodule, PyCell};
 
 mod common;
 
@@ -220,3 +221,160 @@ fn test_raw_identifiers() {
         "typeobj.method.__text_signature__ == '($self)'"
     );
 }
+
+#[test]
+fn test_auto_function() {
+    #[pyfunction(a, b = "None", "*", c = 42)]
+    fn my_function(a: i32, b: Option<i32>, c: i32) {
+        let _ = (a, b, c);
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (a, /, b = "foo", *args, c = -1.5, d = Vec::new(), **kwargs))]
+    fn python_signature(
+        a: i32,
+        b: &str,
+        args: &PyTuple,
+        c: f64,
+        d: Vec<i32>,
+        kwargs: Option<&PyDict>,
+    ) {
+        let _ = (a, b, args, c, d, kwargs);
+    }
+
+    #[pyfunction]
+    fn no_args(py: Python) {
+        let _ = py;
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+    py_assert!(py, f, "f.__text_signature__ == '(a, b=None, *, c=42)'");
+
+    let f = wrap_pyfunction!(python_signature)(py).unwrap();
+    py_assert!(
+        py,
+        f,
+        "f.__text_signature__ == '(a, /, b=\"foo\", *args, c=-1.5, d=..., **kwargs)'"
+    );
+
+    let f = wrap_pyfunction!(no_args)(py).unwrap();
+    py_assert!(py, f, "f.__text_signature__ == '()'");
+    py_assert!(py, f, "f.__doc__ is None");
+}
+
+#[test]
+fn test_auto_methods() {
+    #[pyclass]
+    struct MyClass {}
+
+    #[pymethods]
+    impl MyClass {
+        /// docs
+        fn method(&self, a: i32, b: Option<i32>) {
+            let _ = (a, b);
+        }
+        #[classmethod]
+        #[args(c = "true")]
+        fn class_method(_cls: &PyType, c: bool) {
+            let _ = c;
+        }
+        #[staticmethod]
+        #[args(d, args = "*")]
+        fn static_method(d: i32, args: &PyTuple) {
+            let _ = (d, args);
+        }
+        #[pyo3(text_signature = "($self, x)")]
+        fn overridden(&self, e: i32) {
+            let _ = e;
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let typeobj = py.get_type::<MyClass>();
+
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.method.__text_signature__ == '($self, a, b=None)'"
+    );
+    py_assert!(py, typeobj, "typeobj.method.__doc__ == 'docs'");
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.class_method.__text_signature__ == '($cls, c=True)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.static_method.__text_signature__ == '(d, *args)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.overridden.__text_signature__ == '($self, x)'"
+    );
+}
+
+#[test]
+#[cfg_attr(all(Py_LIMITED_API, not(Py_3_10)), ignore)]
+fn test_auto_class() {
+    /// docs
+    #[pyclass]
+    struct MyClass {}
+
+    #[pymethods]
+    impl MyClass {
+        #[new]
+        #[args(a, b = "None", "*", c = 42)]
+        fn new(a: i32, b: Option<i32>, c: i32) -> Self {
+            let _ = (a, b, c);
+            Self {}
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let typeobj = py.get_type::<MyClass>();
+
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.__text_signature__ == '(a, b=None, *, c=42)'"
+    );
+    py_assert!(py, typeobj, "typeobj.__doc__ == 'docs'");
+}
+
+#[test]
+fn test_docstring_signature() {
+    /// my_function(a, b, /)
+    /// --
+    ///
+    /// docs
+    #[pyfunction]
+    fn my_function(a: i32, b: i32) {
+        let _ = (a, b);
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+    py_assert!(py, f, "f.__text_signature__ == '(a, b, /)'");
+    py_assert!(py, f, "f.__doc__ == 'docs'");
+}
+
+#[test]
+fn test_invalid_python_signature() {
+    // A required positional argument can't follow an optional one in Python
+    #[pyfunction]
+    fn my_function(a: Option<i32>, b: i32) {
+        let _ = (a, b);
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+    py_assert!(py, f, "f.__text_signature__ is None");
+}
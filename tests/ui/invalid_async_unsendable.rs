@@ -0,0 +1,15 @@
+use pyo3::prelude::*;
+
+#[pyclass(unsendable)]
+struct NotThreadSafe {
+    data: std::rc::Rc<i32>,
+}
+
+#[pymethods]
+impl NotThreadSafe {
+    async fn get(&self) -> i32 {
+        *self.data
+    }
+}
+
+fn main() {}
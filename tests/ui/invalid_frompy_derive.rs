@@ -165,4 +165,38 @@ struct InvalidFromPyWithLiteral {
     field: String,
 }
 
+#[derive(FromPyObject)]
+struct FlattenWithItem {
+    #[pyo3(item, flatten)]
+    field: String,
+}
+
+#[derive(FromPyObject)]
+#[pyo3(rename_all = "camelCase")]
+struct RenameAllTuple(String, String);
+
+#[derive(FromPyObject)]
+#[pyo3(rename_all = "Title Case")]
+struct InvalidRenameAll {
+    field: String,
+}
+
+#[derive(FromPyObject)]
+#[pyo3(tag = "kind")]
+struct TaggedStruct {
+    field: String,
+}
+
+#[derive(FromPyObject)]
+#[pyo3(transparent)]
+enum TransparentEnum {
+    A(String),
+}
+
+#[derive(FromPyObject)]
+#[pyo3(attribute)]
+enum AttributeWithoutTag {
+    A { field: String },
+}
+
 fn main() {}
@@ -0,0 +1,36 @@
+use pyo3::prelude::*;
+
+#[pyclass(frozen)]
+pub struct Foo {
+    #[pyo3(get)]
+    field: u32,
+}
+
+#[pymethods]
+impl Foo {
+    fn mut_method(&mut self) {}
+}
+
+fn borrow_mut_fails(foo: Py<Foo>, py: Python) {
+    let borrow = foo.as_ref(py).borrow_mut();
+}
+
+#[pyclass(frozen)]
+pub struct FrozenWithSetter {
+    #[pyo3(set)]
+    field: u32,
+}
+
+// enums with fields are always frozen
+#[pyclass]
+#[derive(Clone)]
+pub enum Shape {
+    Circle { radius: f64 },
+}
+
+#[pymethods]
+impl Shape {
+    fn grow(&mut self) {}
+}
+
+fn main() {}
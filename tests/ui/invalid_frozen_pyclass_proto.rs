@@ -0,0 +1,35 @@
+use pyo3::class::{PyGCProtocol, PyIterProtocol, PyObjectProtocol};
+use pyo3::prelude::*;
+use pyo3::PyRefMut;
+
+#[pyclass(frozen, gc)]
+pub struct Foo {
+    field: u32,
+}
+
+#[pyproto]
+impl PyObjectProtocol for Foo {
+    fn __setattr__(&mut self, _name: &str, _value: u32) {}
+
+    fn __str__(&self) -> String {
+        self.field.to_string()
+    }
+}
+
+#[pyproto]
+impl PyIterProtocol for Foo {
+    fn __iter__(slf: PyRefMut<Self>) -> Py<Foo> {
+        slf.into()
+    }
+}
+
+#[pyproto]
+impl PyGCProtocol for Foo {
+    fn __traverse__(&self, _visit: pyo3::PyVisit) -> Result<(), pyo3::PyTraverseError> {
+        Ok(())
+    }
+
+    fn __clear__(&mut self) {}
+}
+
+fn main() {}
@@ -0,0 +1,64 @@
+use pyo3::prelude::*;
+
+#[derive(IntoPyObject)]
+#[pyo3(annotation = "Foo")]
+struct AnnotatedStruct {
+    a: String,
+}
+
+#[derive(IntoPyObject)]
+enum AnnotatedVariant {
+    #[pyo3(annotation = "str")]
+    Str(String),
+}
+
+fn extract_len(obj: &PyAny) -> PyResult<usize> {
+    obj.len()
+}
+
+#[derive(IntoPyObject)]
+struct FromPyWithField {
+    #[pyo3(item, from_py_with = "extract_len")]
+    len: usize,
+}
+
+#[derive(IntoPyObject)]
+struct FromPyWithTupleField(#[pyo3(from_py_with = "extract_len")] usize);
+
+#[derive(IntoPyObject)]
+struct MixedFields {
+    a: String,
+    #[pyo3(item)]
+    b: String,
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(tag = "kind")]
+enum TaggedAttributes {
+    Circle { radius: f64 },
+}
+
+#[derive(IntoPyObject)]
+struct Point(f64, f64);
+
+#[derive(IntoPyObject)]
+struct FlattenedTuple {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(flatten)]
+    point: Point,
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(tag = "kind")]
+enum TaggedNewtype {
+    Point(Point),
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(tag = "kind", attribute)]
+enum AttributeTagged {
+    Point { x: f64, y: f64 },
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use pyo3::prelude::*;
+
+#[pyfunction]
+#[pyo3(panic = "ignore")]
+fn unknown_policy() {}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as")]
+fn raise_as_without_type() {}
+
+#[pyfunction]
+#[pyo3(panic = "abort", panic = "raise")]
+fn duplicate_policy() {}
+
+#[pymodule]
+#[pyo3(panic = "raise_as = 1")]
+fn invalid_module_policy(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Ok(())
+}
+
+fn main() {}
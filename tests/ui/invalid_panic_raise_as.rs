@@ -0,0 +1,14 @@
+use pyo3::prelude::*;
+
+#[pyclass]
+struct NotAnException {}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as = NotAnException")]
+fn raise_as_class() {}
+
+#[pyfunction]
+#[pyo3(panic = "raise_as = pyo3::types::PyDict")]
+fn raise_as_native_type() {}
+
+fn main() {}
@@ -21,4 +21,30 @@ struct InvalidModule {}
 #[pyclass(weakrev)]
 struct InvalidArg {}
 
+#[pyclass(ord)]
+struct OrdWithoutEq {}
+
+#[pyclass(eq, hash)]
+struct HashWithEq {}
+
+#[pyclass(hash)]
+struct HashWithoutEq {}
+
+#[pyclass(get_all)]
+struct UselessGet {
+    #[pyo3(get)]
+    field: i32,
+}
+
+#[pyclass]
+struct UselessSkip {
+    #[pyo3(skip)]
+    field: i32,
+}
+
+#[pyclass(frozen, set_all)]
+struct FrozenSetAll {
+    field: i32,
+}
+
 fn main() {}
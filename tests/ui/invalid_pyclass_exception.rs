@@ -0,0 +1,10 @@
+use pyo3::prelude::*;
+
+#[pyclass]
+struct NotAnException {}
+
+fn raise() -> PyResult<()> {
+    Err(PyClassInitializer::from(NotAnException {}).into())
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+#![allow(unused_variables)]
+
+use pyo3::prelude::*;
+
+#[pyfunction]
+#[pyo3(signature = ())]
+fn function_with_one_argument_empty_signature(_x: i32) {}
+
+#[pyfunction]
+#[pyo3(signature = (x))]
+fn function_with_one_entry_signature_no_args() {}
+
+#[pyfunction]
+#[pyo3(signature = (x, y))]
+fn function_with_swapped_arguments(y: i32, x: i32) {}
+
+#[pyfunction]
+#[pyo3(signature = (x, /, /))]
+fn function_with_duplicate_slash(x: i32) {}
+
+#[pyfunction]
+#[pyo3(signature = (x = 1, y))]
+fn function_with_default_before_required(x: i32, y: i32) {}
+
+#[pyfunction]
+#[pyo3(signature = (*args, /, x))]
+fn function_with_slash_after_args(args: &pyo3::types::PyTuple, x: i32) {}
+
+#[pyclass]
+struct MyClass;
+
+#[pymethods]
+impl MyClass {
+    #[args(x = "1")]
+    #[pyo3(signature = (x = 1))]
+    fn method_with_args_and_signature(&self, x: i32) {}
+}
+
+fn main() {}
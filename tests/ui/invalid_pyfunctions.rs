@@ -6,7 +6,4 @@ fn generic_function<T>(value: T) {}
 #[pyfunction]
 fn impl_trait_function(impl_trait: impl AsRef<PyAny>) {}
 
-#[pyfunction]
-async fn async_function() {}
-
 fn main() {}
@@ -105,7 +105,15 @@ impl MyClass {
 
 #[pymethods]
 impl MyClass {
-    async fn async_method(&self) {}
+    #[getter]
+    async fn async_getter(&self) {}
+}
+
+#[pymethods]
+impl MyClass {
+    async fn __repr__(&self) -> String {
+        String::new()
+    }
 }
 
 #[pymethods]